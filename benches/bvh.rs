@@ -0,0 +1,69 @@
+use hutspot::bvh::Bvh;
+use hutspot::geom::{Vector3D, distance_to_triangle};
+use hutspot::timer::bench;
+use std::hint::black_box;
+
+// Pseudo-random point in [-10, 110]^2 x [-10, 10], from a linear congruential generator.
+fn query(i: usize) -> Vector3D {
+    let mut state = (i as u64).wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+    let mut next = || {
+        state = state.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+        (state >> 11) as f64 / (1u64 << 53) as f64
+    };
+    Vector3D::new(next() * 120. - 10., next() * 120. - 10., next() * 20. - 10.)
+}
+
+fn main() {
+    let n = 100;
+    let point = |i: usize, j: usize| Vector3D::new(i as f64, j as f64, ((i as f64) * 0.3).sin() * ((j as f64) * 0.2).cos());
+    let triangles = (0..n)
+        .flat_map(|i| {
+            (0..n).flat_map(move |j| {
+                [
+                    (point(i, j), point(i + 1, j), point(i + 1, j + 1)),
+                    (point(i, j), point(i + 1, j + 1), point(i, j + 1)),
+                ]
+            })
+        })
+        .collect::<Vec<_>>();
+    println!("{} triangles", triangles.len());
+
+    bench(
+        |_| {
+            black_box(Bvh::new(triangles.clone()));
+        },
+        "build",
+        10,
+    );
+    let bvh = Bvh::new(triangles.clone());
+
+    bench(
+        |i| {
+            let p = query(i);
+            black_box(triangles.iter().map(|&t| distance_to_triangle(p, t)).fold(f64::INFINITY, f64::min));
+        },
+        "closest point (brute force)",
+        100,
+    );
+    bench(
+        |i| {
+            black_box(bvh.closest_point(query(i)));
+        },
+        "closest point (bvh)",
+        100,
+    );
+    bench(
+        |i| {
+            black_box(bvh.k_nearest(query(i), 10));
+        },
+        "10 nearest (bvh)",
+        100,
+    );
+    bench(
+        |i| {
+            black_box(bvh.cast_ray(query(i) + Vector3D::new(0., 0., 20.), Vector3D::new(0.1, 0.2, -1.)));
+        },
+        "ray cast (bvh)",
+        100,
+    );
+}
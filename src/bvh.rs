@@ -0,0 +1,258 @@
+use crate::geom::{Aabb, TriangleFeature, Vector3D, closest_point_on_triangle, ray_aabb_intersection, ray_triangle_intersection_watertight};
+use ordered_float::OrderedFloat;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+type Triangle = (Vector3D, Vector3D, Vector3D);
+
+// Maximum number of triangles in a leaf.
+const LEAF_SIZE: usize = 4;
+
+#[derive(Debug, Clone, Copy)]
+enum NodeKind {
+    Leaf { start: usize, end: usize },
+    Inner { left: usize, right: usize },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    aabb: Aabb,
+    kind: NodeKind,
+}
+
+/// The closest point on a triangle of a `Bvh` to a query point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClosestPoint {
+    /// Index of the triangle (in the order given to `Bvh::new`).
+    pub triangle: usize,
+    pub point: Vector3D,
+    /// Barycentric coordinates of `point` with respect to the triangle.
+    /// These are the ones `closest_point_on_triangle` finds along the way, rather than `calculate_barycentric_coordinates` of `point`:
+    /// that would repeat the projection, and on degenerate triangles it parameterizes along edge `ab` or `bc` instead of the edge that holds `point`.
+    pub barycentric: (f64, f64, f64),
+    /// The feature of the triangle on which `point` lies.
+    pub feature: TriangleFeature,
+    pub distance: f64,
+}
+
+/// The first intersection of a ray with the triangles of a `Bvh`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    /// Index of the triangle (in the order given to `Bvh::new`).
+    pub triangle: usize,
+    /// Ray parameter of the hit, such that `point = origin + t * direction`.
+    pub t: f64,
+    pub point: Vector3D,
+    /// Barycentric coordinates of `point` with respect to the triangle.
+    pub barycentric: (f64, f64, f64),
+}
+
+/// A bounding volume hierarchy (AABB tree) over a triangle soup, for closest-point, k-nearest, and ray queries.
+///
+/// The tree is built top-down by splitting the triangles at the median centroid along the longest axis, until at most four triangles remain per leaf.
+///
+/// # Example
+/// ```
+/// use hutspot::bvh::Bvh;
+/// use hutspot::geom::{distance_to_triangle, Vector3D};
+///
+/// // A bumpy 20x20 grid.
+/// let height = |x: f64, y: f64| (x * 0.7).sin() * (y * 0.3).cos();
+/// let point = |i: usize, j: usize| Vector3D::new(i as f64, j as f64, height(i as f64, j as f64));
+/// let triangles = (0..20)
+///     .flat_map(|i| (0..20).flat_map(move |j| [(point(i, j), point(i + 1, j), point(i + 1, j + 1)), (point(i, j), point(i + 1, j + 1), point(i, j + 1))]))
+///     .collect::<Vec<_>>();
+/// let bvh = Bvh::new(triangles.clone());
+///
+/// for query in [Vector3D::new(3.3, 7.1, 2.), Vector3D::new(-4., 25., 0.), Vector3D::new(10.5, 10.5, -1.)] {
+///     let brute_force = triangles.iter().map(|&t| distance_to_triangle(query, t)).fold(f64::INFINITY, f64::min);
+///     let closest = bvh.closest_point(query).unwrap();
+///     assert!((closest.distance - brute_force).abs() < 1e-9);
+///     assert!(((closest.point - query).norm() - brute_force).abs() < 1e-9);
+///
+///     let nearest = bvh.k_nearest(query, 5);
+///     assert_eq!(nearest.len(), 5);
+///     assert_eq!(nearest[0].distance, closest.distance);
+///     assert!(nearest.windows(2).all(|w| w[0].distance <= w[1].distance));
+/// }
+///
+/// let hit = bvh.cast_ray(Vector3D::new(5.25, 5.5, 10.), Vector3D::new(0., 0., -1.)).unwrap();
+/// assert!((hit.point - Vector3D::new(5.25, 5.5, hit.point.z)).norm() < 1e-9);
+/// assert!((hit.t - (10. - hit.point.z)).abs() < 1e-9);
+/// assert!(bvh.cast_ray(Vector3D::new(5.25, 5.5, 10.), Vector3D::new(0., 0., 1.)).is_none());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Bvh {
+    triangles: Vec<Triangle>,
+    // Triangle indices, ordered such that every leaf covers a contiguous range.
+    order: Vec<usize>,
+    nodes: Vec<Node>,
+}
+
+impl Bvh {
+    /// Builds a hierarchy over `triangles`.
+    #[must_use]
+    pub fn new(triangles: Vec<Triangle>) -> Self {
+        let mut bvh = Self {
+            order: (0..triangles.len()).collect(),
+            nodes: Vec::with_capacity(2 * triangles.len() / LEAF_SIZE + 1),
+            triangles,
+        };
+        if !bvh.triangles.is_empty() {
+            bvh.build(0, bvh.triangles.len());
+        }
+        bvh
+    }
+
+    // Builds the subtree over `order[start..end]` and returns the index of its root.
+    fn build(&mut self, start: usize, end: usize) -> usize {
+        let index = self.nodes.len();
+        let aabb = self.order[start..end]
+            .iter()
+            .fold(Aabb::empty(), |aabb, &i| aabb.union(&Aabb::from_triangle(self.triangles[i])));
+        self.nodes.push(Node {
+            aabb,
+            kind: NodeKind::Leaf { start, end },
+        });
+
+        let centroid = |t: &Triangle| (t.0 + t.1 + t.2) / 3.;
+        let centroids = Aabb::from_points(self.order[start..end].iter().map(|&i| centroid(&self.triangles[i])));
+        let axis = centroids.extent().imax();
+        if end - start <= LEAF_SIZE || centroids.extent()[axis] <= 0. {
+            return index;
+        }
+
+        let mid = start + (end - start) / 2;
+        let triangles = &self.triangles;
+        self.order[start..end].select_nth_unstable_by(mid - start, |&i, &j| {
+            centroid(&triangles[i])[axis].total_cmp(&centroid(&triangles[j])[axis])
+        });
+        let left = self.build(start, mid);
+        let right = self.build(mid, end);
+        self.nodes[index].kind = NodeKind::Inner { left, right };
+        index
+    }
+
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.triangles.len()
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.triangles.is_empty()
+    }
+
+    #[must_use]
+    pub fn triangle(&self, i: usize) -> Triangle {
+        self.triangles[i]
+    }
+
+    /// The bounding box of all triangles, or `None` if there are none.
+    #[must_use]
+    pub fn aabb(&self) -> Option<Aabb> {
+        self.nodes.first().map(|node| node.aabb)
+    }
+
+    fn closest(&self, triangle: usize, p: Vector3D) -> ClosestPoint {
+        let closest = closest_point_on_triangle(p, self.triangles[triangle]);
+        ClosestPoint {
+            triangle,
+            point: closest.point,
+            barycentric: closest.barycentric,
+            feature: closest.feature,
+            distance: (p - closest.point).norm(),
+        }
+    }
+
+    /// Finds the closest point to `p` on any of the triangles.
+    /// # Arguments
+    /// * `p` - The query point.
+    /// # Returns
+    /// * `Option<ClosestPoint>` - The closest point, its triangle and barycentric coordinates, or `None` if there are no triangles.
+    #[must_use]
+    pub fn closest_point(&self, p: Vector3D) -> Option<ClosestPoint> {
+        self.k_nearest(p, 1).pop()
+    }
+
+    /// Finds the `k` triangles closest to `p`.
+    /// # Arguments
+    /// * `p` - The query point.
+    /// * `k` - The number of triangles to find.
+    /// # Returns
+    /// * `Vec<ClosestPoint>` - The closest point on each of the (at most) `k` closest triangles, ordered by increasing distance.
+    #[must_use]
+    pub fn k_nearest(&self, p: Vector3D, k: usize) -> Vec<ClosestPoint> {
+        if k == 0 || self.nodes.is_empty() {
+            return vec![];
+        }
+
+        // Max-heap of the best candidates so far, and min-heap of nodes to visit, both by squared distance.
+        let mut best: BinaryHeap<(OrderedFloat<f64>, usize)> = BinaryHeap::with_capacity(k + 1);
+        let mut queue = BinaryHeap::from([Reverse((OrderedFloat(self.nodes[0].aabb.distance_squared(p)), 0))]);
+        while let Some(Reverse((distance, node))) = queue.pop() {
+            if best.len() == k && best.peek().is_some_and(|&(worst, _)| distance > worst) {
+                break;
+            }
+            match self.nodes[node].kind {
+                NodeKind::Leaf { start, end } => {
+                    for &triangle in &self.order[start..end] {
+                        let q = closest_point_on_triangle(p, self.triangles[triangle]).point;
+                        best.push((OrderedFloat((p - q).norm_squared()), triangle));
+                        if best.len() > k {
+                            best.pop();
+                        }
+                    }
+                }
+                NodeKind::Inner { left, right } => {
+                    for child in [left, right] {
+                        queue.push(Reverse((OrderedFloat(self.nodes[child].aabb.distance_squared(p)), child)));
+                    }
+                }
+            }
+        }
+
+        best.into_sorted_vec()
+            .into_iter()
+            .map(|(_, triangle)| self.closest(triangle, p))
+            .collect()
+    }
+
+    /// Finds the first triangle hit by the ray from `origin` in direction `direction`.
+    /// # Arguments
+    /// * `origin` - The origin of the ray.
+    /// * `direction` - The direction of the ray (not necessarily normalized).
+    /// # Returns
+    /// * `Option<RayHit>` - The hit with the smallest non-negative ray parameter, or `None` if the ray misses all triangles.
+    ///
+    /// Both faces of the triangles are hit, and rays through shared edges are never missed, see `geom::ray_triangle_intersection_watertight`.
+    #[must_use]
+    pub fn cast_ray(&self, origin: Vector3D, direction: Vector3D) -> Option<RayHit> {
+        let mut hit: Option<RayHit> = None;
+        let mut stack = if self.nodes.is_empty() { vec![] } else { vec![0] };
+        while let Some(node) = stack.pop() {
+            let closest = hit.map_or(f64::INFINITY, |hit| hit.t);
+            if ray_aabb_intersection(origin, direction, &self.nodes[node].aabb).is_none_or(|(entry, _)| entry > closest) {
+                continue;
+            }
+            match self.nodes[node].kind {
+                NodeKind::Leaf { start, end } => {
+                    for &triangle in &self.order[start..end] {
+                        if let Some(triangle_hit) = ray_triangle_intersection_watertight(origin, direction, self.triangles[triangle], false)
+                            && hit.is_none_or(|hit| triangle_hit.t < hit.t)
+                        {
+                            hit = Some(RayHit {
+                                triangle,
+                                t: triangle_hit.t,
+                                point: origin + direction * triangle_hit.t,
+                                barycentric: triangle_hit.barycentric,
+                            });
+                        }
+                    }
+                }
+                NodeKind::Inner { left, right } => stack.extend([left, right]),
+            }
+        }
+        hit
+    }
+}
@@ -0,0 +1,326 @@
+use crate::geom::Vector3D;
+use crate::mesh::{EdgeID, FaceID, Mesh, MeshError, VertID};
+use ordered_float::OrderedFloat;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+// Parameters closer than this to the end of an edge snap to its endpoint while tracing.
+const SNAP: f64 = 1e-9;
+
+// A location on the surface while tracing a path: a vertex, or a point on halfedge `e` that is about to enter the face of `e`.
+#[derive(Debug, Clone, Copy)]
+enum Location {
+    Vert(VertID),
+    Edge(EdgeID, Vector3D, f64),
+}
+
+/// A triangle mesh for geodesic distance and shortest path queries.
+///
+/// Distances are approximated with the fast marching method (Kimmel and Sethian, Computing Geodesic Paths on Manifolds, 1998), where every triangle update unfolds the triangle into the plane and places a virtual point source.
+/// This is exact on developable regions (such as planes and cylinders) and a close approximation elsewhere.
+/// Paths are traced backwards from the target by descending the distance field across the faces, so they cut straight through the triangles instead of zigzagging along the edges.
+///
+/// # Example
+/// ```
+/// use hutspot::geodesic::GeodesicMesh;
+/// use hutspot::geom::Vector3D;
+///
+/// // A flat 10x10 grid, where the geodesic distance is the Euclidean distance.
+/// let positions = (0..=10).flat_map(|j| (0..=10).map(move |i| Vector3D::new(f64::from(i), f64::from(j), 0.))).collect::<Vec<_>>();
+/// let index = |i: usize, j: usize| j * 11 + i;
+/// let faces = (0..10)
+///     .flat_map(|j| (0..10).flat_map(move |i| [[index(i, j), index(i + 1, j), index(i + 1, j + 1)], [index(i, j), index(i + 1, j + 1), index(i, j + 1)]]))
+///     .collect::<Vec<_>>();
+/// let mesh = GeodesicMesh::new(positions.clone(), &faces).unwrap();
+///
+/// let distances = mesh.distances(&[index(0, 0)]).unwrap();
+/// for (p, d) in positions.iter().zip(&distances) {
+///     assert!((p.norm() - d).abs() < 1e-3 * p.norm().max(1.), "{p:?}: {d}");
+/// }
+/// assert!(mesh.distances(&[positions.len()]).is_none());
+/// assert!(mesh.shortest_path(index(0, 0), positions.len()).is_none());
+///
+/// // The path to (10, 3) is straight, while the shortest path along the edges has length 10 + 3 * (2f64.sqrt() - 1).
+/// let (distance, path) = mesh.shortest_path(index(0, 0), index(10, 3)).unwrap();
+/// let straight = Vector3D::new(10., 3., 0.).norm();
+/// let length = path.windows(2).map(|w| (w[1] - w[0]).norm()).sum::<f64>();
+/// assert!((distance - straight).abs() < 1e-9 && (length - straight).abs() < 1e-9, "{distance} {length}");
+/// assert_eq!(path.first(), Some(&positions[index(0, 0)]));
+/// assert_eq!(path.last(), Some(&positions[index(10, 3)]));
+/// assert!(path.iter().all(|p| (p.y * 10. - p.x * 3.).abs() < 1e-9));
+///
+/// // On a sphere, the distances approximate the great-circle distances.
+/// let (n, m) = (32, 16);
+/// let angle = |i: usize, n: usize| std::f64::consts::PI * i as f64 / n as f64;
+/// let mut positions = vec![Vector3D::new(0., 0., 1.), Vector3D::new(0., 0., -1.)];
+/// positions.extend((1..m).flat_map(|j| (0..n).map(move |k| Vector3D::new(angle(j, m).sin() * angle(2 * k, n).cos(), angle(j, m).sin() * angle(2 * k, n).sin(), angle(j, m).cos()))));
+/// let index = |k: usize, j: usize| 2 + (j - 1) * n + k % n;
+/// let mut faces = (0..n).flat_map(|k| [[0, index(k, 1), index(k + 1, 1)], [1, index(k + 1, m - 1), index(k, m - 1)]]).collect::<Vec<_>>();
+/// faces.extend((1..m - 1).flat_map(|j| (0..n).flat_map(move |k| [[index(k, j), index(k, j + 1), index(k + 1, j + 1)], [index(k, j), index(k + 1, j + 1), index(k + 1, j)]])));
+/// let mesh = GeodesicMesh::new(positions.clone(), &faces).unwrap();
+///
+/// let source = index(0, m / 2);
+/// let distances = mesh.distances(&[source]).unwrap();
+/// for (p, d) in positions.iter().zip(&distances) {
+///     assert!((positions[source].angle(p) - d).abs() < 0.05 * d.max(1.), "{p:?}: {d}");
+/// }
+/// let path = mesh.trace_path(&distances, 0).unwrap();
+/// assert_eq!((path.first(), path.last()), (Some(&positions[source]), Some(&positions[0])));
+/// let length = path.windows(2).map(|w| (w[1] - w[0]).norm()).sum::<f64>();
+/// assert!((length - distances[0]).abs() < 1e-2 && path.iter().all(|p| p.y.abs() < 0.05), "{length} {}", distances[0]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct GeodesicMesh {
+    mesh: Mesh<Vector3D>,
+    // The corners of every face, in the order of the faces of `mesh`.
+    triangles: Vec<[VertID; 3]>,
+}
+
+impl GeodesicMesh {
+    /// Builds a geodesic mesh from vertex positions and triangles.
+    /// # Arguments
+    /// * `positions` - The position of each vertex.
+    /// * `faces` - The triangles, as indices into `positions`.
+    /// # Returns
+    /// * `Result<Self, MeshError>` - The mesh, or an error if the triangles do not describe an oriented manifold (with boundary).
+    pub fn new(positions: Vec<Vector3D>, faces: &[[usize; 3]]) -> Result<Self, MeshError> {
+        let mesh = Mesh::from_faces(positions, &faces.iter().map(|face| face.to_vec()).collect::<Vec<_>>())?;
+        Ok(Self {
+            mesh,
+            triangles: faces.iter().map(|face| face.map(VertID)).collect(),
+        })
+    }
+
+    fn position(&self, v: VertID) -> Vector3D {
+        *self.mesh.vert_data(v)
+    }
+
+    // The corners of face `f`, in the order of its halfedges (as `Mesh::from_faces` keeps the order of the faces and their corners).
+    fn triangle(&self, f: FaceID) -> [VertID; 3] {
+        self.triangles[f.0]
+    }
+
+    /// Computes the geodesic distance from the nearest source to every vertex.
+    /// # Arguments
+    /// * `sources` - The source vertices (indices into the positions).
+    /// # Returns
+    /// * `Option<Vec<f64>>` - The distance of every vertex, which is infinite for vertices that are not connected to any source, or `None` if a source is not a vertex.
+    #[must_use]
+    pub fn distances(&self, sources: &[usize]) -> Option<Vec<f64>> {
+        if sources.iter().any(|&source| source >= self.mesh.nr_verts()) {
+            return None;
+        }
+        let mut distances = vec![f64::INFINITY; self.mesh.nr_verts()];
+        let mut frozen = vec![false; self.mesh.nr_verts()];
+        let mut queue = BinaryHeap::new();
+        for &source in sources {
+            distances[source] = 0.;
+            queue.push(Reverse((OrderedFloat(0.), source)));
+        }
+
+        while let Some(Reverse((OrderedFloat(distance), v))) = queue.pop() {
+            if frozen[v] || distance > distances[v] {
+                continue;
+            }
+            frozen[v] = true;
+            for f in self.mesh.vfaces(VertID(v)) {
+                let corners = self.triangle(f);
+                for (i, &VertID(c)) in corners.iter().enumerate() {
+                    if frozen[c] {
+                        continue;
+                    }
+                    let (VertID(a), VertID(b)) = (corners[(i + 1) % 3], corners[(i + 2) % 3]);
+                    let p = |x: usize| self.position(VertID(x));
+                    let mut candidate = [a, b]
+                        .iter()
+                        .filter(|&&x| frozen[x])
+                        .map(|&x| distances[x] + (p(x) - p(c)).norm())
+                        .fold(f64::INFINITY, f64::min);
+                    if frozen[a]
+                        && frozen[b]
+                        && let Some(unfolded) = triangle_update((p(a), distances[a]), (p(b), distances[b]), p(c))
+                    {
+                        candidate = candidate.min(unfolded);
+                    }
+                    if candidate < distances[c] {
+                        distances[c] = candidate;
+                        queue.push(Reverse((OrderedFloat(candidate), c)));
+                    }
+                }
+            }
+        }
+        Some(distances)
+    }
+
+    // The direction of steepest descent of the distance field at point `p` in face `f`.
+    // This points towards the virtual point source that explains the distances at the two lowest corners, unfolded into the plane of `f`, which is straight on flat regions.
+    // Without such a source, it falls back to the negated gradient of the linear interpolation of `distances` on `f`.
+    #[allow(clippy::many_single_char_names)]
+    fn descent(&self, f: FaceID, p: Vector3D, distances: &[f64]) -> Vector3D {
+        let mut corners = self.triangle(f).map(|v| (self.position(v), distances[v.0]));
+        let normal = (corners[1].0 - corners[0].0).cross(&(corners[2].0 - corners[0].0));
+        if normal.norm_squared() == 0. {
+            return Vector3D::zeros();
+        }
+        let gradient = (0..3)
+            .map(|i| normal.cross(&(corners[(i + 2) % 3].0 - corners[(i + 1) % 3].0)) * corners[i].1)
+            .sum::<Vector3D>()
+            / normal.norm_squared();
+
+        corners.sort_by(|x, y| x.1.total_cmp(&y.1));
+        let [(a, distance_a), (b, distance_b), (c, distance_c)] = corners;
+        let Some((source_x, source_y)) = virtual_source(distance_a, distance_b, (b - a).norm()) else {
+            return -gradient;
+        };
+        let x_axis = (b - a).normalize();
+        let y_axis = normal.cross(&x_axis).normalize();
+        // Of the two mirrored sources, pick the one that best explains the distance at the third corner.
+        let source = [1., -1.]
+            .map(|side| a + x_axis * source_x + y_axis * (side * source_y))
+            .into_iter()
+            .min_by(|s, t| ((s - c).norm() - distance_c).abs().total_cmp(&((t - c).norm() - distance_c).abs()))
+            .unwrap_or(a);
+        if source == p { -gradient } else { source - p }
+    }
+
+    // Intersects the ray from `origin` in direction `direction` with halfedge `e`, both in the plane of face `f`.
+    // Returns the point and its parameter along `e` (from its root), if the ray hits `e` ahead of `origin`.
+    #[allow(clippy::many_single_char_names)]
+    fn ray_edge(&self, f: FaceID, origin: Vector3D, direction: Vector3D, e: EdgeID) -> Option<(Vector3D, f64)> {
+        let [a, b, c] = self.triangle(f).map(|v| self.position(v));
+        let normal = (b - a).cross(&(c - a));
+        let (x, y) = (self.position(self.mesh.root(e)), self.position(self.mesh.toor(e)));
+        let denominator = normal.dot(&direction.cross(&(y - x)));
+        let t = normal.dot(&(x - origin).cross(&(y - x))) / denominator;
+        let s = normal.dot(&(x - origin).cross(&direction)) / denominator;
+        (t > 0. && (0. ..=1.).contains(&s)).then(|| (x + (y - x) * s, s))
+    }
+
+    // The distance at parameter `s` along halfedge `e`, interpolated linearly.
+    fn distance_along(&self, e: EdgeID, s: f64, distances: &[f64]) -> f64 {
+        let (u, v) = self.mesh.endpoints(e);
+        (distances[v.0] - distances[u.0]).mul_add(s, distances[u.0])
+    }
+
+    // The location of the point at parameter `s` along halfedge `e`, snapped to the nearest endpoint if it lies (nearly) on it. The point then enters the face of the twin of `e`.
+    fn crossing(&self, e: EdgeID, point: Vector3D, s: f64) -> Location {
+        if s < SNAP {
+            Location::Vert(self.mesh.root(e))
+        } else if s > 1. - SNAP {
+            Location::Vert(self.mesh.toor(e))
+        } else {
+            Location::Edge(self.mesh.twin(e), point, 1. - s)
+        }
+    }
+
+    // The steepest descending step from vertex `v`: either across one of its faces, or along one of its edges.
+    fn descend_from_vert(&self, v: VertID, distances: &[f64]) -> Option<Location> {
+        let origin = self.position(v);
+        let slope = |point: Vector3D, distance: f64| (distance - distances[v.0]) / (point - origin).norm();
+        let along_edges = self
+            .mesh
+            .vneighbors(v)
+            .into_iter()
+            .map(|w| (slope(self.position(w), distances[w.0]), Location::Vert(w)));
+        let across_faces = self.mesh.outgoing(v).into_iter().filter_map(|e| {
+            let f = self.mesh.face_of(e)?;
+            let opposite = self.mesh.next(e);
+            let (point, s) = self.ray_edge(f, origin, self.descent(f, origin, distances), opposite)?;
+            Some((
+                slope(point, self.distance_along(opposite, s, distances)),
+                self.crossing(opposite, point, s),
+            ))
+        });
+        along_edges
+            .chain(across_faces)
+            .filter(|(slope, _)| *slope < 0.)
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, location)| location)
+    }
+
+    // The descending step from the point `point` at parameter `s` along halfedge `e`: across the face of `e`, or to the lowest endpoint of `e`.
+    fn descend_from_edge(&self, e: EdgeID, point: Vector3D, s: f64, distances: &[f64]) -> Location {
+        let distance = self.distance_along(e, s, distances);
+        let across = self.mesh.face_of(e).and_then(|f| {
+            let direction = self.descent(f, point, distances);
+            [self.mesh.next(e), self.mesh.prev(e)].into_iter().find_map(|other| {
+                let (point, s) = self.ray_edge(f, point, direction, other)?;
+                (self.distance_along(other, s, distances) < distance).then(|| self.crossing(other, point, s))
+            })
+        });
+        across.unwrap_or_else(|| {
+            let (u, v) = self.mesh.endpoints(e);
+            Location::Vert(if distances[u.0] <= distances[v.0] { u } else { v })
+        })
+    }
+
+    /// Traces the geodesic path from vertex `target` back to the sources of `distances`, by descending the gradient of the distance field.
+    /// # Arguments
+    /// * `distances` - The distances computed by `distances`.
+    /// * `target` - The vertex (index into the positions) where the path ends.
+    /// # Returns
+    /// * `Option<Vec<Vector3D>>` - The path as a polyline from a source to `target`, whose interior points lie on the edges (or vertices) that it crosses, or `None` if `target` is not connected to any source.
+    ///   Also `None` if `target` is not a vertex, or `distances` does not have a distance for every vertex.
+    #[must_use]
+    pub fn trace_path(&self, distances: &[f64], target: usize) -> Option<Vec<Vector3D>> {
+        if distances.len() != self.mesh.nr_verts() || !distances.get(target)?.is_finite() {
+            return None;
+        }
+
+        let mut path = vec![self.position(VertID(target))];
+        let mut location = Location::Vert(VertID(target));
+        // Every step crosses a face or follows an edge, so this bounds the length of a descending path.
+        for _ in 0..=2 * (self.mesh.nr_faces() + self.mesh.nr_verts()) {
+            location = match location {
+                Location::Vert(v) if distances[v.0] == 0. => break,
+                Location::Vert(v) => match self.descend_from_vert(v, distances) {
+                    Some(next) => next,
+                    None => break,
+                },
+                Location::Edge(e, point, s) => self.descend_from_edge(e, point, s, distances),
+            };
+            path.push(match location {
+                Location::Vert(v) => self.position(v),
+                Location::Edge(_, point, _) => point,
+            });
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Computes the geodesic distance and path from vertex `source` to vertex `target`.
+    /// # Arguments
+    /// * `source` - The vertex (index into the positions) where the path starts.
+    /// * `target` - The vertex (index into the positions) where the path ends.
+    /// # Returns
+    /// * `Option<(f64, Vec<Vector3D>)>` - The geodesic distance and the path as a polyline from `source` to `target`, or `None` if they are not connected (or either is not a vertex).
+    #[must_use]
+    pub fn shortest_path(&self, source: usize, target: usize) -> Option<(f64, Vec<Vector3D>)> {
+        let distances = self.distances(&[source])?;
+        self.trace_path(&distances, target).map(|path| (distances[target], path))
+    }
+}
+
+// The position of a virtual point source at distances `distance_a` and `distance_b` from points `a` = (0, 0) and `b` = (`length`, 0), with a non-positive second coordinate, if it exists.
+fn virtual_source(distance_a: f64, distance_b: f64, length: f64) -> Option<(f64, f64)> {
+    if length == 0. {
+        return None;
+    }
+    let source_x = length.mul_add(length, distance_a.mul_add(distance_a, -distance_b * distance_b)) / (2. * length);
+    let source_y = -distance_a.mul_add(distance_a, -source_x * source_x).sqrt();
+    (!source_y.is_nan()).then_some((source_x, source_y))
+}
+
+// Computes the distance at `c` from the known distances at `a` and `b`, by unfolding the triangle into the plane and placing a virtual point source at the given distances from `a` and `b`, on the other side of edge `ab`.
+// Returns `None` if no such source exists, or if the straight line from it to `c` does not pass through edge `ab`.
+fn triangle_update((a, distance_a): (Vector3D, f64), (b, distance_b): (Vector3D, f64), c: Vector3D) -> Option<f64> {
+    let length = (b - a).norm();
+    let axis = (b - a) / length;
+    let (c_x, c_y) = ((c - a).dot(&axis), (c - a - axis * (c - a).dot(&axis)).norm());
+    if c_y == 0. || !c_y.is_finite() {
+        return None;
+    }
+    let (source_x, source_y) = virtual_source(distance_a, distance_b, length)?;
+    let crossing = (c_x - source_x).mul_add(-source_y / (c_y - source_y), source_x);
+    (0. ..=length).contains(&crossing).then(|| (c_x - source_x).hypot(c_y - source_y))
+}
@@ -1,5 +1,6 @@
 use crate::consts::EPS;
-use approx::AbsDiffEq;
+use crate::predicates;
+use itertools::Itertools;
 
 pub type Vector2D = nalgebra::SVector<f64, 2>;
 pub type Vector3D = nalgebra::SVector<f64, 3>;
@@ -12,6 +13,75 @@ pub enum Orientation {
     CCW, // Counterclockwise
 }
 
+impl Orientation {
+    /// Returns the opposite orientation (`C` stays `C`).
+    #[must_use]
+    pub const fn reversed(self) -> Self {
+        match self {
+            Self::C => Self::C,
+            Self::CW => Self::CCW,
+            Self::CCW => Self::CW,
+        }
+    }
+}
+
+/// An axis-aligned bounding box, given by its minimum and maximum corners.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Vector3D,
+    pub max: Vector3D,
+}
+
+impl Aabb {
+    #[must_use]
+    pub const fn new(min: Vector3D, max: Vector3D) -> Self {
+        Self { min, max }
+    }
+
+    /// The empty box, which is the identity for `union`.
+    #[must_use]
+    pub fn empty() -> Self {
+        Self::new(Vector3D::repeat(f64::INFINITY), Vector3D::repeat(f64::NEG_INFINITY))
+    }
+
+    /// The smallest box containing all `points`.
+    #[must_use]
+    pub fn from_points(points: impl IntoIterator<Item = Vector3D>) -> Self {
+        points.into_iter().fold(Self::empty(), |aabb, p| aabb.union(&Self::new(p, p)))
+    }
+
+    #[must_use]
+    pub fn from_triangle(t: (Vector3D, Vector3D, Vector3D)) -> Self {
+        Self::from_points([t.0, t.1, t.2])
+    }
+
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        Self::new(self.min.inf(&other.min), self.max.sup(&other.max))
+    }
+
+    #[must_use]
+    pub fn center(&self) -> Vector3D {
+        (self.min + self.max) * 0.5
+    }
+
+    #[must_use]
+    pub fn extent(&self) -> Vector3D {
+        self.max - self.min
+    }
+
+    #[must_use]
+    pub fn contains(&self, p: Vector3D) -> bool {
+        (0..3).all(|i| self.min[i] <= p[i] && p[i] <= self.max[i])
+    }
+
+    /// The squared distance from point `p` to the box (zero if `p` lies inside).
+    #[must_use]
+    pub fn distance_squared(&self, p: Vector3D) -> f64 {
+        (p - p.sup(&self.min).inf(&self.max)).norm_squared()
+    }
+}
+
 /// Calculates the area of a triangle `t` in 3D space.
 /// # Arguments
 /// * `t` - A tuple of three vertices representing the triangle.
@@ -37,10 +107,11 @@ pub fn calculate_triangle_area(t: (Vector3D, Vector3D, Vector3D)) -> f64 {
 ///
 /// # Source
 /// This method is based on the concept of coplanarity in vector mathematics, which can be determined using the scalar triple product. For more details, see [Wikipedia](https://en.wikipedia.org/wiki/Coplanarity).
+/// The sign of the triple product is evaluated exactly with `predicates::orient3d`.
 #[must_use]
 #[inline]
 pub fn are_points_coplanar(a: Vector3D, b: Vector3D, c: Vector3D, d: Vector3D) -> bool {
-    (b - a).cross(&(c - a)).dot(&(d - a)) == 0.
+    predicates::orient3d(a, b, c, d) == Orientation::C
 }
 
 /// Calculates the orientation of three points `a`, `b`, `c`, with normal `n`, in 3D space.
@@ -54,17 +125,30 @@ pub fn are_points_coplanar(a: Vector3D, b: Vector3D, c: Vector3D, d: Vector3D) -
 ///
 /// # Source
 /// This method is based on the right-hand rule for the cross product and the dot product to determine the relative orientation of the points in a plane. For more details, see [Wikipedia](https://en.wikipedia.org/wiki/Orientation_(vector_space)).
+/// The sign of `((b - a) x (c - a)) . n` is evaluated exactly with `predicates::orient3d_along`, so the result is consistent under permutation of `a`, `b`, `c`.
+///
+/// # Example
+/// ```
+/// use hutspot::geom::{calculate_orientation, Orientation, Vector3D};
+/// let n = Vector3D::new(0., 0., 1.);
+/// let (b, c) = (Vector3D::new(12., 12., 0.), Vector3D::new(24., 24., 0.));
+/// for i in 0..16 {
+///     for j in 0..16 {
+///         let a = Vector3D::new(0.5 + f64::from(i) * f64::EPSILON, 0.5 + f64::from(j) * f64::EPSILON, 0.);
+///         let orientation = calculate_orientation(a, b, c, n);
+///         assert_eq!(orientation == Orientation::C, i == j);
+///         assert_eq!(calculate_orientation(b, c, a, n), orientation);
+///         assert_eq!(calculate_orientation(c, a, b, n), orientation);
+///         assert_eq!(calculate_orientation(b, a, c, n), orientation.reversed());
+///         assert_eq!(calculate_orientation(a, c, b, n), orientation.reversed());
+///         assert_eq!(calculate_orientation(c, b, a, n), orientation.reversed());
+///     }
+/// }
+/// ```
 #[must_use]
 #[inline]
 pub fn calculate_orientation(a: Vector3D, b: Vector3D, c: Vector3D, n: Vector3D) -> Orientation {
-    let orientation = (b - a).cross(&(c - a)).dot(&n);
-    if orientation > 0. {
-        Orientation::CCW
-    } else if orientation < 0. {
-        Orientation::CW
-    } else {
-        Orientation::C
-    }
+    predicates::orient3d_along(a, b, c, n)
 }
 
 /// Projects point `point` onto a plane `plane` along reference `reference`.
@@ -149,42 +233,190 @@ pub fn is_point_inside_triangle(p: Vector3D, t: (Vector3D, Vector3D, Vector3D))
     u >= -EPS && v >= -EPS && (u + v) <= 1.0 + EPS
 }
 
-/// Calculates the distance of point `p` to triangle `t`
+/// Calculates the distance of point `p` to triangle `t`.
+/// # Arguments
+/// * `p` - The point.
+/// * `t` - A tuple of three vertices representing the triangle.
+/// # Returns
+/// * `f64` - The distance from `p` to the closest point of `t`, see `closest_point_on_triangle`.
 #[must_use]
 #[inline]
 pub fn distance_to_triangle(p: Vector3D, t: (Vector3D, Vector3D, Vector3D)) -> f64 {
-    let (a, b, c) = t;
+    (p - closest_point_on_triangle(p, t).point).norm()
+}
+
+/// Calculates the distance of point `p` to the line segment (`a`, `b`).
+/// # Arguments
+/// * `p` - The point.
+/// * `a` - First point of the line segment.
+/// * `b` - Second point of the line segment.
+/// # Returns
+/// * `f64` - The distance from `p` to the closest point of the segment, see `closest_point_on_segment`.
+#[must_use]
+#[inline]
+pub fn distance_to_segment(p: Vector3D, a: Vector3D, b: Vector3D) -> f64 {
+    (p - closest_point_on_segment(p, a, b).0).norm()
+}
 
+/// Calculates the closest point to point `p` on the line segment (`a`, `b`).
+/// # Arguments
+/// * `p` - The point.
+/// * `a` - First point of the line segment.
+/// * `b` - Second point of the line segment.
+/// # Returns
+/// * `(Vector3D, f64)` - The closest point and its parameter `s` in `[0, 1]`, such that the point is `a + s * (b - a)`. A degenerate segment (`a == b`) yields `(a, 0)`.
+///
+/// # Example
+/// ```
+/// use hutspot::geom::{closest_point_on_segment, Vector3D};
+/// let (a, b) = (Vector3D::new(0., 0., 0.), Vector3D::new(2., 0., 0.));
+/// assert_eq!(closest_point_on_segment(Vector3D::new(0.5, 1., 0.), a, b), (Vector3D::new(0.5, 0., 0.), 0.25));
+/// assert_eq!(closest_point_on_segment(Vector3D::new(-1., 1., 0.), a, b), (a, 0.));
+/// assert_eq!(closest_point_on_segment(Vector3D::new(3., 1., 0.), a, b), (b, 1.));
+/// assert_eq!(closest_point_on_segment(Vector3D::new(3., 1., 0.), a, a), (a, 0.));
+/// ```
+#[must_use]
+#[inline]
+pub fn closest_point_on_segment(p: Vector3D, a: Vector3D, b: Vector3D) -> (Vector3D, f64) {
+    let ab = b - a;
+    let s = (p - a).dot(&ab) / ab.dot(&ab);
+    if !s.is_finite() {
+        return (a, 0.);
+    }
+    match s.clamp(0.0, 1.0) {
+        0.0 => (a, 0.0),
+        1.0 => (b, 1.0),
+        s => (a + ab * s, s),
+    }
+}
+
+/// The feature of a triangle (`t.0`, `t.1`, `t.2`) on which a point lies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriangleFeature {
+    /// The vertex `t.i`.
+    Vertex(usize),
+    /// The interior of edge `i`, which is (`t.0`, `t.1`), (`t.1`, `t.2`), or (`t.2`, `t.0`) for `i` = 0, 1, 2.
+    Edge(usize),
+    /// The interior of the triangle.
+    Face,
+}
+
+/// The closest point on a triangle to a query point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TriangleClosestPoint {
+    pub point: Vector3D,
+    /// Barycentric coordinates of `point` with respect to the triangle.
+    pub barycentric: (f64, f64, f64),
+    /// The feature of the triangle on which `point` lies.
+    pub feature: TriangleFeature,
+}
+
+/// Calculates the closest point to point `p` on triangle `t`.
+/// # Arguments
+/// * `p` - The point.
+/// * `t` - A tuple of three vertices representing the triangle.
+/// # Returns
+/// * `TriangleClosestPoint` - The closest point, its barycentric coordinates, and the feature (vertex, edge, or face interior) of `t` on which it lies.
+///
+/// # Source
+/// This method determines in which Voronoi region (of the vertices, edges, or face) of the triangle the point lies, using only dot products. For more details, see Ericson, Real-Time Collision Detection, Section 5.1.5.
+/// Degenerate triangles (with collinear or coinciding vertices) are handled as their three edges, so the result never contains NaNs.
+///
+/// # Example
+/// ```
+/// use hutspot::geom::{closest_point_on_triangle, TriangleFeature, Vector3D};
+/// let t = (Vector3D::new(0., 0., 0.), Vector3D::new(1., 0., 0.), Vector3D::new(0., 1., 0.));
+/// let tests = vec![
+///     (Vector3D::new(0.25, 0.25, 1.), Vector3D::new(0.25, 0.25, 0.), (0.5, 0.25, 0.25), TriangleFeature::Face),
+///     (Vector3D::new(-1., -1., 0.), t.0, (1., 0., 0.), TriangleFeature::Vertex(0)),
+///     (Vector3D::new(2., -1., 3.), t.1, (0., 1., 0.), TriangleFeature::Vertex(1)),
+///     (Vector3D::new(0., 2., 0.), t.2, (0., 0., 1.), TriangleFeature::Vertex(2)),
+///     (Vector3D::new(0.5, -1., 0.), Vector3D::new(0.5, 0., 0.), (0.5, 0.5, 0.), TriangleFeature::Edge(0)),
+///     (Vector3D::new(1., 1., 0.), Vector3D::new(0.5, 0.5, 0.), (0., 0.5, 0.5), TriangleFeature::Edge(1)),
+///     (Vector3D::new(-1., 0.5, 0.), Vector3D::new(0., 0.5, 0.), (0.5, 0., 0.5), TriangleFeature::Edge(2)),
+/// ];
+/// for (p, point, barycentric, feature) in tests {
+///     let closest = closest_point_on_triangle(p, t);
+///     assert!((closest.point - point).norm() < 1e-12, "{closest:?}");
+///     assert!((closest.barycentric.0 - barycentric.0).abs() < 1e-12 && (closest.barycentric.1 - barycentric.1).abs() < 1e-12, "{closest:?}");
+///     assert_eq!(closest.feature, feature);
+/// }
+///
+/// // Degenerate triangles.
+/// let (a, b) = (Vector3D::new(0., 0., 0.), Vector3D::new(2., 0., 0.));
+/// let closest = closest_point_on_triangle(Vector3D::new(1.5, 1., 0.), (a, b, Vector3D::new(1., 0., 0.)));
+/// assert_eq!(closest.point, Vector3D::new(1.5, 0., 0.));
+/// let closest = closest_point_on_triangle(Vector3D::new(1.5, 1., 0.), (a, a, a));
+/// assert_eq!((closest.point, closest.feature), (a, TriangleFeature::Vertex(0)));
+/// ```
+#[must_use]
+#[allow(clippy::many_single_char_names)]
+pub fn closest_point_on_triangle(p: Vector3D, t: (Vector3D, Vector3D, Vector3D)) -> TriangleClosestPoint {
+    let closest = |point, barycentric, feature| TriangleClosestPoint { point, barycentric, feature };
+    let (a, b, c) = t;
     let ab = b - a;
     let ac = c - a;
+
+    // The squared sine of the angle at `a` is (numerically) zero for degenerate triangles.
+    if ab.cross(&ac).norm_squared() <= f64::EPSILON * ab.norm_squared() * ac.norm_squared() {
+        return [(a, b), (b, c), (c, a)]
+            .into_iter()
+            .enumerate()
+            .map(|(i, (u, v))| {
+                let (point, s) = closest_point_on_segment(p, u, v);
+                let mut barycentric = [0.; 3];
+                barycentric[i] = 1. - s;
+                barycentric[(i + 1) % 3] = s;
+                let feature = match s {
+                    0. => TriangleFeature::Vertex(i),
+                    1. => TriangleFeature::Vertex((i + 1) % 3),
+                    _ => TriangleFeature::Edge(i),
+                };
+                closest(point, barycentric.into(), feature)
+            })
+            .min_by(|x, y| (p - x.point).norm_squared().total_cmp(&(p - y.point).norm_squared()))
+            .unwrap_or_else(|| closest(a, (1., 0., 0.), TriangleFeature::Vertex(0)));
+    }
+
     let ap = p - a;
+    let (d1, d2) = (ab.dot(&ap), ac.dot(&ap));
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return closest(a, (1., 0., 0.), TriangleFeature::Vertex(0));
+    }
 
-    // Normal vector of the triangle plane
-    let n = ab.cross(&ac);
-    let n_norm = n.norm();
-    let n_unit = n / n_norm;
+    let bp = p - b;
+    let (d3, d4) = (ab.dot(&bp), ac.dot(&bp));
+    if d3 >= 0.0 && d4 <= d3 {
+        return closest(b, (0., 1., 0.), TriangleFeature::Vertex(1));
+    }
 
-    // Distance from p to the plane
-    let dist_to_plane = ap.dot(&n_unit);
-    let proj = p - dist_to_plane * n_unit;
+    let vc = d1.mul_add(d4, -(d3 * d2));
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        let v = d1 / (d1 - d3);
+        return closest(a + ab * v, (1. - v, v, 0.), TriangleFeature::Edge(0));
+    }
 
-    if n_norm != 0.0 && is_point_inside_triangle(proj, (a, b, c)) {
-        dist_to_plane.abs() // Perpendicular distance
-    } else {
-        // Closest distance to one of the triangle’s edges
-        let d1 = distance_to_segment(p, a, b);
-        let d2 = distance_to_segment(p, b, c);
-        let d3 = distance_to_segment(p, c, a);
-        d1.min(d2).min(d3)
+    let cp = p - c;
+    let (d5, d6) = (ab.dot(&cp), ac.dot(&cp));
+    if d6 >= 0.0 && d5 <= d6 {
+        return closest(c, (0., 0., 1.), TriangleFeature::Vertex(2));
     }
-}
 
-fn distance_to_segment(p: Vector3D, a: Vector3D, b: Vector3D) -> f64 {
-    let ab = b - a;
-    let t = (p - a).dot(&ab) / ab.dot(&ab);
-    let t_clamped = t.clamp(0.0, 1.0);
-    let closest = a + ab * t_clamped;
-    (p - closest).norm()
+    let vb = d5.mul_add(d2, -(d1 * d6));
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        let w = d2 / (d2 - d6);
+        return closest(a + ac * w, (1. - w, 0., w), TriangleFeature::Edge(2));
+    }
+
+    let va = d3.mul_add(d6, -(d5 * d4));
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+        return closest(b + (c - b) * w, (0., 1. - w, w), TriangleFeature::Edge(1));
+    }
+
+    let denom = va + vb + vc;
+    let (v, w) = (vb / denom, vc / denom);
+    closest(a + ab * v + ac * w, (1. - v - w, v, w), TriangleFeature::Face)
 }
 
 // Calculate the barycentric coordinates of point `p` with respect to triangle `t`.
@@ -278,6 +510,258 @@ pub fn is_within_inclusive_range(a: f64, b: f64, c: f64) -> bool {
     if b < c { (b..=c).contains(&a) } else { (c..=b).contains(&a) }
 }
 
+/// Classification of the intersection of two line segments `p` and `q`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SegmentIntersection<V> {
+    /// The segments do not intersect.
+    Disjoint,
+    /// The segments cross at a single point in the interior of both.
+    Crossing(V),
+    /// The segments meet at a single point that is an endpoint of both.
+    Touching(V),
+    /// An endpoint of one segment lies in the interior of the other.
+    TJunction(V),
+    /// The segments are collinear and overlap in the segment between the two points (ordered along `p`).
+    Overlap(V, V),
+}
+
+impl<V: Copy> SegmentIntersection<V> {
+    /// Returns the intersection point, or `None` if the segments are disjoint or overlap in a segment.
+    #[must_use]
+    pub const fn point(&self) -> Option<V> {
+        match *self {
+            Self::Crossing(x) | Self::Touching(x) | Self::TJunction(x) => Some(x),
+            Self::Disjoint | Self::Overlap(..) => None,
+        }
+    }
+
+    /// Maps the points of the intersection with `f`, keeping its type.
+    #[must_use]
+    pub fn map<W>(self, f: impl Fn(V) -> W) -> SegmentIntersection<W> {
+        match self {
+            Self::Disjoint => SegmentIntersection::Disjoint,
+            Self::Crossing(x) => SegmentIntersection::Crossing(f(x)),
+            Self::Touching(x) => SegmentIntersection::Touching(f(x)),
+            Self::TJunction(x) => SegmentIntersection::TJunction(f(x)),
+            Self::Overlap(x, y) => SegmentIntersection::Overlap(f(x), f(y)),
+        }
+    }
+}
+
+// A point of a segment-segment intersection, expressed as one of the four endpoints or as a parameter along `p`.
+#[derive(Debug, Clone, Copy)]
+enum SegmentPoint {
+    PU,
+    PV,
+    QU,
+    QV,
+    AlongP(f64),
+}
+
+impl SegmentPoint {
+    fn resolve<V>(self, p_u: V, p_v: V, q_u: V, q_v: V) -> V
+    where
+        V: Copy + std::ops::Add<Output = V> + std::ops::Sub<Output = V> + std::ops::Mul<f64, Output = V>,
+    {
+        match self {
+            Self::PU => p_u,
+            Self::PV => p_v,
+            Self::QU => q_u,
+            Self::QV => q_v,
+            Self::AlongP(t) => p_u + (p_v - p_u) * t,
+        }
+    }
+}
+
+// Classifies the intersection of the (non-degenerate) segment `a`-`b` with the point `x`.
+fn classify_point_on_segment(
+    x: SegmentPoint,
+    x_pos: Vector2D,
+    a: (SegmentPoint, Vector2D),
+    b: (SegmentPoint, Vector2D),
+) -> SegmentIntersection<SegmentPoint> {
+    if x_pos == a.1 || x_pos == b.1 {
+        SegmentIntersection::Touching(x)
+    } else if predicates::orient2d(a.1, b.1, x_pos) == Orientation::C
+        && is_within_inclusive_range(x_pos.x, a.1.x, b.1.x)
+        && is_within_inclusive_range(x_pos.y, a.1.y, b.1.y)
+    {
+        SegmentIntersection::TJunction(x)
+    } else {
+        SegmentIntersection::Disjoint
+    }
+}
+
+// Classifies the intersection of two segments with exact predicates. Only the crossing point is computed in floating point.
+fn classify_lineseg_intersection(p_u: Vector2D, p_v: Vector2D, q_u: Vector2D, q_v: Vector2D) -> SegmentIntersection<SegmentPoint> {
+    use SegmentIntersection::{Crossing, Disjoint, Overlap, TJunction, Touching};
+    use SegmentPoint::{AlongP, PU, PV, QU, QV};
+
+    match (p_u == p_v, q_u == q_v) {
+        (true, true) => return if p_u == q_u { Touching(PU) } else { Disjoint },
+        (true, false) => return classify_point_on_segment(PU, p_u, (QU, q_u), (QV, q_v)),
+        (false, true) => return classify_point_on_segment(QU, q_u, (PU, p_u), (PV, p_v)),
+        (false, false) => {}
+    }
+
+    let o1 = predicates::orient2d(p_u, p_v, q_u);
+    let o2 = predicates::orient2d(p_u, p_v, q_v);
+    if o1 == Orientation::C && o2 == Orientation::C {
+        // Collinear: intersect the intervals along the dominant axis of `p`, oriented such that `p_u` comes first.
+        let d = p_v - p_u;
+        let axis = usize::from(d.y.abs() > d.x.abs());
+        let direction = d[axis].signum();
+        let key = |v: Vector2D| v[axis] * direction;
+        let (q_first, q_last) = if key(q_u) <= key(q_v) {
+            ((QU, q_u), (QV, q_v))
+        } else {
+            ((QV, q_v), (QU, q_u))
+        };
+        let start = if key(p_u) >= key(q_first.1) { (PU, p_u) } else { q_first };
+        let end = if key(p_v) <= key(q_last.1) { (PV, p_v) } else { q_last };
+        return match key(start.1).partial_cmp(&key(end.1)) {
+            Some(std::cmp::Ordering::Less) => Overlap(start.0, end.0),
+            Some(std::cmp::Ordering::Equal) => Touching(start.0),
+            _ => Disjoint,
+        };
+    }
+
+    let o3 = predicates::orient2d(q_u, q_v, p_u);
+    let o4 = predicates::orient2d(q_u, q_v, p_v);
+    if o1 == o2 || o3 == o4 {
+        return Disjoint;
+    }
+
+    // The supporting lines meet in a single point, so an endpoint on the other line is the intersection.
+    let collinear = |o: Orientation| o == Orientation::C;
+    match (collinear(o1) || collinear(o2), collinear(o3), collinear(o4)) {
+        (true, true, _) => Touching(PU),
+        (true, _, true) => Touching(PV),
+        (true, false, false) => TJunction(if collinear(o1) { QU } else { QV }),
+        (false, true, _) => TJunction(PU),
+        (false, _, true) => TJunction(PV),
+        (false, false, false) => {
+            let (r, s) = (p_v - p_u, q_v - q_u);
+            let t = (q_u - p_u).perp(&s) / r.perp(&s);
+            Crossing(AlongP(if t.is_finite() { t.clamp(0., 1.) } else { 0.5 }))
+        }
+    }
+}
+
+// Finds two coordinate axes such that dropping the third one maps the (coplanar) points injectively onto a coordinate plane.
+fn coplanar_projection_axes(points: [Vector3D; 4]) -> (usize, usize) {
+    let keep = |dropped: usize| ((dropped + 1) % 3, (dropped + 2) % 3);
+    let project = |v: Vector3D, (i, j): (usize, usize)| Vector2D::new(v[i], v[j]);
+
+    let Some((a, b)) = points.iter().tuple_combinations().find(|(a, b)| a != b).map(|(&a, &b)| (a, b)) else {
+        return (0, 1);
+    };
+    for &x in &points {
+        let normal = (b - a).cross(&(x - a));
+        let mut order = [0, 1, 2];
+        order.sort_by(|&i, &j| normal[j].abs().total_cmp(&normal[i].abs()));
+        if let Some(&dropped) = order
+            .iter()
+            .find(|&&k| predicates::orient2d(project(a, keep(k)), project(b, keep(k)), project(x, keep(k))) != Orientation::C)
+        {
+            return keep(dropped);
+        }
+    }
+
+    // All points are collinear: drop the axis along which the line varies least.
+    let d = b - a;
+    keep(d.iamin())
+}
+
+/// Classifies the intersection of two line segments (`p_u`, `p_v`) and (`q_u`, `q_v`) in 2D space.
+/// # Arguments
+/// * `p_u` - First point of the first line segment.
+/// * `p_v` - Second point of the first line segment.
+/// * `q_u` - First point of the second line segment.
+/// * `q_v` - Second point of the second line segment.
+/// # Returns
+/// * `SegmentIntersection<Vector2D>` - Whether the segments are disjoint, cross properly, touch at a shared endpoint, form a T-junction, or overlap collinearly (with the overlap segment).
+///
+/// # Source
+/// The classification only uses the exact `predicates::orient2d` and comparisons of input coordinates, so it is consistent and never panics.
+/// Only the location of a proper crossing is computed in floating point, using the parametric form of the line equation. For more details, see [Wikipedia](https://en.wikipedia.org/wiki/Intersection_(geometry)#Two_line_segments).
+///
+/// # Example
+/// ```
+/// use hutspot::geom::{classify_2d_lineseg_intersection, SegmentIntersection, Vector2D};
+/// let v = Vector2D::new;
+/// let tests = vec![
+///     ((v(0., 0.), v(2., 2.), v(2., 0.), v(0., 2.)), SegmentIntersection::Crossing(v(1., 1.))),
+///     ((v(0., 0.), v(2., 0.), v(2., 0.), v(3., 1.)), SegmentIntersection::Touching(v(2., 0.))),
+///     ((v(0., 0.), v(2., 0.), v(1., 0.), v(1., 1.)), SegmentIntersection::TJunction(v(1., 0.))),
+///     ((v(1., -1.), v(1., 1.), v(0., 0.), v(1., 0.)), SegmentIntersection::TJunction(v(1., 0.))),
+///     ((v(0., 0.), v(2., 0.), v(3., 0.), v(1., 0.)), SegmentIntersection::Overlap(v(1., 0.), v(2., 0.))),
+///     ((v(2., 2.), v(0., 0.), v(-1., -1.), v(3., 3.)), SegmentIntersection::Overlap(v(2., 2.), v(0., 0.))),
+///     ((v(0., 0.), v(1., 0.), v(1., 0.), v(2., 0.)), SegmentIntersection::Touching(v(1., 0.))),
+///     ((v(0., 0.), v(1., 0.), v(2., 0.), v(3., 0.)), SegmentIntersection::Disjoint),
+///     ((v(0., 0.), v(1., 0.), v(0., 1.), v(1., 1.)), SegmentIntersection::Disjoint),
+///     ((v(0., 0.), v(1., 0.), v(2., 1.), v(2., -1.)), SegmentIntersection::Disjoint),
+///     ((v(0.5, 0.), v(0.5, 0.), v(0., 0.), v(1., 0.)), SegmentIntersection::TJunction(v(0.5, 0.))),
+///     ((v(0.5, 0.), v(0.5, 0.), v(0.5, 0.), v(0.5, 0.)), SegmentIntersection::Touching(v(0.5, 0.))),
+/// ];
+/// for ((a, b, c, d), expected) in tests {
+///     let intersection = classify_2d_lineseg_intersection(a, b, c, d);
+///     assert_eq!(intersection, expected, "intersection({a:?}, {b:?}, {c:?}, {d:?}) = {intersection:?}, but should be: {expected:?}");
+/// }
+///
+/// // Nearly collinear segments are classified exactly, regardless of the argument order.
+/// let (a, b) = (v(0.1, 0.1), v(0.7, 0.7));
+/// let (c, d) = (v(0.3, 0.3 + f64::EPSILON), v(0.9, 0.9));
+/// assert_eq!(classify_2d_lineseg_intersection(a, b, c, d), SegmentIntersection::Disjoint);
+/// assert_eq!(classify_2d_lineseg_intersection(c, d, b, a), SegmentIntersection::Disjoint);
+/// ```
+#[must_use]
+pub fn classify_2d_lineseg_intersection(p_u: Vector2D, p_v: Vector2D, q_u: Vector2D, q_v: Vector2D) -> SegmentIntersection<Vector2D> {
+    classify_lineseg_intersection(p_u, p_v, q_u, q_v).map(|x| x.resolve(p_u, p_v, q_u, q_v))
+}
+
+/// Classifies the intersection of two line segments (`p_u`, `p_v`) and (`q_u`, `q_v`) in 3D space.
+/// # Arguments
+/// * `p_u` - First point of the first line segment.
+/// * `p_v` - Second point of the first line segment.
+/// * `q_u` - First point of the second line segment.
+/// * `q_v` - Second point of the second line segment.
+/// # Returns
+/// * `SegmentIntersection<Vector3D>` - Whether the segments are disjoint, cross properly, touch at a shared endpoint, form a T-junction, or overlap collinearly (with the overlap segment).
+///
+/// # Source
+/// Non-coplanar segments are rejected with the exact `predicates::orient3d`. Coplanar segments are projected onto a coordinate plane by dropping one coordinate (which is exact),
+/// chosen such that the projection is injective, and classified with `classify_2d_lineseg_intersection`.
+///
+/// # Example
+/// ```
+/// use hutspot::geom::{classify_3d_lineseg_intersection, SegmentIntersection, Vector3D};
+/// let v = Vector3D::new;
+/// let tests = vec![
+///     ((v(0., 0., 0.), v(2., 2., 2.), v(2., 0., 2.), v(0., 2., 0.)), SegmentIntersection::Crossing(v(1., 1., 1.))),
+///     ((v(0., 0., 0.), v(2., 2., 2.), v(2., 0., 2.), v(0., 2., 0. + f64::EPSILON)), SegmentIntersection::Disjoint),
+///     ((v(0., 0., 1.), v(0., 0., 3.), v(0., 0., 2.), v(0., 0., 5.)), SegmentIntersection::Overlap(v(0., 0., 2.), v(0., 0., 3.))),
+///     ((v(0., 0., 1.), v(0., 0., 3.), v(0., 0., 3.), v(0., 0., 5.)), SegmentIntersection::Touching(v(0., 0., 3.))),
+///     ((v(0., 0., 1.), v(0., 0., 3.), v(0., 0., 2.), v(0., 1., 2.)), SegmentIntersection::TJunction(v(0., 0., 2.))),
+///     ((v(1., 0., 0.), v(1., 0., 2.), v(0., 0., 0.), v(2., 0., 0.)), SegmentIntersection::TJunction(v(1., 0., 0.))),
+///     ((v(0., 0., 0.), v(1., 0., 0.), v(0., 1., 0.), v(1., 1., 0.)), SegmentIntersection::Disjoint),
+/// ];
+/// for ((a, b, c, d), expected) in tests {
+///     let intersection = classify_3d_lineseg_intersection(a, b, c, d);
+///     assert_eq!(intersection, expected, "intersection({a:?}, {b:?}, {c:?}, {d:?}) = {intersection:?}, but should be: {expected:?}");
+/// }
+/// ```
+#[must_use]
+pub fn classify_3d_lineseg_intersection(p_u: Vector3D, p_v: Vector3D, q_u: Vector3D, q_v: Vector3D) -> SegmentIntersection<Vector3D> {
+    if !are_points_coplanar(p_u, p_v, q_u, q_v) {
+        return SegmentIntersection::Disjoint;
+    }
+
+    let axes = coplanar_projection_axes([p_u, p_v, q_u, q_v]);
+    let project = |v: Vector3D| Vector2D::new(v[axes.0], v[axes.1]);
+    classify_lineseg_intersection(project(p_u), project(p_v), project(q_u), project(q_v)).map(|x| x.resolve(p_u, p_v, q_u, q_v))
+}
+
 /// Calculates the intersection of two line segments (`p_u`, `p_v`) and (`q_u`, `q_v`) in 2D space.
 /// # Arguments
 /// * `p_u` - First point of the first line segment.
@@ -285,10 +769,10 @@ pub fn is_within_inclusive_range(a: f64, b: f64, c: f64) -> bool {
 /// * `q_u` - First point of the second line segment.
 /// * `q_v` - Second point of the second line segment.
 /// # Returns
-/// * `Option<Intersection2D>` - The intersection point and type, or `None` if no intersection.
+/// * `Option<Vector2D>` - The intersection point, or `None` if the segments are disjoint or overlap in more than a single point.
 ///
 /// # Source
-/// This method uses the parametric form of the line equation to calculate the intersection point of two line segments in 2D space. For more details, see [Wikipedia](https://en.wikipedia.org/wiki/Intersection_(geometry)#Two_line_segments).
+/// This is a thin wrapper around `classify_2d_lineseg_intersection`.
 ///
 /// # Example
 /// ```
@@ -333,25 +817,7 @@ pub fn is_within_inclusive_range(a: f64, b: f64, c: f64) -> bool {
 /// ```
 #[must_use]
 pub fn calculate_2d_lineseg_intersection(p_u: Vector2D, p_v: Vector2D, q_u: Vector2D, q_v: Vector2D) -> Option<Vector2D> {
-    let (x1, x2, x3, x4, y1, y2, y3, y4) = (p_u.x, p_v.x, q_u.x, q_v.x, p_u.y, p_v.y, q_u.y, q_v.y);
-
-    let t_numerator = (x1 - x3) * (y3 - y4) - (y1 - y3) * (x3 - x4);
-    let u_numerator = (x1 - x3) * (y1 - y2) - (y1 - y3) * (x1 - x2);
-    let denominator = (x1 - x2) * (y3 - y4) - (y1 - y2) * (x3 - x4);
-
-    if is_within_inclusive_range(t_numerator, 0.0, denominator) && is_within_inclusive_range(u_numerator, 0.0, denominator) {
-        let t = t_numerator / denominator;
-        let s_t = Vector2D::new(t.mul_add(x2 - x1, x1), t.mul_add(y2 - y1, y1));
-        let u = u_numerator / denominator;
-        let s_u = Vector2D::new(u.mul_add(x4 - x3, x3), u.mul_add(y4 - y3, y3));
-        assert!(
-            s_t.abs_diff_eq(&s_u, 10. * EPS),
-            "p_u: {p_u}\np_v: {p_v}\nq_u: {q_u}\nq_v: {q_v}\nt: {t}\ns_t: {s_t}\nu: {u}\ns_u: {s_u}"
-        );
-        Some(s_t)
-    } else {
-        None
-    }
+    classify_2d_lineseg_intersection(p_u, p_v, q_u, q_v).point()
 }
 
 /// Calculates the intersection of two line segments (`p_u`, `p_v`) and (`q_u`, `q_v`) in 3D space.
@@ -361,7 +827,10 @@ pub fn calculate_2d_lineseg_intersection(p_u: Vector2D, p_v: Vector2D, q_u: Vect
 /// * `q_u` - First point of the second line segment.
 /// * `q_v` - Second point of the second line segment.
 /// # Returns
-/// * `Option<Intersection3D>` - The intersection point and type, or `None` if no intersection.
+/// * `Option<Vector3D>` - The intersection point, or `None` if the segments are disjoint or overlap in more than a single point.
+///
+/// # Source
+/// This is a thin wrapper around `classify_3d_lineseg_intersection`.
 ///
 /// # Example
 /// ```
@@ -429,26 +898,7 @@ pub fn calculate_2d_lineseg_intersection(p_u: Vector2D, p_v: Vector2D, q_u: Vect
 /// ```
 #[must_use]
 pub fn calculate_3d_lineseg_intersection(p_u: Vector3D, p_v: Vector3D, q_u: Vector3D, q_v: Vector3D) -> Option<Vector3D> {
-    if !are_points_coplanar(p_u, p_v, q_u, q_v) {
-        return None;
-    }
-
-    let p = p_v - p_u;
-    let q = q_v - q_u;
-    let normal = p.cross(&q);
-    if normal == Vector3D::new(0., 0., 0.) {
-        return None; // Lines are parallel
-    }
-    let reference = p_u;
-    let plane = (p.normalize(), p.cross(&normal.normalize()).normalize());
-    assert!(plane.0 != Vector3D::new(0., 0., 0.));
-    calculate_2d_lineseg_intersection(
-        project_point_onto_plane(p_u, plane, reference),
-        project_point_onto_plane(p_v, plane, reference),
-        project_point_onto_plane(q_u, plane, reference),
-        project_point_onto_plane(q_v, plane, reference),
-    )
-    .map(|point_in_2d| reference + (plane.0 * point_in_2d.x) + (plane.1 * point_in_2d.y))
+    classify_3d_lineseg_intersection(p_u, p_v, q_u, q_v).point()
 }
 
 /// Calculates the clockwise angle between `a->b` and `a->c` with the three points `a`, `b`, `c`, with normal `n`, in 3D space.
@@ -510,3 +960,612 @@ pub fn calculate_clockwise_angle(a: Vector3D, b: Vector3D, c: Vector3D, n: Vecto
         angle
     }
 }
+
+/// The intersection of a ray with a triangle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayTriangleHit {
+    /// Ray parameter of the hit, such that the hit point is `origin + t * direction`.
+    pub t: f64,
+    /// Barycentric coordinates of the hit point with respect to the triangle.
+    pub barycentric: (f64, f64, f64),
+}
+
+/// Calculates the intersection of the ray from `origin` in direction `direction` with triangle `t`.
+/// # Arguments
+/// * `origin` - The origin of the ray.
+/// * `direction` - The direction of the ray (not necessarily normalized).
+/// * `t` - A tuple of three vertices representing the triangle.
+/// * `cull_backfaces` - If `true`, only hits on the front face are reported, i.e., where the ray points against the normal `(t.1 - t.0) x (t.2 - t.0)`.
+/// # Returns
+/// * `Option<RayTriangleHit>` - The ray parameter (non-negative) and barycentric coordinates of the hit, or `None` if the ray misses the triangle or is parallel to it.
+///
+/// # Source
+/// This method is the Möller-Trumbore algorithm. For more details, see [Wikipedia](https://en.wikipedia.org/wiki/M%C3%B6ller%E2%80%93Trumbore_intersection_algorithm).
+/// Rays through a shared edge or vertex of adjacent triangles may miss all of them due to rounding, see `ray_triangle_intersection_watertight` for a variant without this problem.
+///
+/// # Example
+/// ```
+/// use hutspot::geom::{ray_triangle_intersection, Vector3D};
+/// let t = (Vector3D::new(0., 0., 0.), Vector3D::new(1., 0., 0.), Vector3D::new(0., 1., 0.));
+/// let hit = ray_triangle_intersection(Vector3D::new(0.25, 0.5, 2.), Vector3D::new(0., 0., -2.), t, true).unwrap();
+/// assert_eq!((hit.t, hit.barycentric), (1., (0.25, 0.25, 0.5)));
+///
+/// // Hitting the back face.
+/// let (origin, direction) = (Vector3D::new(0.25, 0.5, -2.), Vector3D::new(0., 0., 1.));
+/// assert_eq!(ray_triangle_intersection(origin, direction, t, false).unwrap().t, 2.);
+/// assert!(ray_triangle_intersection(origin, direction, t, true).is_none());
+///
+/// // Missing the triangle, pointing away from it, and parallel to it.
+/// assert!(ray_triangle_intersection(Vector3D::new(1., 1., 1.), Vector3D::new(0., 0., -1.), t, false).is_none());
+/// assert!(ray_triangle_intersection(Vector3D::new(0.25, 0.25, 1.), Vector3D::new(0., 0., 1.), t, false).is_none());
+/// assert!(ray_triangle_intersection(Vector3D::new(-1., 0.25, 0.), Vector3D::new(1., 0., 0.), t, false).is_none());
+/// ```
+#[must_use]
+#[allow(clippy::many_single_char_names)]
+pub fn ray_triangle_intersection(
+    origin: Vector3D,
+    direction: Vector3D,
+    t: (Vector3D, Vector3D, Vector3D),
+    cull_backfaces: bool,
+) -> Option<RayTriangleHit> {
+    let e1 = t.1 - t.0;
+    let e2 = t.2 - t.0;
+    let p = direction.cross(&e2);
+    let det = e1.dot(&p);
+    if det == 0. || (cull_backfaces && det < 0.) {
+        return None;
+    }
+    let inverse_det = 1. / det;
+    let s = origin - t.0;
+    let u = s.dot(&p) * inverse_det;
+    if !(0. ..=1.).contains(&u) {
+        return None;
+    }
+    let q = s.cross(&e1);
+    let v = direction.dot(&q) * inverse_det;
+    if v < 0. || u + v > 1. {
+        return None;
+    }
+    let t = e2.dot(&q) * inverse_det;
+    (t >= 0.).then_some(RayTriangleHit {
+        t,
+        barycentric: (1. - u - v, u, v),
+    })
+}
+
+/// Calculates the intersection of the ray from `origin` in direction `direction` with triangle `t`, such that rays through shared edges and vertices are never missed.
+/// # Arguments
+/// * `origin` - The origin of the ray.
+/// * `direction` - The direction of the ray (not necessarily normalized).
+/// * `t` - A tuple of three vertices representing the triangle.
+/// * `cull_backfaces` - If `true`, only hits on the front face are reported, i.e., where the ray points against the normal `(t.1 - t.0) x (t.2 - t.0)`.
+/// # Returns
+/// * `Option<RayTriangleHit>` - The ray parameter (non-negative) and barycentric coordinates of the hit, or `None` if the ray misses the triangle or is parallel to it, or if the direction is zero or not finite.
+///
+/// # Source
+/// This method is the watertight ray-triangle intersection of Woop, Benthin, and Wald (2013). The ray is transformed such that it points along the positive z-axis, after which the edge tests only depend on the edge endpoints. Adjacent triangles therefore evaluate their shared edge identically, and a ray through the edge hits at least one of them.
+/// For more details, see [Watertight Ray/Triangle Intersection](https://jcgt.org/published/0002/01/05/).
+///
+/// # Example
+/// ```
+/// use hutspot::geom::{ray_triangle_intersection_watertight, Vector3D};
+/// let t = (Vector3D::new(0., 0., 0.), Vector3D::new(1., 0., 0.), Vector3D::new(0., 1., 0.));
+/// let hit = ray_triangle_intersection_watertight(Vector3D::new(0.25, 0.5, 2.), Vector3D::new(0., 0., -2.), t, true).unwrap();
+/// assert_eq!((hit.t, hit.barycentric), (1., (0.25, 0.25, 0.5)));
+/// assert!(ray_triangle_intersection_watertight(Vector3D::new(0.25, 0.5, -2.), Vector3D::new(0., 0., 1.), t, true).is_none());
+/// assert!(ray_triangle_intersection_watertight(Vector3D::new(0.25, 0.5, 0.), Vector3D::zeros(), t, false).is_none());
+///
+/// // Rays through the shared edge of two triangles always hit one of them.
+/// let (a, b) = (Vector3D::new(0.1, 0.3, 0.7), Vector3D::new(2.9, 1.3, -0.3));
+/// let (c, d) = (Vector3D::new(0.3, 2.1, 0.2), Vector3D::new(2.3, -1.7, 0.1));
+/// let direction = Vector3D::new(0.3, -0.1, -1.);
+/// for i in 1..1000 {
+///     let origin = a + (b - a) * (f64::from(i) / 1000.) - direction * 3.;
+///     assert!(
+///         ray_triangle_intersection_watertight(origin, direction, (a, b, c), false).is_some()
+///             || ray_triangle_intersection_watertight(origin, direction, (b, a, d), false).is_some()
+///     );
+/// }
+/// ```
+#[must_use]
+#[allow(clippy::many_single_char_names, clippy::similar_names, clippy::suboptimal_flops)]
+pub fn ray_triangle_intersection_watertight(
+    origin: Vector3D,
+    direction: Vector3D,
+    t: (Vector3D, Vector3D, Vector3D),
+    cull_backfaces: bool,
+) -> Option<RayTriangleHit> {
+    // Permute the axes such that the ray points along the positive z-axis (swapping x and y to preserve the winding).
+    let kz = direction.iamax();
+    let (mut kx, mut ky) = ((kz + 1) % 3, (kz + 2) % 3);
+    if direction[kz] < 0. {
+        std::mem::swap(&mut kx, &mut ky);
+    }
+    let shear = Vector3D::new(direction[kx] / direction[kz], direction[ky] / direction[kz], 1. / direction[kz]);
+    // A zero, non-finite, or vanishingly small direction has no usable shear.
+    if !shear.iter().all(|x| x.is_finite()) {
+        return None;
+    }
+
+    // Shear the vertices, relative to the origin, such that the ray becomes the z-axis.
+    let (a, b, c) = (t.0 - origin, t.1 - origin, t.2 - origin);
+    let project = |p: Vector3D| (shear.x.mul_add(-p[kz], p[kx]), shear.y.mul_add(-p[kz], p[ky]));
+    let ((ax, ay), (bx, by), (cx, cy)) = (project(a), project(b), project(c));
+
+    // Scaled barycentric coordinates, as signed areas of the projected edges with respect to the ray.
+    // These are deliberately not fused, so that an edge shared by two triangles yields exactly opposite values in both.
+    let u = cx * by - cy * bx;
+    let v = ax * cy - ay * cx;
+    let w = bx * ay - by * ax;
+    let any_negative = u < 0. || v < 0. || w < 0.;
+    let any_positive = u > 0. || v > 0. || w > 0.;
+    if any_negative && (cull_backfaces || any_positive) {
+        return None;
+    }
+
+    let det = u + v + w;
+    if det == 0. {
+        return None;
+    }
+    // Scaled ray parameter, which must have the same sign as `det`.
+    let scaled_t = u.mul_add(shear.z * a[kz], v.mul_add(shear.z * b[kz], w * shear.z * c[kz]));
+    if scaled_t * det.signum() < 0. {
+        return None;
+    }
+    let inverse_det = 1. / det;
+    Some(RayTriangleHit {
+        t: scaled_t * inverse_det,
+        barycentric: (u * inverse_det, v * inverse_det, w * inverse_det),
+    })
+}
+
+/// Calculates the intersection of the ray from `origin` in direction `direction` with the plane through `point` with normal `normal`.
+/// # Arguments
+/// * `origin` - The origin of the ray.
+/// * `direction` - The direction of the ray (not necessarily normalized).
+/// * `point` - A point on the plane.
+/// * `normal` - The normal of the plane (not necessarily normalized).
+/// # Returns
+/// * `Option<f64>` - The ray parameter `t` (non-negative) such that `origin + t * direction` lies on the plane, or `None` if the ray points away from or is parallel to the plane.
+///
+/// # Example
+/// ```
+/// use hutspot::geom::{ray_plane_intersection, Vector3D};
+/// let (point, normal) = (Vector3D::new(0., 0., 1.), Vector3D::new(0., 0., 2.));
+/// assert_eq!(ray_plane_intersection(Vector3D::new(5., 3., -1.), Vector3D::new(1., 1., 1.), point, normal), Some(2.));
+/// assert_eq!(ray_plane_intersection(Vector3D::new(5., 3., 3.), Vector3D::new(1., 1., -4.), point, normal), Some(0.5));
+/// assert_eq!(ray_plane_intersection(Vector3D::new(5., 3., 1.), Vector3D::new(1., 1., -4.), point, normal), Some(0.));
+/// assert_eq!(ray_plane_intersection(Vector3D::new(5., 3., 3.), Vector3D::new(1., 1., 1.), point, normal), None);
+/// assert_eq!(ray_plane_intersection(Vector3D::new(5., 3., 3.), Vector3D::new(1., 1., 0.), point, normal), None);
+/// ```
+#[must_use]
+#[inline]
+pub fn ray_plane_intersection(origin: Vector3D, direction: Vector3D, point: Vector3D, normal: Vector3D) -> Option<f64> {
+    let t = (point - origin).dot(&normal) / direction.dot(&normal);
+    (t >= 0. && t.is_finite()).then_some(t)
+}
+
+/// Calculates the intersection of the ray from `origin` in direction `direction` with the axis-aligned bounding box `aabb`.
+/// # Arguments
+/// * `origin` - The origin of the ray.
+/// * `direction` - The direction of the ray (not necessarily normalized).
+/// * `aabb` - The bounding box.
+/// # Returns
+/// * `Option<(f64, f64)>` - The ray parameters at which the ray enters and exits the box (the entry is zero if `origin` lies inside the box), or `None` if the ray misses the box.
+///
+/// # Source
+/// This method is the slab test, which intersects the parameter intervals in which the ray lies between the two planes of each axis. For more details, see [Wikipedia](https://en.wikipedia.org/wiki/Slab_method).
+/// The test is conservative: the exit parameters are enlarged by their maximum rounding error, as in Ize, Robust BVH Ray Traversal (2013). Rays parallel to a face of the box are handled without NaNs, so rays that hit a triangle also hit its bounding box.
+///
+/// # Example
+/// ```
+/// use hutspot::geom::{ray_aabb_intersection, Aabb, Vector3D};
+/// let aabb = Aabb::new(Vector3D::new(0., 0., 0.), Vector3D::new(1., 2., 3.));
+/// assert_eq!(ray_aabb_intersection(Vector3D::new(-1., 1., 1.), Vector3D::new(1., 0., 0.), &aabb).map(|(entry, _)| entry), Some(1.));
+/// assert_eq!(ray_aabb_intersection(Vector3D::new(0.5, 1., 1.), Vector3D::new(0., 0., -1.), &aabb).map(|(entry, _)| entry), Some(0.));
+/// assert!(ray_aabb_intersection(Vector3D::new(-1., 1., 1.), Vector3D::new(-1., 0., 0.), &aabb).is_none());
+/// assert!(ray_aabb_intersection(Vector3D::new(-1., 3., 1.), Vector3D::new(1., 0., 0.), &aabb).is_none());
+///
+/// // Rays in a face and through an edge of the box.
+/// assert!(ray_aabb_intersection(Vector3D::new(-1., 0., 1.), Vector3D::new(1., 0., 0.), &aabb).is_some());
+/// assert!(ray_aabb_intersection(Vector3D::new(-1., -1., 1.), Vector3D::new(1., 1., 0.), &aabb).is_some());
+/// ```
+#[must_use]
+pub fn ray_aabb_intersection(origin: Vector3D, direction: Vector3D, aabb: &Aabb) -> Option<(f64, f64)> {
+    // Relative rounding error of the exit parameters, 2 * gamma(3) in the notation of Pharr et al.
+    const EXIT_ERROR: f64 = 2. * (3. * f64::EPSILON * 0.5) / (1. - 3. * f64::EPSILON * 0.5);
+    let (mut entry, mut exit) = (0f64, f64::INFINITY);
+    for i in 0..3 {
+        if direction[i] == 0. {
+            // The ray is parallel to the slab, so it lies either inside or outside it.
+            if origin[i] < aabb.min[i] || origin[i] > aabb.max[i] {
+                return None;
+            }
+            continue;
+        }
+        let inverse_direction = 1. / direction[i];
+        let t1 = (aabb.min[i] - origin[i]) * inverse_direction;
+        let t2 = (aabb.max[i] - origin[i]) * inverse_direction;
+        entry = entry.max(t1.min(t2));
+        exit = exit.min(t1.max(t2) * (1. + EXIT_ERROR));
+    }
+    (entry <= exit).then_some((entry, exit))
+}
+
+// The sign of `predicates::orient3d(a, b, c, d)`: positive if `d` lies above the plane of `a`, `b`, `c`, seen counterclockwise.
+fn orient3d_sign(a: Vector3D, b: Vector3D, c: Vector3D, d: Vector3D) -> i8 {
+    match predicates::orient3d(a, b, c, d) {
+        Orientation::CCW => 1,
+        Orientation::CW => -1,
+        Orientation::C => 0,
+    }
+}
+
+// Whether the vertices of triangle `t` are collinear (or coincide), evaluated exactly.
+fn is_degenerate_triangle(t: (Vector3D, Vector3D, Vector3D)) -> bool {
+    (0..3).all(|dropped| {
+        let project = |v: Vector3D| Vector2D::new(v[(dropped + 1) % 3], v[(dropped + 2) % 3]);
+        predicates::orient2d(project(t.0), project(t.1), project(t.2)) == Orientation::C
+    })
+}
+
+// The longest edge of triangle `t`, which covers all of `t` if it is degenerate.
+fn longest_edge(t: (Vector3D, Vector3D, Vector3D)) -> (Vector3D, Vector3D) {
+    [(t.0, t.1), (t.1, t.2), (t.2, t.0)]
+        .into_iter()
+        .max_by(|(a, b), (c, d)| (b - a).norm_squared().total_cmp(&(d - c).norm_squared()))
+        .unwrap_or((t.0, t.1))
+}
+
+// The intersection of the segment `p`-`q` with the plane of triangle `t` that it crosses, given the exact sides of `p` and `q`.
+#[allow(clippy::many_single_char_names)]
+fn segment_plane_crossing(p: Vector3D, q: Vector3D, side_p: i8, side_q: i8, t: (Vector3D, Vector3D, Vector3D)) -> f64 {
+    match (side_p, side_q) {
+        (0, _) => 0.,
+        (_, 0) => 1.,
+        _ => {
+            let n = (t.1 - t.0).cross(&(t.2 - t.0));
+            let s = (t.0 - p).dot(&n) / (q - p).dot(&n);
+            if s.is_finite() { s.clamp(0., 1.) } else { 0.5 }
+        }
+    }
+}
+
+// The parameter interval of the segment `p`-`q` that lies in the (non-degenerate) triangle `t`, or `None` if they are disjoint.
+// Whether they intersect is decided exactly, only the interval itself is computed in floating point.
+#[allow(clippy::many_single_char_names)]
+fn segment_triangle_range(p: Vector3D, q: Vector3D, t: (Vector3D, Vector3D, Vector3D)) -> Option<(f64, f64)> {
+    let (a, b, c) = t;
+    let (side_p, side_q) = (orient3d_sign(a, b, c, p), orient3d_sign(a, b, c, q));
+    if side_p * side_q > 0 {
+        return None;
+    }
+    if side_p != 0 || side_q != 0 {
+        // The segment meets the plane in a single point, which lies in the triangle iff the segment sees all edges with the same orientation.
+        let sides = [orient3d_sign(p, q, a, b), orient3d_sign(p, q, b, c), orient3d_sign(p, q, c, a)];
+        if sides.contains(&1) && sides.contains(&-1) {
+            return None;
+        }
+        let s = segment_plane_crossing(p, q, side_p, side_q, t);
+        return Some((s, s));
+    }
+
+    // Coplanar: clip the segment against the edges of the projected triangle, oriented counterclockwise.
+    let axes = coplanar_projection_axes([a, b, c, c]);
+    let project = |v: Vector3D| Vector2D::new(v[axes.0], v[axes.1]);
+    let (p, q) = (project(p), project(q));
+    let mut corners = [project(a), project(b), project(c)];
+    if predicates::orient2d(corners[0], corners[1], corners[2]) == Orientation::CW {
+        corners.swap(1, 2);
+    }
+    let edges = [(corners[0], corners[1]), (corners[1], corners[2]), (corners[2], corners[0])];
+    let inside = |x: Vector2D| edges.iter().all(|&(u, v)| predicates::orient2d(u, v, x) != Orientation::CW);
+    if !inside(p)
+        && !inside(q)
+        && edges
+            .iter()
+            .all(|&(u, v)| matches!(classify_lineseg_intersection(p, q, u, v), SegmentIntersection::Disjoint))
+    {
+        return None;
+    }
+
+    let (mut start, mut end) = (0f64, 1f64);
+    for (u, v) in edges {
+        let (fp, fq) = ((v - u).perp(&(p - u)), (v - u).perp(&(q - u)));
+        let s = (fp / (fp - fq)).clamp(0., 1.);
+        match (
+            predicates::orient2d(u, v, p) == Orientation::CW,
+            predicates::orient2d(u, v, q) == Orientation::CW,
+        ) {
+            (true, false) => start = start.max(s),
+            (false, true) => end = end.min(s),
+            _ => {}
+        }
+    }
+    // The segment touches the triangle, but rounding may have made the interval empty.
+    Some(if start <= end {
+        (start, end)
+    } else {
+        (f64::midpoint(start, end), f64::midpoint(start, end))
+    })
+}
+
+/// Calculates the intersection of the line segment (`p`, `q`) with triangle `t` in 3D space.
+/// # Arguments
+/// * `p` - First point of the line segment.
+/// * `q` - Second point of the line segment.
+/// * `t` - A tuple of three vertices representing the triangle.
+/// # Returns
+/// * `Option<(Vector3D, (f64, f64, f64))>` - The intersection point closest to `p` and its barycentric coordinates with respect to `t`, or `None` if the segment misses the triangle.
+///
+/// # Source
+/// Whether the segment intersects the triangle is decided with the exact `predicates::orient3d`: the endpoints must not lie strictly on the same side of the plane of the triangle, and the segment must see the three edges with the same orientation.
+/// Segments in the plane of the triangle are projected onto a coordinate plane and clipped against the edges, deciding with the exact `predicates::orient2d`. Degenerate triangles are treated as their longest edge.
+///
+/// # Example
+/// ```
+/// use hutspot::geom::{segment_triangle_intersection, Vector3D};
+/// let t = (Vector3D::new(0., 0., 0.), Vector3D::new(2., 0., 0.), Vector3D::new(0., 2., 0.));
+/// let (point, barycentric) = segment_triangle_intersection(Vector3D::new(0.5, 1., 1.), Vector3D::new(0.5, 1., -3.), t).unwrap();
+/// assert_eq!(point, Vector3D::new(0.5, 1., 0.));
+/// assert!((barycentric.0 - 0.25).abs() < 1e-12 && (barycentric.1 - 0.25).abs() < 1e-12 && (barycentric.2 - 0.5).abs() < 1e-12);
+///
+/// // Touching the triangle at an endpoint, or in its plane.
+/// assert_eq!(segment_triangle_intersection(Vector3D::new(1., 1., 0.), Vector3D::new(1., 1., 1.), t).unwrap().0, Vector3D::new(1., 1., 0.));
+/// assert_eq!(segment_triangle_intersection(Vector3D::new(-1., 0.5, 0.), Vector3D::new(3., 0.5, 0.), t).unwrap().0, Vector3D::new(0., 0.5, 0.));
+///
+/// // Missing the triangle by the smallest possible amount.
+/// assert!(segment_triangle_intersection(Vector3D::new(1., 1., f64::EPSILON), Vector3D::new(1., 1., 1.), t).is_none());
+/// assert!(segment_triangle_intersection(Vector3D::new(1., 1. + f64::EPSILON, 1.), Vector3D::new(1., 1. + f64::EPSILON, -1.), t).is_none());
+/// assert!(segment_triangle_intersection(Vector3D::new(-1., 0.5, f64::EPSILON), Vector3D::new(3., 0.5, f64::EPSILON), t).is_none());
+///
+/// // A degenerate triangle.
+/// let degenerate = (Vector3D::new(0., 0., 0.), Vector3D::new(2., 0., 0.), Vector3D::new(1., 0., 0.));
+/// assert_eq!(segment_triangle_intersection(Vector3D::new(1.5, -1., 0.), Vector3D::new(1.5, 1., 0.), degenerate).unwrap().0, Vector3D::new(1.5, 0., 0.));
+/// ```
+#[must_use]
+#[allow(clippy::many_single_char_names)]
+pub fn segment_triangle_intersection(p: Vector3D, q: Vector3D, t: (Vector3D, Vector3D, Vector3D)) -> Option<(Vector3D, (f64, f64, f64))> {
+    let point = if is_degenerate_triangle(t) {
+        let (a, b) = longest_edge(t);
+        match classify_3d_lineseg_intersection(p, q, a, b) {
+            SegmentIntersection::Disjoint => return None,
+            SegmentIntersection::Overlap(x, _) => x,
+            intersection => intersection.point()?,
+        }
+    } else {
+        let (s, _) = segment_triangle_range(p, q, t)?;
+        p + (q - p) * s
+    };
+    // The point lies on the triangle, so this only recovers (and clamps) its barycentric coordinates.
+    Some((point, closest_point_on_triangle(point, t).barycentric))
+}
+
+/// Classification of the intersection of two triangles in 3D space.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TriangleIntersection {
+    /// The triangles meet in the segment between the two points (which coincide if they meet in a single point).
+    Segment(Vector3D, Vector3D),
+    /// The triangles are coplanar and overlap in the convex polygon with the given vertices (which may be a segment or a single point).
+    Coplanar(Vec<Vector3D>),
+}
+
+// The intersection of two non-degenerate coplanar triangles, by clipping `t1` against the edges of `t2` (Sutherland-Hodgman).
+fn coplanar_triangle_intersection(t1: (Vector3D, Vector3D, Vector3D), t2: (Vector3D, Vector3D, Vector3D)) -> Option<Vec<Vector3D>> {
+    let edges = |t: (Vector3D, Vector3D, Vector3D)| [(t.0, t.1), (t.1, t.2), (t.2, t.0)];
+    let first_contact = edges(t1)
+        .into_iter()
+        .map(|(p, q)| (p, q, t2))
+        .chain(edges(t2).into_iter().map(|(p, q)| (p, q, t1)))
+        .find_map(|(p, q, t)| segment_triangle_range(p, q, t).map(|(s, _)| p + (q - p) * s))?;
+
+    let axes = coplanar_projection_axes([t2.0, t2.1, t2.2, t2.2]);
+    let project = |v: Vector3D| Vector2D::new(v[axes.0], v[axes.1]);
+    let mut corners = [project(t2.0), project(t2.1), project(t2.2)];
+    if predicates::orient2d(corners[0], corners[1], corners[2]) == Orientation::CW {
+        corners.swap(1, 2);
+    }
+
+    let mut polygon = vec![t1.0, t1.1, t1.2];
+    for (u, v) in [(corners[0], corners[1]), (corners[1], corners[2]), (corners[2], corners[0])] {
+        let inside = |x: Vector3D| predicates::orient2d(u, v, project(x)) != Orientation::CW;
+        let height = |x: Vector3D| (v - u).perp(&(project(x) - u));
+        let mut clipped = vec![];
+        for (&x, &y) in polygon.iter().circular_tuple_windows() {
+            if inside(x) {
+                clipped.push(x);
+            }
+            if inside(x) != inside(y) {
+                let s = height(x) / (height(x) - height(y));
+                clipped.push(x + (y - x) * if s.is_finite() { s.clamp(0., 1.) } else { 0.5 });
+            }
+        }
+        polygon = clipped;
+    }
+    polygon.dedup();
+    if polygon.len() > 1 && polygon.first() == polygon.last() {
+        polygon.pop();
+    }
+
+    // The triangles touch, but rounding may have clipped away the contact.
+    Some(if polygon.is_empty() { vec![first_contact] } else { polygon })
+}
+
+// Reorders the vertices of a triangle, with exact sides `d` with respect to the plane of the other triangle, such that the first vertex is alone on its side.
+// Returns the rotation to apply, and whether the other triangle must be flipped such that the first vertex lies on its positive side (and the others on its non-positive side).
+const fn lone_vertex_first(d: (i8, i8, i8)) -> (usize, bool) {
+    match d {
+        (1, 1, _) => (2, true),
+        (1, _, 1) => (1, true),
+        (1, _, _) => (0, false),
+        (-1, -1, _) => (2, false),
+        (-1, _, -1) => (1, false),
+        (-1, _, _) => (0, true),
+        (_, -1, 0 | 1) => (1, true),
+        (_, -1, _) => (0, false),
+        (_, 1, 1) => (0, true),
+        (_, 1, _) => (1, false),
+        (_, _, 1) => (2, false),
+        _ => (2, true),
+    }
+}
+
+const fn rotate<V: Copy>(t: (V, V, V), k: usize) -> (V, V, V) {
+    match k {
+        0 => t,
+        1 => (t.1, t.2, t.0),
+        _ => (t.2, t.0, t.1),
+    }
+}
+
+const fn flip<V: Copy>(t: (V, V, V)) -> (V, V, V) {
+    (t.0, t.2, t.1)
+}
+
+/// Calculates the intersection of two triangles `t1` and `t2` in 3D space.
+/// # Arguments
+/// * `t1` - A tuple of three vertices representing the first triangle.
+/// * `t2` - A tuple of three vertices representing the second triangle.
+/// # Returns
+/// * `Option<TriangleIntersection>` - The intersection segment, or the intersection polygon if the triangles are coplanar, or `None` if the triangles are disjoint.
+///
+/// Triangles that share a vertex or an edge intersect (in that vertex or edge), so mesh self-intersection checks should skip or separately handle adjacent triangles.
+/// Degenerate triangles are treated as their longest edge, so their intersections are always segments.
+///
+/// # Source
+/// This method is the algorithm of Guigue and Devillers, Fast and Robust Triangle-Triangle Overlap Test Using Orientation Predicates (2003).
+/// All decisions use the exact `predicates::orient3d`, so touching triangles are classified correctly, and only the endpoints of the intersection segment are computed in floating point.
+/// Coplanar triangles are projected onto a coordinate plane and clipped against each other.
+///
+/// # Example
+/// ```
+/// use hutspot::geom::{segment_triangle_intersection, triangle_triangle_intersection, TriangleIntersection, Vector3D};
+/// // The intersection segment, in either direction.
+/// let is_segment = |intersection, a, b| matches!(intersection, Some(TriangleIntersection::Segment(x, y)) if (x, y) == (a, b) || (x, y) == (b, a));
+/// let t1 = (Vector3D::new(0., 0., 0.), Vector3D::new(4., 0., 0.), Vector3D::new(0., 4., 0.));
+/// let t2 = (Vector3D::new(1., 1., -1.), Vector3D::new(1., 1., 1.), Vector3D::new(5., 1., 1.));
+/// assert!(is_segment(triangle_triangle_intersection(t1, t2), Vector3D::new(1., 1., 0.), Vector3D::new(3., 1., 0.)));
+///
+/// // Sharing an edge, touching at a vertex, and barely missing.
+/// let t2 = (Vector3D::new(0., 0., 0.), Vector3D::new(4., 0., 0.), Vector3D::new(2., 0., 3.));
+/// assert!(is_segment(triangle_triangle_intersection(t1, t2), Vector3D::new(0., 0., 0.), Vector3D::new(4., 0., 0.)));
+/// let t2 = (Vector3D::new(1., 1., 0.), Vector3D::new(1., 2., 1.), Vector3D::new(2., 1., 1.));
+/// assert!(is_segment(triangle_triangle_intersection(t1, t2), Vector3D::new(1., 1., 0.), Vector3D::new(1., 1., 0.)));
+/// let t2 = (Vector3D::new(1., 1., f64::EPSILON), Vector3D::new(1., 2., 1.), Vector3D::new(2., 1., 1.));
+/// assert_eq!(triangle_triangle_intersection(t1, t2), None);
+///
+/// // Coplanar triangles.
+/// let t2 = (Vector3D::new(1., 1., 0.), Vector3D::new(5., 1., 0.), Vector3D::new(1., 5., 0.));
+/// let Some(TriangleIntersection::Coplanar(polygon)) = triangle_triangle_intersection(t1, t2) else { panic!() };
+/// assert_eq!(polygon.len(), 3);
+/// for corner in [Vector3D::new(1., 1., 0.), Vector3D::new(3., 1., 0.), Vector3D::new(1., 3., 0.)] {
+///     assert!(polygon.iter().any(|&p| (p - corner).norm() < 1e-12));
+/// }
+///
+/// // A degenerate triangle acts as its longest edge, which pierces `t1` in a point; a parallel triangle misses it.
+/// let t2 = (Vector3D::new(1., 1., -1.), Vector3D::new(1., 1., 1.), Vector3D::new(1., 1., 0.5));
+/// assert!(is_segment(triangle_triangle_intersection(t1, t2), Vector3D::new(1., 1., 0.), Vector3D::new(1., 1., 0.)));
+/// let t2 = (Vector3D::new(0., 0., 1.), Vector3D::new(4., 0., 1.), Vector3D::new(0., 4., 1.));
+/// assert_eq!(triangle_triangle_intersection(t1, t2), None);
+///
+/// // On small random triangles, the triangles intersect iff an edge of one intersects the other.
+/// let mut seed = 12345u64;
+/// let mut random = || {
+///     seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+///     f64::from((seed >> 33) as u32 % 4)
+/// };
+/// let mut triangle = || (Vector3D::new(random(), random(), random()), Vector3D::new(random(), random(), random()), Vector3D::new(random(), random(), random()));
+/// for _ in 0..2000 {
+///     let (t1, t2) = (triangle(), triangle());
+///     let edges = |t: (Vector3D, Vector3D, Vector3D)| [(t.0, t.1), (t.1, t.2), (t.2, t.0)];
+///     let hits = edges(t1).into_iter().filter_map(|(p, q)| segment_triangle_intersection(p, q, t2))
+///         .chain(edges(t2).into_iter().filter_map(|(p, q)| segment_triangle_intersection(p, q, t1)))
+///         .map(|(point, _)| point)
+///         .collect::<Vec<_>>();
+///     let intersection = triangle_triangle_intersection(t1, t2);
+///     assert_eq!(intersection.is_some(), !hits.is_empty(), "{t1:?} {t2:?}");
+///     if let Some(TriangleIntersection::Segment(a, b)) = intersection {
+///         for p in hits {
+///             assert!(hutspot::geom::distance_to_segment(p, a, b) < 1e-9, "{t1:?} {t2:?}");
+///         }
+///     }
+/// }
+/// ```
+#[must_use]
+#[allow(clippy::many_single_char_names)]
+pub fn triangle_triangle_intersection(t1: (Vector3D, Vector3D, Vector3D), t2: (Vector3D, Vector3D, Vector3D)) -> Option<TriangleIntersection> {
+    let lerp = |(p, q): (Vector3D, Vector3D), (s, t): (f64, f64)| TriangleIntersection::Segment(p + (q - p) * s, p + (q - p) * t);
+    match (is_degenerate_triangle(t1), is_degenerate_triangle(t2)) {
+        (true, true) => {
+            let ((a, b), (c, d)) = (longest_edge(t1), longest_edge(t2));
+            return match classify_3d_lineseg_intersection(a, b, c, d) {
+                SegmentIntersection::Overlap(x, y) => Some(TriangleIntersection::Segment(x, y)),
+                intersection => intersection.point().map(|x| TriangleIntersection::Segment(x, x)),
+            };
+        }
+        (true, false) => return segment_triangle_range(longest_edge(t1).0, longest_edge(t1).1, t2).map(|range| lerp(longest_edge(t1), range)),
+        (false, true) => return segment_triangle_range(longest_edge(t2).0, longest_edge(t2).1, t1).map(|range| lerp(longest_edge(t2), range)),
+        (false, false) => {}
+    }
+
+    // The sides of the vertices of each triangle with respect to the plane of the other.
+    let d1 = (
+        orient3d_sign(t2.0, t2.1, t2.2, t1.0),
+        orient3d_sign(t2.0, t2.1, t2.2, t1.1),
+        orient3d_sign(t2.0, t2.1, t2.2, t1.2),
+    );
+    if d1.0 * d1.1 > 0 && d1.0 * d1.2 > 0 {
+        return None;
+    }
+    if d1 == (0, 0, 0) {
+        return coplanar_triangle_intersection(t1, t2).map(TriangleIntersection::Coplanar);
+    }
+    let mut d2 = (
+        orient3d_sign(t1.0, t1.1, t1.2, t2.0),
+        orient3d_sign(t1.0, t1.1, t1.2, t2.1),
+        orient3d_sign(t1.0, t1.1, t1.2, t2.2),
+    );
+    if d2.0 * d2.1 > 0 && d2.0 * d2.2 > 0 {
+        return None;
+    }
+
+    // Reorder both triangles such that `p1` (`p2`) is alone on the positive side of the plane of the other triangle.
+    let (k1, flip2) = lone_vertex_first(d1);
+    let (t1, mut t2) = (rotate(t1, k1), t2);
+    if flip2 {
+        t2 = flip(t2);
+        d2 = flip(d2);
+    }
+    let (k2, flip1) = lone_vertex_first(d2);
+    let (mut t1, t2) = (t1, rotate(t2, k2));
+    if flip1 {
+        t1 = flip(t1);
+    }
+
+    // Both triangles cross the line in which their planes meet in an interval. The orientations of the vertices decide how these intervals overlap.
+    let ((p1, q1, r1), (p2, q2, r2)) = (t1, t2);
+    let crossing1 =
+        |x: Vector3D, y: Vector3D| x + (y - x) * segment_plane_crossing(x, y, orient3d_sign(p2, q2, r2, x), orient3d_sign(p2, q2, r2, y), t2);
+    let crossing2 =
+        |x: Vector3D, y: Vector3D| x + (y - x) * segment_plane_crossing(x, y, orient3d_sign(p1, q1, r1, x), orient3d_sign(p1, q1, r1, y), t1);
+    let segment = if orient3d_sign(p1, q1, r2, p2) > 0 {
+        if orient3d_sign(p1, r1, r2, p2) > 0 {
+            return None;
+        }
+        if orient3d_sign(p1, r1, q2, p2) > 0 {
+            (crossing1(p1, r1), crossing2(p2, r2))
+        } else {
+            (crossing2(p2, q2), crossing2(p2, r2))
+        }
+    } else {
+        if orient3d_sign(p1, q1, q2, p2) < 0 {
+            return None;
+        }
+        if orient3d_sign(p1, r1, q2, p2) >= 0 {
+            (crossing1(p1, r1), crossing1(p1, q1))
+        } else {
+            (crossing2(p2, q2), crossing1(p1, q1))
+        }
+    };
+    Some(TriangleIntersection::Segment(segment.0, segment.1))
+}
@@ -1,9 +1,243 @@
-use itertools::Itertools;
+// Neighbor functions (and the adjacencies that generalize them) are taken by value, so that closures can be passed directly.
+#![allow(clippy::needless_pass_by_value)]
+
+use itertools::Either;
+use nalgebra::DMatrix;
 use ordered_float::OrderedFloat;
-use std::collections::HashSet;
-use std::fmt::Debug;
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::fmt::{self, Debug};
 use std::hash::Hash;
 
+// Marks a missing index.
+const NONE: usize = usize::MAX;
+
+// A graph that can enumerate the neighbors of its nodes: a neighbor function (a closure `Fn(T) -> Vec<T>`), or a `Graph`, whose neighbors are slices that do not need to be allocated for every query.
+// The public functions take neighbor functions, so that the types of closures are inferred, and `Graph` runs the same algorithms through its methods.
+trait Adjacency<T> {
+    // The neighbors of `node` (its successors, for directed graphs).
+    fn neighbors(&self, node: T) -> impl Iterator<Item = T>;
+
+    // The neighbors of `node` with the weights of the edges to them, if the graph stores its own weights. These must agree with the weight function that comes with the graph.
+    // The default returns `None`, which falls back to the weight function.
+    fn weighted_neighbors(&self, _node: T) -> Option<impl Iterator<Item = (T, OrderedFloat<f64>)>> {
+        None::<std::iter::Empty<_>>
+    }
+}
+
+impl<T, F: Fn(T) -> Vec<T>> Adjacency<T> for F {
+    fn neighbors(&self, node: T) -> impl Iterator<Item = T> {
+        self(node).into_iter()
+    }
+}
+
+// An adjacency that only enumerates the neighbors of another one, for searches whose weights are derived from the weight function (such as reduced weights), and so may not come from the stored weights.
+struct NeighborsOnly<'a, A>(&'a A);
+
+impl<T, A: Adjacency<T>> Adjacency<T> for NeighborsOnly<'_, A> {
+    fn neighbors(&self, node: T) -> impl Iterator<Item = T> {
+        self.0.neighbors(node)
+    }
+}
+
+// The neighbors of `node` with the weights of the edges to them: the stored weights of the adjacency if it has them, and otherwise the weight function.
+fn weighted_neighbors<'a, T: Copy + 'a>(
+    neighbor_function: &'a impl Adjacency<T>,
+    weight_function: &'a impl Fn(T, T) -> OrderedFloat<f64>,
+    node: T,
+) -> impl Iterator<Item = (T, OrderedFloat<f64>)> + 'a {
+    neighbor_function.weighted_neighbors(node).map_or_else(
+        || {
+            Either::Right(
+                neighbor_function
+                    .neighbors(node)
+                    .map(move |neighbor| (neighbor, weight_function(node, neighbor))),
+            )
+        },
+        Either::Left,
+    )
+}
+
+/// Errors that can occur when building a `Graph` from a list of edges.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphError {
+    /// The edge refers to a node outside `0..nr_nodes`.
+    NodeOutOfBounds(usize, usize),
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NodeOutOfBounds(u, v) => write!(f, "edge ({u}, {v}) refers to a node that does not exist"),
+        }
+    }
+}
+
+impl std::error::Error for GraphError {}
+
+/// A graph on the nodes `0..n`, stored in compressed sparse row (CSR) form: the neighbors of every node are a contiguous, sorted slice of one array, with the weights of the edges to them alongside.
+///
+/// Undirected graphs store every edge in both directions. Unweighted graphs have unit weights.
+///
+/// The algorithms of this module run on a graph through its methods of the same name. These take the same arguments without the neighbor function and the weight function,
+/// whose place is taken by the stored weights, and without the list of nodes where that would be all nodes. Other costs, such as capacities, are still passed as functions.
+/// To use other weights, pass the neighbors to the function itself, as in `find_shortest_path(a, b, |v| graph.neighbors(v).to_vec(), weight_function)`.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::{find_shortest_path, Graph};
+/// use ordered_float::OrderedFloat;
+/// use std::collections::HashSet;
+///
+/// let graph = Graph::from_weighted_edges(7, [(1, 2, 4.), (1, 3, 2.), (2, 3, 5.), (3, 5, 3.), (4, 2, 10.), (4, 6, 11.), (5, 4, 4.)], true).unwrap();
+/// assert_eq!(graph.neighbors(1), &[2, 3]);
+/// assert_eq!(graph.edge_weight(4, 6), Some(OrderedFloat(11.)));
+/// assert_eq!(graph.edge_weight(6, 4), None);
+///
+/// let (path, cost) = graph.find_shortest_path(1, 6).unwrap();
+/// assert_eq!(path, vec![1, 3, 5, 4, 6]);
+/// assert_eq!(cost, OrderedFloat(2.0 + 3.0 + 4.0 + 11.0));
+/// assert_eq!(graph.find_shortest_path_bidirectional(1, 6), Some((path, cost)));
+/// assert_eq!(graph.find_shortest_path(6, 1), None);
+///
+/// // Other weights on the same edges.
+/// let weight_function = |u, v| OrderedFloat(if (u, v) == (1, 3) { 10. } else { 1. });
+/// let (path, cost) = find_shortest_path(1, 6, |v| graph.neighbors(v).to_vec(), weight_function).unwrap();
+/// assert_eq!(path, vec![1, 2, 3, 5, 4, 6]);
+/// assert_eq!(cost, OrderedFloat(5.));
+///
+/// let graph = Graph::from_edges(7, [(1, 2), (1, 3), (2, 3), (3, 5), (4, 2), (4, 6), (5, 4)], false).unwrap();
+/// assert_eq!(graph.neighbors(4), &[2, 5, 6]);
+/// assert_eq!(graph.find_ccs(), vec![HashSet::from([0]), HashSet::from([1, 2, 3, 4, 5, 6])]);
+/// assert_eq!(graph.find_shortest_path(1, 6), Some((vec![1, 2, 4, 6], OrderedFloat(3.))));
+///
+/// assert!(Graph::from_edges(2, [(0, 2)], true).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Graph {
+    // The neighbors of node `v` are `targets[offsets[v]..offsets[v + 1]]`.
+    offsets: Vec<usize>,
+    targets: Vec<usize>,
+    weights: Vec<OrderedFloat<f64>>,
+    directed: bool,
+}
+
+impl Graph {
+    /// Builds an unweighted graph on the nodes `0..nr_nodes` from its edges.
+    /// # Arguments
+    /// * `nr_nodes` - The number of nodes.
+    /// * `edges` - The edges, as pairs of nodes. Parallel edges and self-loops are kept.
+    /// * `directed` - Whether the edges are directed. Otherwise, every edge is stored in both directions.
+    /// # Returns
+    /// * `Result<Graph, GraphError>` - The graph, or an error if an edge refers to a node outside `0..nr_nodes`.
+    pub fn from_edges(nr_nodes: usize, edges: impl IntoIterator<Item = (usize, usize)>, directed: bool) -> Result<Self, GraphError> {
+        Self::from_weighted_edges(nr_nodes, edges.into_iter().map(|(u, v)| (u, v, 1.)), directed)
+    }
+
+    /// Builds a weighted graph on the nodes `0..nr_nodes` from its edges.
+    /// # Arguments
+    /// * `nr_nodes` - The number of nodes.
+    /// * `edges` - The edges, as pairs of nodes with a weight. Parallel edges and self-loops are kept.
+    /// * `directed` - Whether the edges are directed. Otherwise, every edge is stored in both directions.
+    /// # Returns
+    /// * `Result<Graph, GraphError>` - The graph, or an error if an edge refers to a node outside `0..nr_nodes`.
+    pub fn from_weighted_edges(nr_nodes: usize, edges: impl IntoIterator<Item = (usize, usize, f64)>, directed: bool) -> Result<Self, GraphError> {
+        let mut arcs = vec![];
+        for (u, v, weight) in edges {
+            if u >= nr_nodes || v >= nr_nodes {
+                return Err(GraphError::NodeOutOfBounds(u, v));
+            }
+            arcs.push((u, v, OrderedFloat(weight)));
+            if !directed && u != v {
+                arcs.push((v, u, OrderedFloat(weight)));
+            }
+        }
+        arcs.sort_unstable();
+
+        let mut offsets = vec![0; nr_nodes + 1];
+        for &(u, _, _) in &arcs {
+            offsets[u + 1] += 1;
+        }
+        for v in 0..nr_nodes {
+            offsets[v + 1] += offsets[v];
+        }
+        Ok(Self {
+            offsets,
+            targets: arcs.iter().map(|&(_, v, _)| v).collect(),
+            weights: arcs.iter().map(|&(_, _, weight)| weight).collect(),
+            directed,
+        })
+    }
+
+    #[must_use]
+    pub const fn nr_nodes(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    /// The number of stored (directed) edges, which counts every undirected edge (except self-loops) twice.
+    #[must_use]
+    pub const fn nr_edges(&self) -> usize {
+        self.targets.len()
+    }
+
+    #[must_use]
+    pub const fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    #[must_use]
+    pub const fn nodes(&self) -> std::ops::Range<usize> {
+        0..self.nr_nodes()
+    }
+
+    /// The neighbors of node `v` (its successors, for directed graphs), in increasing order.
+    #[must_use]
+    pub fn neighbors(&self, v: usize) -> &[usize] {
+        &self.targets[self.offsets[v]..self.offsets[v + 1]]
+    }
+
+    /// The neighbors of node `v` with the weights of the edges to them.
+    pub fn weighted_neighbors(&self, v: usize) -> impl Iterator<Item = (usize, OrderedFloat<f64>)> + '_ {
+        let range = self.offsets[v]..self.offsets[v + 1];
+        self.targets[range.clone()].iter().copied().zip(self.weights[range].iter().copied())
+    }
+
+    /// The weight of the edge from `u` to `v` (the smallest, if there are parallel edges), or `None` if there is no such edge.
+    #[must_use]
+    pub fn edge_weight(&self, u: usize, v: usize) -> Option<OrderedFloat<f64>> {
+        let start = self.offsets[u];
+        let neighbors = self.neighbors(u);
+        let i = neighbors.partition_point(|&w| w < v);
+        (neighbors.get(i) == Some(&v)).then(|| self.weights[start + i])
+    }
+
+    /// The edge weights as a weight function, for the algorithms that take one. Missing edges have infinite weight.
+    pub fn weight_function(&self) -> impl Fn(usize, usize) -> OrderedFloat<f64> + '_ {
+        |u, v| self.edge_weight(u, v).unwrap_or(OrderedFloat(f64::INFINITY))
+    }
+}
+
+impl Adjacency<usize> for Graph {
+    fn neighbors(&self, node: usize) -> impl Iterator<Item = usize> {
+        Self::neighbors(self, node).iter().copied()
+    }
+
+    fn weighted_neighbors(&self, node: usize) -> Option<impl Iterator<Item = (usize, OrderedFloat<f64>)>> {
+        Some(Self::weighted_neighbors(self, node))
+    }
+}
+
+impl Adjacency<usize> for &Graph {
+    fn neighbors(&self, node: usize) -> impl Iterator<Item = usize> {
+        Graph::neighbors(self, node).iter().copied()
+    }
+
+    fn weighted_neighbors(&self, node: usize) -> Option<impl Iterator<Item = (usize, OrderedFloat<f64>)>> {
+        Some(Graph::weighted_neighbors(self, node))
+    }
+}
+
 //
 // 1 -> 2 <- 4 -> 6
 //  \   |    ^
@@ -18,7 +252,6 @@ use std::hash::Hash;
 /// * `b` - The target element.
 /// * `neighbor_function` - A function that returns the neighbors of a given element.
 /// * `weight_function` - A function that returns the weight of the edge between two elements.
-/// * `cache` - A mutable reference to a cache for storing the neighbors and weights for each element.
 ///
 /// # Returns
 /// * `Option<(Vec<T>, OrderedFloat<f64>)>` - An optional tuple containing the shortest path as a vector of elements
@@ -28,7 +261,6 @@ use std::hash::Hash;
 /// ```
 /// use hutspot::graph::find_shortest_path;
 /// use ordered_float::OrderedFloat;
-/// use std::collections::HashMap;
 ///
 /// let neighbor_function = |node: u32| -> Vec<u32> {
 ///     match node {
@@ -55,14 +287,13 @@ use std::hash::Hash;
 ///     }
 /// };
 ///
-/// let mut cache: HashMap<u32, Vec<(u32, OrderedFloat<f64>)>> = HashMap::new();
-/// let result = find_shortest_path(1, 6, neighbor_function, weight_function, &mut cache);
+/// let result = find_shortest_path(1, 6, neighbor_function, weight_function);
 /// assert!(result.is_some());
 /// let (path, cost) = result.unwrap();
 /// assert_eq!(path, vec![1, 3, 5, 4, 6]);
 /// assert_eq!(cost, OrderedFloat(2.0 + 3.0 + 4.0 + 11.0));
 ///
-/// let result = find_shortest_path(6, 1, neighbor_function, weight_function, &mut cache);
+/// let result = find_shortest_path(6, 1, neighbor_function, weight_function);
 /// assert!(result.is_none());
 /// ```
 pub fn find_shortest_path<T: Eq + Hash + Clone + Copy>(
@@ -70,16 +301,19 @@ pub fn find_shortest_path<T: Eq + Hash + Clone + Copy>(
     b: T,
     neighbor_function: impl Fn(T) -> Vec<T>,
     weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+) -> Option<(Vec<T>, OrderedFloat<f64>)> {
+    shortest_path(a, b, &neighbor_function, &weight_function)
+}
+
+fn shortest_path<T: Eq + Hash + Clone + Copy>(
+    a: T,
+    b: T,
+    neighbor_function: &impl Adjacency<T>,
+    weight_function: &impl Fn(T, T) -> OrderedFloat<f64>,
 ) -> Option<(Vec<T>, OrderedFloat<f64>)> {
     pathfinding::prelude::dijkstra(
         &a,
-        |&elem| {
-            let neighbors = neighbor_function(elem)
-                .iter()
-                .map(|&neighbor| (neighbor, weight_function(elem, neighbor)))
-                .collect_vec();
-            neighbors
-        },
+        |&elem| weighted_neighbors(neighbor_function, weight_function, elem),
         |&elem| elem == b,
     )
 }
@@ -90,255 +324,5872 @@ pub fn find_shortest_path_astar<T: Eq + Hash + Clone + Copy>(
     neighbor_function: impl Fn(T) -> Vec<T>,
     weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
     heuristic_function: impl Fn(T, T) -> OrderedFloat<f64>,
+) -> Option<(Vec<T>, OrderedFloat<f64>)> {
+    shortest_path_astar(a, b, neighbor_function, weight_function, heuristic_function)
+}
+
+fn shortest_path_astar<T: Eq + Hash + Clone + Copy>(
+    a: T,
+    b: T,
+    neighbor_function: impl Adjacency<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+    heuristic_function: impl Fn(T, T) -> OrderedFloat<f64>,
 ) -> Option<(Vec<T>, OrderedFloat<f64>)> {
     pathfinding::directed::astar::astar(
         &a,
-        |&elem| {
-            let neighbors = neighbor_function(elem)
-                .iter()
-                .map(|&neighbor| (neighbor, weight_function(elem, neighbor)))
-                .collect_vec();
-            neighbors
-        },
+        |&elem| weighted_neighbors(&neighbor_function, &weight_function, elem),
         |&elem| heuristic_function(elem, b),
         |&elem| elem == b,
     )
 }
 
-/// Finds the shortest cycle through element `a` using the `find_shortest_path` function (Dijkstra's algorithm).
+// One side of a bidirectional search: the elements reached so far with their distance and the index of their predecessor, and a queue of indices keyed by distance plus potential.
+struct Search<T> {
+    index: HashMap<T, usize>,
+    nodes: Vec<(T, OrderedFloat<f64>, usize)>,
+    queue: BinaryHeap<Reverse<(OrderedFloat<f64>, usize)>>,
+}
+
+impl<T: Eq + Hash + Clone + Copy> Search<T> {
+    fn new(start: T, key: OrderedFloat<f64>) -> Self {
+        Self {
+            index: HashMap::from([(start, 0)]),
+            nodes: vec![(start, OrderedFloat(0.), NONE)],
+            queue: BinaryHeap::from([Reverse((key, 0))]),
+        }
+    }
+
+    fn path(&self, mut index: usize) -> Vec<T> {
+        let mut path = vec![];
+        while index != NONE {
+            path.push(self.nodes[index].0);
+            index = self.nodes[index].2;
+        }
+        path
+    }
+}
+
+// Bidirectional Dijkstra on the edge weights reduced by `potential` (a feasible potential towards `b`), which is A* when the potential is not zero.
+// The reverse search uses the negated potential, so both searches work on the same reduced graph and may stop once their smallest keys add up to the best path found.
+fn bidirectional<T: Eq + Hash + Clone + Copy>(
+    a: T,
+    b: T,
+    neighbor_function: &impl Adjacency<T>,
+    reverse_neighbor_function: &impl Adjacency<T>,
+    weight_function: &impl Fn(T, T) -> OrderedFloat<f64>,
+    potential: &impl Fn(T) -> OrderedFloat<f64>,
+) -> Option<(Vec<T>, OrderedFloat<f64>)> {
+    if a == b {
+        return Some((vec![a], OrderedFloat(0.)));
+    }
+    let mut searches = [Search::new(a, potential(a)), Search::new(b, -potential(b))];
+    // The best path found so far, as its weight and the indices of its middle element in both searches.
+    let mut best: Option<(OrderedFloat<f64>, [usize; 2])> = None;
+    while let (Some(&Reverse((forward, _))), Some(&Reverse((reverse, _)))) = (searches[0].queue.peek(), searches[1].queue.peek()) {
+        if best.is_some_and(|(best, _)| forward + reverse >= best) {
+            break;
+        }
+        let side = usize::from(forward > reverse);
+        let signed_potential = |node: T| if side == 0 { potential(node) } else { -potential(node) };
+        let Reverse((key, current)) = searches[side].queue.pop().unwrap();
+        let (node, cost, _) = searches[side].nodes[current];
+        if key > cost + signed_potential(node) {
+            continue;
+        }
+        let neighbors: Vec<(T, OrderedFloat<f64>)> = if side == 0 {
+            weighted_neighbors(neighbor_function, weight_function, node).collect()
+        } else {
+            weighted_neighbors(reverse_neighbor_function, &|u, v| weight_function(v, u), node).collect()
+        };
+        for (neighbor, weight) in neighbors {
+            let next = cost + weight;
+            let search = &mut searches[side];
+            let index = match search.index.entry(neighbor) {
+                Entry::Occupied(entry) => {
+                    let index = *entry.get();
+                    if next >= search.nodes[index].1 {
+                        continue;
+                    }
+                    search.nodes[index] = (neighbor, next, current);
+                    index
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(search.nodes.len());
+                    search.nodes.push((neighbor, next, current));
+                    search.nodes.len() - 1
+                }
+            };
+            search.queue.push(Reverse((next + signed_potential(neighbor), index)));
+            let other = &searches[1 - side];
+            if let Some(&other_index) = other.index.get(&neighbor) {
+                let total = next + other.nodes[other_index].1;
+                if best.is_none_or(|(best, _)| total < best) {
+                    best = Some((total, if side == 0 { [index, other_index] } else { [other_index, index] }));
+                }
+            }
+        }
+    }
+
+    best.map(|(cost, [forward, reverse])| {
+        let mut path = searches[0].path(forward);
+        path.reverse();
+        path.extend(searches[1].path(reverse).into_iter().skip(1));
+        (path, cost)
+    })
+}
+
+/// Finds the shortest path from element `a` to element `b` using bidirectional Dijkstra's algorithm, searching forward from `a` and backward from `b` at the same time.
+///
+/// This explores far fewer elements than `find_shortest_path` on large graphs, and returns the same result (up to ties).
 ///
 /// # Arguments
-/// * `a` - The starting element, which is also the element through which the cycle must pass.
-/// * `neighbor_function` - A function that returns the neighbors of a given element.
-/// * `weight_function` - A function that returns the weight of the edge between two elements.
-/// * `cache` - A mutable reference to a cache for storing the neighbors and weights for each element.
+/// * `a` - The starting element.
+/// * `b` - The target element.
+/// * `neighbor_function` - A function that returns the (outgoing) neighbors of a given element.
+/// * `reverse_neighbor_function` - A function that returns the elements that have a given element as their neighbor. For undirected graphs, this is the same as `neighbor_function`.
+/// * `weight_function` - A function that returns the (non-negative) weight of the edge from the first element to the second.
 ///
 /// # Returns
-/// * `Option<(Vec<T>, OrderedFloat<f64>)>` - An optional tuple containing the shortest cycle as a vector of elements
-///    and the total weight of the cycle as an `OrderedFloat<f64>`. Returns `None` if no cycle is found.
+/// * `Option<(Vec<T>, OrderedFloat<f64>)>` - An optional tuple containing the shortest path as a vector of elements
+///   and the total weight of the path as an `OrderedFloat<f64>`. Returns `None` if no path is found.
 ///
 /// # Example
 /// ```
-/// use hutspot::graph::find_shortest_cycle;
+/// use hutspot::graph::find_shortest_path_bidirectional;
 /// use ordered_float::OrderedFloat;
-/// use std::collections::HashMap;
-///
-/// let neighbor_function = |node: u32| -> Vec<u32> {
-///     match node {
-///         1 => vec![2, 3],
-///         2 => vec![3],
-///         3 => vec![5],
-///         4 => vec![2, 6],
-///         5 => vec![4],
-///         6 => vec![],
-///         _ => vec![],
-///     }
-/// };
-///
-/// let weight_function = |a: u32, b: u32| -> OrderedFloat<f64> {
-///     match (a, b) {
-///         (1, 2) => 4.0.into(),
-///         (1, 3) => 2.0.into(),
-///         (2, 3) => 5.0.into(),
-///         (3, 5) => 3.0.into(),
-///         (4, 2) => 10.0.into(),
-///         (4, 6) => 11.0.into(),
-///         (5, 4) => 4.0.into(),
-///         _ => OrderedFloat(f64::INFINITY),
-///     }
-/// };
 ///
-/// let mut cache: HashMap<u32, Vec<(u32, OrderedFloat<f64>)>> = HashMap::new();
-/// let result = find_shortest_cycle(1, neighbor_function, weight_function, &mut cache);
-/// assert!(result.is_none());
+/// // The graph of `find_shortest_path`, together with its reversal.
+/// let edges = [(1, 2, 4.), (1, 3, 2.), (2, 3, 5.), (3, 5, 3.), (4, 2, 10.), (4, 6, 11.), (5, 4, 4.)];
+/// let neighbor_function = |node: u32| -> Vec<u32> { edges.iter().filter(|e| e.0 == node).map(|e| e.1).collect() };
+/// let reverse_neighbor_function = |node: u32| -> Vec<u32> { edges.iter().filter(|e| e.1 == node).map(|e| e.0).collect() };
+/// let weight_function = |a: u32, b: u32| -> OrderedFloat<f64> { OrderedFloat(edges.iter().find(|e| e.0 == a && e.1 == b).unwrap().2) };
 ///
-/// let result = find_shortest_cycle(3, neighbor_function, weight_function, &mut cache);
+/// let result = find_shortest_path_bidirectional(1, 6, neighbor_function, reverse_neighbor_function, weight_function);
 /// assert!(result.is_some());
 /// let (path, cost) = result.unwrap();
-/// assert_eq!(path, vec![3, 5, 4, 2]);
-/// assert_eq!(cost, OrderedFloat(3.0 + 4.0 + 10.0 + 5.0));
+/// assert_eq!(path, vec![1, 3, 5, 4, 6]);
+/// assert_eq!(cost, OrderedFloat(2.0 + 3.0 + 4.0 + 11.0));
+///
+/// let result = find_shortest_path_bidirectional(6, 1, neighbor_function, reverse_neighbor_function, weight_function);
+/// assert!(result.is_none());
 /// ```
-pub fn find_shortest_cycle<T: Eq + Hash + Clone + Copy>(
+pub fn find_shortest_path_bidirectional<T: Eq + Hash + Clone + Copy>(
     a: T,
+    b: T,
     neighbor_function: impl Fn(T) -> Vec<T>,
+    reverse_neighbor_function: impl Fn(T) -> Vec<T>,
     weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
 ) -> Option<(Vec<T>, OrderedFloat<f64>)> {
-    neighbor_function(a)
-        .iter()
-        .filter_map(|&neighbor| find_shortest_path(neighbor, a, &neighbor_function, &weight_function))
-        .sorted_by(|(_, cost1), (_, cost2)| cost1.cmp(cost2))
-        .next()
-        .map(|(path, score)| {
-            let (last, rest) = path.split_last().unwrap();
-            ([&[*last], rest].concat(), score + weight_function(a, *path.first().unwrap()))
-        })
+    bidirectional(a, b, &neighbor_function, &reverse_neighbor_function, &weight_function, &|_| {
+        OrderedFloat(0.)
+    })
 }
 
-/// Finds the connected components of a graph.
+/// Finds the shortest path from element `a` to element `b` using bidirectional A*, searching forward from `a` and backward from `b` at the same time.
+///
+/// Both searches are guided by the average of the forward estimate `heuristic_function(x, b)` and the backward estimate `heuristic_function(a, x)`,
+/// which keeps them consistent with each other, so the result is exact (up to ties) as long as the heuristic is consistent (such as the Euclidean distance between elements).
+///
+/// # Arguments
+/// * `a` - The starting element.
+/// * `b` - The target element.
+/// * `neighbor_function` - A function that returns the (outgoing) neighbors of a given element.
+/// * `reverse_neighbor_function` - A function that returns the elements that have a given element as their neighbor. For undirected graphs, this is the same as `neighbor_function`.
+/// * `weight_function` - A function that returns the (non-negative) weight of the edge from the first element to the second.
+/// * `heuristic_function` - A function that estimates the weight of the shortest path from the first element to the second, without overestimating it.
+///
+/// # Returns
+/// * `Option<(Vec<T>, OrderedFloat<f64>)>` - An optional tuple containing the shortest path as a vector of elements
+///   and the total weight of the path as an `OrderedFloat<f64>`. Returns `None` if no path is found.
 ///
 /// # Example
 /// ```
-/// use hutspot::graph::find_ccs;
-/// use std::collections::HashSet;
-/// let neighbor_function_undirected = |node: u32| -> Vec<u32> {
-///     match node {
-///         1 => vec![2, 3],
-///         2 => vec![1, 3, 4],
-///         3 => vec![1, 2, 5],
-///         4 => vec![2, 5, 6],
-///         5 => vec![3, 4],
-///         6 => vec![5],
-///         _ => vec![],
-///     }
+/// use hutspot::graph::{find_shortest_path, find_shortest_path_astar_bidirectional};
+/// use ordered_float::OrderedFloat;
+///
+/// // A 50x50 grid with diagonals and a wall in the middle.
+/// let neighbor_function = |(x, y): (i32, i32)| -> Vec<(i32, i32)> {
+///     (-1..=1)
+///         .flat_map(|dx| (-1..=1).map(move |dy| (x + dx, y + dy)))
+///         .filter(|&(nx, ny)| (nx, ny) != (x, y) && (0..50).contains(&nx) && (0..50).contains(&ny) && !(nx == 25 && ny > 5))
+///         .collect()
 /// };
+/// let distance = |(ax, ay): (i32, i32), (bx, by): (i32, i32)| OrderedFloat(f64::from(ax - bx).hypot(f64::from(ay - by)));
 ///
-/// let ccs = find_ccs(&vec![1, 2, 3, 4, 5, 6, 7], neighbor_function_undirected);
-/// assert_eq!(ccs[0], HashSet::from([1, 2, 3, 4, 5, 6]));
-/// assert_eq!(ccs[1], HashSet::from([7]));
+/// let (path, cost) = find_shortest_path_astar_bidirectional((0, 49), (49, 49), neighbor_function, neighbor_function, distance, distance).unwrap();
+/// let (_, expected) = find_shortest_path((0, 49), (49, 49), neighbor_function, distance).unwrap();
+/// assert!((cost - expected).abs() < 1e-9);
+/// assert_eq!(path.first(), Some(&(0, 49)));
+/// assert_eq!(path.last(), Some(&(49, 49)));
+/// assert!(path.contains(&(25, 5)));
 /// ```
-///
-pub fn find_ccs<T>(nodes: &[T], neighbor_function: impl Fn(T) -> Vec<T>) -> Vec<HashSet<T>>
-where
-    T: Eq + Hash + Clone + Copy,
-{
-    let mut visited = HashSet::new();
-    let mut ccs = vec![];
-    for &node in nodes {
-        if visited.contains(&node) {
-            continue;
+pub fn find_shortest_path_astar_bidirectional<T: Eq + Hash + Clone + Copy>(
+    a: T,
+    b: T,
+    neighbor_function: impl Fn(T) -> Vec<T>,
+    reverse_neighbor_function: impl Fn(T) -> Vec<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+    heuristic_function: impl Fn(T, T) -> OrderedFloat<f64>,
+) -> Option<(Vec<T>, OrderedFloat<f64>)> {
+    let potential = |node: T| (heuristic_function(node, b) - heuristic_function(a, node)) / 2.;
+    bidirectional(a, b, &neighbor_function, &reverse_neighbor_function, &weight_function, &potential)
+}
+
+/// The shortest paths from a set of seeds to every element they reach. See `find_shortest_path_forest`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShortestPathForest<T: Eq + Hash> {
+    /// The distance from the nearest seed to every reached element.
+    pub distances: HashMap<T, OrderedFloat<f64>>,
+    /// The previous element on a shortest path to every reached element, except the seeds.
+    pub predecessors: HashMap<T, T>,
+    /// The nearest seed of every reached element (the seed at the start of its shortest path).
+    pub seeds: HashMap<T, T>,
+}
+
+impl<T: Eq + Hash + Clone + Copy> ShortestPathForest<T> {
+    // Collects the reached elements, given as (element, distance, index of predecessor, index of seed).
+    fn from_nodes(nodes: &[(T, OrderedFloat<f64>, usize, usize)]) -> Self {
+        Self {
+            distances: nodes.iter().map(|&(node, distance, ..)| (node, distance)).collect(),
+            predecessors: nodes
+                .iter()
+                .filter(|&&(.., predecessor, _)| predecessor != NONE)
+                .map(|&(node, _, predecessor, _)| (node, nodes[predecessor].0))
+                .collect(),
+            seeds: nodes.iter().map(|&(node, .., seed)| (node, nodes[seed].0)).collect(),
         }
-        let cc = find_cc(node, &neighbor_function);
-        visited.extend(cc.clone());
-        ccs.push(cc);
     }
-    ccs.into_iter().collect()
+
+    /// Reconstructs the shortest path from the nearest seed to element `node`, by following the predecessors.
+    ///
+    /// # Returns
+    /// * `Option<Vec<T>>` - The path, starting at a seed and ending at `node`, or `None` if `node` was not reached.
+    #[must_use]
+    pub fn path(&self, node: T) -> Option<Vec<T>> {
+        if !self.distances.contains_key(&node) {
+            return None;
+        }
+        let mut path = vec![node];
+        while let Some(&previous) = self.predecessors.get(path.last().unwrap()) {
+            path.push(previous);
+        }
+        path.reverse();
+        Some(path)
+    }
 }
 
-/// Finds the connected component of a graph that contains a specific node (or reachability from this specific node).
-///
-/// # Example
-/// ```
-/// use hutspot::graph::find_cc;
-/// use std::collections::HashSet;
-/// let neighbor_function = |node: u32| -> Vec<u32> {
-///     match node {
-///         1 => vec![2, 3],
-///         2 => vec![3],
-///         3 => vec![5],
-///         4 => vec![2, 6],
-///         5 => vec![4],
-///         6 => vec![],
-///         _ => vec![],
-///     }
-/// };
-///
-/// let cc = find_cc(1, neighbor_function);
-/// assert_eq!(cc, HashSet::from([1, 2, 3, 4, 5, 6]));
+/// Finds the shortest paths from a set of seeds to every element, using Dijkstra's algorithm started from all seeds at once.
 ///
-/// let cc = find_cc(2, neighbor_function);
-/// assert_eq!(cc, HashSet::from([2, 3, 5, 4, 6]));
+/// This gives distance fields (such as the distance to a boundary) and Voronoi regions (the elements grouped by their nearest seed).
 ///
-/// let cc = find_cc(3, neighbor_function);
-/// assert_eq!(cc, HashSet::from([2, 3, 5, 4, 6]));
+/// # Arguments
+/// * `seeds` - The elements to start from, at distance zero.
+/// * `neighbor_function` - A function that returns the neighbors of a given element.
+/// * `weight_function` - A function that returns the (non-negative) weight of the edge between two elements.
+/// * `cutoff` - If given, the search stops at this distance: elements that are further away from every seed are not reached.
 ///
-/// let cc = find_cc(4, neighbor_function);
-/// assert_eq!(cc, HashSet::from([2, 3, 5, 4, 6]));
+/// # Returns
+/// * `ShortestPathForest<T>` - The distance, predecessor and nearest seed of every reached element. Ties between seeds are broken arbitrarily.
 ///
-/// let cc = find_cc(5, neighbor_function);
-/// assert_eq!(cc, HashSet::from([2, 3, 5, 4, 6]));
+/// # Example
+/// ```
+/// use hutspot::graph::find_shortest_path_forest;
+/// use ordered_float::OrderedFloat;
 ///
-/// let cc = find_cc(6, neighbor_function);
-/// assert_eq!(cc, HashSet::from([6]));
+/// // A path 0 - 1 - ... - 9, with seeds at both ends.
+/// let neighbor_function = |node: u32| -> Vec<u32> { [node.wrapping_sub(1), node + 1].into_iter().filter(|&n| n < 10).collect() };
+/// let weight_function = |_, _| OrderedFloat(1.);
 ///
-/// let cc = find_cc(7, neighbor_function);
-/// assert_eq!(cc, HashSet::from([7]));
+/// let forest = find_shortest_path_forest(&[0, 9], neighbor_function, weight_function, None);
+/// assert_eq!(forest.distances[&3], OrderedFloat(3.));
+/// assert_eq!(forest.distances[&7], OrderedFloat(2.));
+/// assert_eq!(forest.seeds[&3], 0);
+/// assert_eq!(forest.seeds[&7], 9);
+/// assert_eq!(forest.path(7), Some(vec![9, 8, 7]));
+/// assert_eq!(forest.path(0), Some(vec![0]));
 ///
-/// let neighbor_function_undirected = |node: u32| -> Vec<u32> {
+/// // With a cutoff, the middle of the path is not reached.
+/// let forest = find_shortest_path_forest(&[0, 9], neighbor_function, weight_function, Some(3.));
+/// assert_eq!(forest.distances.len(), 8);
+/// assert!(!forest.seeds.contains_key(&4));
+/// assert_eq!(forest.path(5), None);
+/// ```
+pub fn find_shortest_path_forest<T>(
+    seeds: &[T],
+    neighbor_function: impl Fn(T) -> Vec<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+    cutoff: Option<f64>,
+) -> ShortestPathForest<T>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    shortest_path_forest(seeds, &neighbor_function, &weight_function, OrderedFloat(cutoff.unwrap_or(f64::INFINITY)))
+}
+
+fn shortest_path_forest<T>(
+    seeds: &[T],
+    neighbor_function: &impl Adjacency<T>,
+    weight_function: &impl Fn(T, T) -> OrderedFloat<f64>,
+    cutoff: OrderedFloat<f64>,
+) -> ShortestPathForest<T>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    // Every reached element as its distance, the index of its predecessor, and the index of its seed.
+    let mut index = HashMap::new();
+    let mut nodes: Vec<(T, OrderedFloat<f64>, usize, usize)> = vec![];
+    for &seed in seeds {
+        if let Entry::Vacant(entry) = index.entry(seed) {
+            entry.insert(nodes.len());
+            nodes.push((seed, OrderedFloat(0.), NONE, nodes.len()));
+        }
+    }
+    let mut queue = (0..nodes.len()).map(|i| Reverse((OrderedFloat(0.), i))).collect::<BinaryHeap<_>>();
+    while let Some(Reverse((cost, current))) = queue.pop() {
+        let (node, distance, _, seed) = nodes[current];
+        if cost > distance {
+            continue;
+        }
+        for (neighbor, weight) in weighted_neighbors(neighbor_function, weight_function, node) {
+            let next = cost + weight;
+            if next > cutoff {
+                continue;
+            }
+            let i = match index.entry(neighbor) {
+                Entry::Occupied(entry) => {
+                    let i = *entry.get();
+                    if next >= nodes[i].1 {
+                        continue;
+                    }
+                    nodes[i] = (neighbor, next, current, seed);
+                    i
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(nodes.len());
+                    nodes.push((neighbor, next, current, seed));
+                    nodes.len() - 1
+                }
+            };
+            queue.push(Reverse((next, i)));
+        }
+    }
+
+    ShortestPathForest::from_nodes(&nodes)
+}
+
+/// The algorithm used to find shortest paths when weights may be negative. See `find_shortest_path_forest_negative`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NegativeWeightAlgorithm {
+    /// Bellman-Ford: relaxes all edges in rounds, until nothing changes.
+    BellmanFord,
+    /// The shortest path faster algorithm: Bellman-Ford with a queue of the elements whose distance changed. Usually much faster.
+    #[default]
+    Spfa,
+}
+
+// Finds a cycle in the graph of predecessors, if any. Such a cycle is always negative.
+fn predecessor_cycle(predecessors: &[usize]) -> Option<Vec<usize>> {
+    let mut visited = vec![NONE; predecessors.len()];
+    for start in 0..predecessors.len() {
+        let mut node = start;
+        while node != NONE && visited[node] == NONE {
+            visited[node] = start;
+            node = predecessors[node];
+        }
+        if node != NONE && visited[node] == start {
+            let mut cycle = vec![node];
+            let mut previous = predecessors[node];
+            while previous != node {
+                cycle.push(previous);
+                previous = predecessors[previous];
+            }
+            cycle.reverse();
+            return Some(cycle);
+        }
+    }
+    None
+}
+
+fn negative_shortest_path_forest<T>(
+    seeds: &[T],
+    neighbor_function: &impl Adjacency<T>,
+    weight_function: &impl Fn(T, T) -> OrderedFloat<f64>,
+    algorithm: NegativeWeightAlgorithm,
+) -> Result<ShortestPathForest<T>, CycleError<T>>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    // Index the reachable elements (the seeds first) and their edges, including self-loops.
+    let mut ids = HashMap::new();
+    let mut reached = vec![];
+    for &seed in seeds {
+        if let Entry::Vacant(entry) = ids.entry(seed) {
+            entry.insert(reached.len());
+            reached.push(seed);
+        }
+    }
+    let nr_seeds = reached.len();
+    let mut neighbors = vec![];
+    while neighbors.len() < reached.len() {
+        let node = reached[neighbors.len()];
+        let list = neighbor_function.neighbors(node).collect::<Vec<_>>();
+        for &neighbor in &list {
+            if let Entry::Vacant(entry) = ids.entry(neighbor) {
+                entry.insert(reached.len());
+                reached.push(neighbor);
+            }
+        }
+        neighbors.push(list);
+    }
+    let adjacency = neighbors
+        .into_iter()
+        .enumerate()
+        .map(|(u, list)| {
+            list.into_iter()
+                .map(|neighbor| (ids[&neighbor], weight_function(reached[u], neighbor)))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let n = reached.len();
+    let mut distance = vec![OrderedFloat(f64::INFINITY); n];
+    let mut predecessor = vec![NONE; n];
+    let mut seed = (0..n).collect::<Vec<_>>();
+    distance[..nr_seeds].fill(OrderedFloat(0.));
+    let cycle_error = |cycle: Vec<usize>| CycleError {
+        cycle: cycle.into_iter().map(|node| reached[node]).collect(),
+    };
+
+    match algorithm {
+        NegativeWeightAlgorithm::BellmanFord => {
+            // Without negative cycles, n - 1 rounds suffice; after that, the predecessors eventually form a cycle.
+            for round in 1.. {
+                let mut changed = false;
+                for u in 0..n {
+                    if distance[u].is_infinite() {
+                        continue;
+                    }
+                    for &(v, weight) in &adjacency[u] {
+                        if distance[u] + weight < distance[v] {
+                            distance[v] = distance[u] + weight;
+                            predecessor[v] = u;
+                            seed[v] = seed[u];
+                            changed = true;
+                        }
+                    }
+                }
+                if !changed {
+                    break;
+                }
+                if round >= n
+                    && let Some(cycle) = predecessor_cycle(&predecessor)
+                {
+                    return Err(cycle_error(cycle));
+                }
+            }
+        }
+        NegativeWeightAlgorithm::Spfa => {
+            let mut queue = (0..nr_seeds).collect::<VecDeque<_>>();
+            let mut queued = vec![false; n];
+            queued[..nr_seeds].fill(true);
+            let mut relaxations = 0;
+            while let Some(u) = queue.pop_front() {
+                queued[u] = false;
+                for &(v, weight) in &adjacency[u] {
+                    if distance[u] + weight < distance[v] {
+                        distance[v] = distance[u] + weight;
+                        predecessor[v] = u;
+                        seed[v] = seed[u];
+                        // Checking the predecessors every n relaxations finds negative cycles in amortized constant time.
+                        relaxations += 1;
+                        if relaxations % n == 0
+                            && let Some(cycle) = predecessor_cycle(&predecessor)
+                        {
+                            return Err(cycle_error(cycle));
+                        }
+                        if !queued[v] {
+                            queued[v] = true;
+                            queue.push_back(v);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let nodes = (0..n)
+        .filter(|&node| distance[node].is_finite())
+        .map(|node| (reached[node], distance[node], predecessor[node], seed[node]))
+        .collect::<Vec<_>>();
+    // Reached elements come before the ones they reach, so indices only need to be mapped to the filtered list.
+    let mut position = vec![NONE; n];
+    for (i, &(node, ..)) in nodes.iter().enumerate() {
+        position[ids[&node]] = i;
+    }
+    let nodes = nodes
+        .into_iter()
+        .map(|(node, distance, predecessor, seed)| {
+            (
+                node,
+                distance,
+                if predecessor == NONE { NONE } else { position[predecessor] },
+                position[seed],
+            )
+        })
+        .collect::<Vec<_>>();
+    Ok(ShortestPathForest::from_nodes(&nodes))
+}
+
+/// Finds the shortest paths from a set of seeds to every element they reach, when weights may be negative.
+///
+/// # Arguments
+/// * `seeds` - The elements to start from, at distance zero.
+/// * `neighbor_function` - A function that returns the (outgoing) neighbors of a given element.
+/// * `weight_function` - A function that returns the weight of the edge from the first element to the second. Weights may be negative.
+/// * `algorithm` - The algorithm to use.
+///
+/// # Returns
+/// * `Result<ShortestPathForest<T>, CycleError<T>>` - The distance, predecessor and nearest seed of every reached element, or a negative cycle reachable from the seeds.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::{find_shortest_path_forest_negative, NegativeWeightAlgorithm};
+/// use ordered_float::OrderedFloat;
+///
+/// let neighbor_function = |node: u32| -> Vec<u32> {
 ///     match node {
-///         1 => vec![2, 3],
-///         2 => vec![1, 3, 4],
-///         3 => vec![1, 2, 5],
-///         4 => vec![2, 5, 6],
-///         5 => vec![3, 4],
-///         6 => vec![5],
+///         0 => vec![1, 2],
+///         1 => vec![3],
+///         2 => vec![1],
 ///         _ => vec![],
 ///     }
 /// };
+/// // The detour through 2 is shorter, because of the negative edge (2, 1).
+/// let weight_function = |a: u32, b: u32| OrderedFloat(match (a, b) {
+///     (2, 1) => -3.,
+///     _ => 2.,
+/// });
 ///
-/// let cc = find_cc(1, neighbor_function_undirected);
-/// assert_eq!(cc, HashSet::from([1, 2, 3, 4, 5, 6]));
+/// for algorithm in [NegativeWeightAlgorithm::BellmanFord, NegativeWeightAlgorithm::Spfa] {
+///     let forest = find_shortest_path_forest_negative(&[0], neighbor_function, weight_function, algorithm).unwrap();
+///     assert_eq!(forest.distances[&3], OrderedFloat(1.));
+///     assert_eq!(forest.path(3), Some(vec![0, 2, 1, 3]));
+/// }
 ///
-/// let cc = find_cc(2, neighbor_function_undirected);
-/// assert_eq!(cc, HashSet::from([1, 2, 3, 4, 5, 6]));
+/// // With an edge (1, 2) of weight 2, the cycle 1 -> 2 -> 1 has weight -1.
+/// let neighbor_function = |node: u32| -> Vec<u32> { if node == 1 { vec![2, 3] } else { neighbor_function(node) } };
+/// let error = find_shortest_path_forest_negative(&[0], neighbor_function, weight_function, NegativeWeightAlgorithm::default()).unwrap_err();
+/// assert_eq!(error.cycle.len(), 2);
+/// assert!(error.cycle.contains(&1) && error.cycle.contains(&2));
+/// ```
+pub fn find_shortest_path_forest_negative<T>(
+    seeds: &[T],
+    neighbor_function: impl Fn(T) -> Vec<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+    algorithm: NegativeWeightAlgorithm,
+) -> Result<ShortestPathForest<T>, CycleError<T>>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    negative_shortest_path_forest(seeds, &neighbor_function, &weight_function, algorithm)
+}
+
+/// Finds the shortest path from element `a` to element `b` when weights may be negative, using the Bellman-Ford algorithm (or one of its variants).
 ///
-/// let cc = find_cc(3, neighbor_function_undirected);
-/// assert_eq!(cc, HashSet::from([1, 2, 3, 4, 5, 6]));
+/// # Arguments
+/// * `a` - The starting element.
+/// * `b` - The target element.
+/// * `neighbor_function` - A function that returns the (outgoing) neighbors of a given element.
+/// * `weight_function` - A function that returns the weight of the edge from the first element to the second. Weights may be negative.
+/// * `algorithm` - The algorithm to use.
 ///
-/// let cc = find_cc(4, neighbor_function_undirected);
-/// assert_eq!(cc, HashSet::from([1, 2, 3, 4, 5, 6]));
+/// # Returns
+/// * `Result<Option<(Vec<T>, OrderedFloat<f64>)>, CycleError<T>>` - The shortest path and its total weight (or `None` if no path is found), or a negative cycle reachable from `a`.
 ///
-/// let cc = find_cc(5, neighbor_function_undirected);
-/// assert_eq!(cc, HashSet::from([1, 2, 3, 4, 5, 6]));
+/// # Example
+/// ```
+/// use hutspot::graph::{find_shortest_path_bellman_ford, NegativeWeightAlgorithm};
+/// use ordered_float::OrderedFloat;
 ///
-/// let cc = find_cc(6, neighbor_function_undirected);
-/// assert_eq!(cc, HashSet::from([1, 2, 3, 4, 5, 6]));
+/// // Energies along a chain 0 -> 1 -> 2 -> 3, where a shortcut 0 -> 3 is more expensive.
+/// let neighbor_function = |node: u32| -> Vec<u32> {
+///     match node {
+///         0 => vec![1, 3],
+///         1 => vec![2],
+///         2 => vec![3],
+///         _ => vec![],
+///     }
+/// };
+/// let weight_function = |a: u32, b: u32| OrderedFloat(if (a, b) == (0, 3) { 1. } else { f64::from(b) - 2. });
 ///
-/// let cc = find_cc(7, neighbor_function_undirected);
-/// assert_eq!(cc, HashSet::from([7]));
+/// let result = find_shortest_path_bellman_ford(0, 3, neighbor_function, weight_function, NegativeWeightAlgorithm::default()).unwrap();
+/// assert_eq!(result, Some((vec![0, 1, 2, 3], OrderedFloat(0.))));
+/// assert_eq!(find_shortest_path_bellman_ford(3, 0, neighbor_function, weight_function, NegativeWeightAlgorithm::default()), Ok(None));
+/// ```
+#[allow(clippy::type_complexity)]
+pub fn find_shortest_path_bellman_ford<T>(
+    a: T,
+    b: T,
+    neighbor_function: impl Fn(T) -> Vec<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+    algorithm: NegativeWeightAlgorithm,
+) -> Result<Option<(Vec<T>, OrderedFloat<f64>)>, CycleError<T>>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    shortest_path_bellman_ford(a, b, neighbor_function, weight_function, algorithm)
+}
+
+#[allow(clippy::type_complexity)]
+fn shortest_path_bellman_ford<T>(
+    a: T,
+    b: T,
+    neighbor_function: impl Adjacency<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+    algorithm: NegativeWeightAlgorithm,
+) -> Result<Option<(Vec<T>, OrderedFloat<f64>)>, CycleError<T>>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let forest = negative_shortest_path_forest(&[a], &neighbor_function, &weight_function, algorithm)?;
+    Ok(forest.path(b).map(|path| (path, forest.distances[&b])))
+}
+
+/// Finds the shortest paths between all pairs of elements when weights may be negative, using Johnson's algorithm.
+///
+/// The weights are first made non-negative with potentials from one run of the shortest path faster algorithm, and then Dijkstra's algorithm is run from every element.
+///
+/// # Arguments
+/// * `nodes` - The elements of the graph. Elements that are only reachable from them are included as well.
+/// * `neighbor_function` - A function that returns the (outgoing) neighbors of a given element.
+/// * `weight_function` - A function that returns the weight of the edge from the first element to the second. Weights may be negative.
+///
+/// # Returns
+/// * `Result<HashMap<T, ShortestPathForest<T>>, CycleError<T>>` - The shortest paths from every element, or a negative cycle.
+///
+/// # Source
+/// Johnson, D. B. (1977). Efficient algorithms for shortest paths in sparse networks. Journal of the ACM.
 ///
+/// # Example
 /// ```
-pub fn find_cc<T>(node: T, neighbor_function: impl Fn(T) -> Vec<T>) -> HashSet<T>
+/// use hutspot::graph::find_all_shortest_paths_johnson;
+/// use ordered_float::OrderedFloat;
+///
+/// let neighbor_function = |node: u32| -> Vec<u32> {
+///     match node {
+///         0 => vec![1, 2],
+///         1 => vec![3],
+///         2 => vec![1],
+///         _ => vec![],
+///     }
+/// };
+/// let weight_function = |a: u32, b: u32| OrderedFloat(if (a, b) == (2, 1) { -3. } else { 2. });
+///
+/// let all = find_all_shortest_paths_johnson(&[0], neighbor_function, weight_function).unwrap();
+/// assert_eq!(all.len(), 4);
+/// assert_eq!(all[&0].distances[&3], OrderedFloat(1.));
+/// assert_eq!(all[&2].distances[&3], OrderedFloat(-1.));
+/// assert_eq!(all[&2].path(3), Some(vec![2, 1, 3]));
+/// assert!(!all[&3].distances.contains_key(&0));
+/// ```
+pub fn find_all_shortest_paths_johnson<T>(
+    nodes: &[T],
+    neighbor_function: impl Fn(T) -> Vec<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+) -> Result<HashMap<T, ShortestPathForest<T>>, CycleError<T>>
 where
-    T: Eq + Hash + Copy,
+    T: Eq + Hash + Clone + Copy,
 {
-    pathfinding::directed::bfs::bfs_reach(node, |&x| neighbor_function(x)).collect()
+    all_shortest_paths_johnson(nodes, neighbor_function, weight_function)
 }
 
-// Should do this for each connected component (degree of freedom!)
-pub fn two_color<T>(nodes: &[T], neighbor_function: impl Fn(T) -> Vec<T>) -> Option<(HashSet<T>, HashSet<T>)>
+fn all_shortest_paths_johnson<T>(
+    nodes: &[T],
+    neighbor_function: impl Adjacency<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+) -> Result<HashMap<T, ShortestPathForest<T>>, CycleError<T>>
 where
-    T: Eq + Hash + Clone + Copy + Debug,
+    T: Eq + Hash + Clone + Copy,
 {
-    let mut pool = nodes.to_vec();
-    let mut color1 = HashSet::new();
-    let mut color2 = HashSet::new();
+    // Distances from a virtual element with an edge of weight zero to every element.
+    let potential = negative_shortest_path_forest(nodes, &neighbor_function, &weight_function, NegativeWeightAlgorithm::Spfa)?.distances;
+    // Rounding may leave reduced weights slightly negative.
+    let reduced_weight_function = |a: T, b: T| (weight_function(a, b) + potential[&a] - potential[&b]).max(OrderedFloat(0.));
+    Ok(potential
+        .keys()
+        .map(|&source| {
+            let mut forest = shortest_path_forest(
+                &[source],
+                &NeighborsOnly(&neighbor_function),
+                &reduced_weight_function,
+                OrderedFloat(f64::INFINITY),
+            );
+            for (node, distance) in &mut forest.distances {
+                *distance += potential[node] - potential[&source];
+            }
+            (source, forest)
+        })
+        .collect())
+}
 
-    while let Some(s) = pool.pop() {
-        let mut queue = vec![s];
+/// The shortest paths from a set of sources to a set of targets, as a distance matrix and a table of predecessors. See `find_all_pairs_floyd_warshall` and `find_all_pairs_dijkstra`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AllPairsShortestPaths<T: Eq + Hash> {
+    /// The sources, in the order of the rows of the matrices.
+    pub sources: Vec<T>,
+    /// The row of every source.
+    pub source_index: HashMap<T, usize>,
+    /// The targets, in the order of the columns of `distances`.
+    pub targets: Vec<T>,
+    /// The column of every target.
+    pub target_index: HashMap<T, usize>,
+    /// The distance from the source of every row to the target of every column, or infinity if it cannot be reached.
+    pub distances: DMatrix<f64>,
+    /// The elements reachable from the sources, in the order of the columns of `predecessors`.
+    pub nodes: Vec<T>,
+    /// The index of every element in `nodes`.
+    pub index: HashMap<T, usize>,
+    /// The index (in `nodes`) of the element before the element of every column on a shortest path from the source of every row,
+    /// or `NO_PREDECESSOR` for the source itself and elements that cannot be reached.
+    /// This takes 4 bytes per source and reachable element, and is only stored if paths were requested.
+    pub predecessors: Option<DMatrix<u32>>,
+}
 
-        while let Some(node) = queue.pop() {
-            pool.retain(|x| x != &node);
-            if color1.contains(&node) || color2.contains(&node) {
-                continue;
+/// Marks a missing predecessor in `AllPairsShortestPaths::predecessors`.
+pub const NO_PREDECESSOR: u32 = u32::MAX;
+
+// The index of an element in a predecessor table.
+fn predecessor_index(index: usize) -> u32 {
+    u32::try_from(index)
+        .ok()
+        .filter(|&index| index != NO_PREDECESSOR)
+        .expect("predecessor tables hold fewer than 2^32 - 1 elements")
+}
+
+impl<T: Eq + Hash + Clone + Copy> AllPairsShortestPaths<T> {
+    /// Returns the distance from source `a` to target `b`.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The distance (infinity if `b` cannot be reached from `a`), or `None` if `a` is not a source or `b` is not a target.
+    #[must_use]
+    pub fn distance(&self, a: T, b: T) -> Option<f64> {
+        Some(self.distances[(*self.source_index.get(&a)?, *self.target_index.get(&b)?)])
+    }
+
+    /// Reconstructs the shortest path from source `a` to target `b`, by following the predecessors back from `b`.
+    ///
+    /// # Returns
+    /// * `Option<Vec<T>>` - The path, starting at `a` and ending at `b`, or `None` if `b` cannot be reached from `a` (or `a` is not a source, `b` is not a target, or paths were not requested).
+    #[must_use]
+    pub fn path(&self, a: T, b: T) -> Option<Vec<T>> {
+        let predecessors = self.predecessors.as_ref()?;
+        let row = *self.source_index.get(&a)?;
+        self.target_index.get(&b)?;
+        let (start, mut node) = (self.index[&a], *self.index.get(&b)?);
+        let mut path = vec![b];
+        while node != start {
+            let previous = predecessors[(row, node)];
+            if previous == NO_PREDECESSOR {
+                return None;
             }
+            node = previous as usize;
+            path.push(self.nodes[node]);
+        }
+        path.reverse();
+        Some(path)
+    }
+}
 
-            let neighbors = neighbor_function(node);
+// Indexes the distinct elements, in the order of their first occurrence.
+fn index_elements<T: Eq + Hash + Copy>(elements: impl IntoIterator<Item = T>) -> (Vec<T>, HashMap<T, usize>) {
+    let mut unique = vec![];
+    let mut index = HashMap::new();
+    for element in elements {
+        index.entry(element).or_insert_with(|| {
+            unique.push(element);
+            unique.len() - 1
+        });
+    }
+    (unique, index)
+}
 
-            if neighbors.iter().any(|x| color1.contains(x)) {
-                if neighbors.iter().any(|x| color2.contains(x)) {
-                    return None;
-                }
-                color2.insert(node);
-            } else if neighbors.iter().any(|x| color2.contains(x)) {
-                if neighbors.iter().any(|x| color1.contains(x)) {
-                    return None;
-                }
+// Indexes the elements reachable from `nodes` and the weights of their edges, keeping the lightest of parallel edges (self-loops included).
+#[allow(clippy::type_complexity)]
+fn weighted_adjacency<T>(
+    nodes: &[T],
+    neighbor_function: &impl Adjacency<T>,
+    weight_function: &impl Fn(T, T) -> OrderedFloat<f64>,
+) -> (Vec<T>, HashMap<T, usize>, Vec<Vec<(usize, f64)>>)
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let (reached, index, _) = flow_edges(nodes.iter().rev().copied(), neighbor_function);
+    let adjacency = reached
+        .iter()
+        .map(|&node| {
+            let mut lightest: HashMap<usize, f64> = HashMap::new();
+            for (neighbor, OrderedFloat(weight)) in weighted_neighbors(neighbor_function, weight_function, node) {
+                lightest
+                    .entry(index[&neighbor])
+                    .and_modify(|old| *old = old.min(weight))
+                    .or_insert(weight);
+            }
+            lightest.into_iter().collect()
+        })
+        .collect();
+    (reached, index, adjacency)
+}
 
-                color1.insert(node);
-            } else {
-                // Degree of freedom.
-                color2.insert(node);
+/// Finds the shortest paths between all pairs of elements using the Floyd-Warshall algorithm. Weights may be negative.
+///
+/// This takes cubic time in the number of elements, regardless of the number of edges, so it suits small dense graphs. See `find_all_pairs_dijkstra` for sparse graphs.
+///
+/// # Arguments
+/// * `nodes` - The elements of the graph. Elements that are only reachable from them are included as well.
+/// * `neighbor_function` - A function that returns the (outgoing) neighbors of a given element.
+/// * `weight_function` - A function that returns the weight of the edge from the first element to the second. Weights may be negative.
+///
+/// # Returns
+/// * `Result<AllPairsShortestPaths<T>, CycleError<T>>` - The distance matrix and predecessor table, with all elements as sources and targets, or a negative cycle.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::find_all_pairs_floyd_warshall;
+/// use ordered_float::OrderedFloat;
+///
+/// let neighbor_function = |node: u32| -> Vec<u32> {
+///     match node {
+///         0 => vec![1, 2],
+///         1 => vec![3],
+///         2 => vec![1],
+///         _ => vec![],
+///     }
+/// };
+/// let weight_function = |a: u32, b: u32| OrderedFloat(if (a, b) == (2, 1) { -3. } else { 2. });
+///
+/// let all = find_all_pairs_floyd_warshall(&[0], neighbor_function, weight_function).unwrap();
+/// assert_eq!(all.distances.shape(), (4, 4));
+/// assert_eq!(all.distance(0, 3), Some(1.));
+/// assert_eq!(all.distance(3, 0), Some(f64::INFINITY));
+/// assert_eq!(all.path(0, 3), Some(vec![0, 2, 1, 3]));
+/// assert_eq!(all.path(3, 0), None);
+///
+/// // A negative cycle 1 -> 2 -> 1.
+/// let neighbor_function = |node: u32| -> Vec<u32> { if node == 1 { vec![2, 3] } else { neighbor_function(node) } };
+/// assert!(find_all_pairs_floyd_warshall(&[0], neighbor_function, weight_function).is_err());
+/// ```
+pub fn find_all_pairs_floyd_warshall<T>(
+    nodes: &[T],
+    neighbor_function: impl Fn(T) -> Vec<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+) -> Result<AllPairsShortestPaths<T>, CycleError<T>>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    all_pairs_floyd_warshall(nodes, neighbor_function, weight_function)
+}
+
+fn all_pairs_floyd_warshall<T>(
+    nodes: &[T],
+    neighbor_function: impl Adjacency<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+) -> Result<AllPairsShortestPaths<T>, CycleError<T>>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let (reached, index, adjacency) = weighted_adjacency(nodes, &neighbor_function, &weight_function);
+    let n = reached.len();
+    let mut distances = DMatrix::from_element(n, n, f64::INFINITY);
+    let mut predecessors = DMatrix::from_element(n, n, NO_PREDECESSOR);
+    for (u, neighbors) in adjacency.iter().enumerate() {
+        distances[(u, u)] = 0.;
+        for &(v, weight) in neighbors {
+            if weight < distances[(u, v)] {
+                distances[(u, v)] = weight;
+                predecessors[(u, v)] = predecessor_index(u);
             }
+        }
+    }
 
-            queue.extend(neighbors);
+    // The matrices are stored by column, so the innermost loop runs down the columns.
+    for k in 0..n {
+        for j in 0..n {
+            let (to_j, before_j) = (distances[(k, j)], predecessors[(k, j)]);
+            if to_j == f64::INFINITY {
+                continue;
+            }
+            for i in 0..n {
+                let through_k = distances[(i, k)] + to_j;
+                if through_k < distances[(i, j)] {
+                    distances[(i, j)] = through_k;
+                    predecessors[(i, j)] = before_j;
+                }
+            }
         }
     }
-    Some((color1, color2))
+
+    // Find the cycle itself from an element on a negative cycle. If the search does not confirm it (as rounding may differ), the predecessors of that row contain a cycle.
+    if let Some(node) = (0..n).find(|&node| distances[(node, node)] < 0.) {
+        negative_shortest_path_forest(&[reached[node]], &neighbor_function, &weight_function, NegativeWeightAlgorithm::Spfa)?;
+        let row = predecessors
+            .row(node)
+            .iter()
+            .map(|&predecessor| if predecessor == NO_PREDECESSOR { NONE } else { predecessor as usize })
+            .collect::<Vec<_>>();
+        let cycle = predecessor_cycle(&row).unwrap_or_else(|| vec![node]);
+        return Err(CycleError {
+            cycle: cycle.into_iter().map(|i| reached[i]).collect(),
+        });
+    }
+    Ok(AllPairsShortestPaths {
+        sources: reached.clone(),
+        source_index: index.clone(),
+        targets: reached.clone(),
+        target_index: index.clone(),
+        distances,
+        nodes: reached,
+        index,
+        predecessors: Some(predecessors),
+    })
+}
+
+/// Finds the shortest paths from every source to every target by running Dijkstra's algorithm from every source, in parallel over all available threads.
+///
+/// Every search runs over the whole graph (and stops once all targets are reached), but only the rows of the sources and the columns of the targets are stored.
+/// This suits sparse graphs, such as mesh graphs. See `find_all_pairs_floyd_warshall` for dense graphs, and `find_all_shortest_paths_johnson` for negative weights.
+///
+/// # Arguments
+/// * `sources` - The elements to start from. The graph consists of everything reachable from them.
+/// * `targets` - The elements to find the distances to, or `None` for all elements reachable from the sources.
+/// * `neighbor_function` - A function that returns the (outgoing) neighbors of a given element.
+/// * `weight_function` - A function that returns the (non-negative) weight of the edge from the first element to the second.
+/// * `paths` - Whether to store the predecessor table, which `AllPairsShortestPaths::path` needs.
+///   It takes 4 bytes per source and reachable element (on top of the 8 bytes per source and target of the distances), so leave it off when only distances are needed.
+///
+/// # Returns
+/// * `AllPairsShortestPaths<T>` - The distance matrix (a row per distinct source and a column per distinct target) and, if requested, the predecessor table.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::find_all_pairs_dijkstra;
+/// use ordered_float::OrderedFloat;
+///
+/// // A 10x10 grid, where distances are Manhattan distances.
+/// let neighbor_function = |(x, y): (i32, i32)| -> Vec<(i32, i32)> {
+///     [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)].into_iter().filter(|&(x, y)| (0..10).contains(&x) && (0..10).contains(&y)).collect()
+/// };
+/// let all = find_all_pairs_dijkstra(&[(0, 0)], None, neighbor_function, |_, _| OrderedFloat(1.), false);
+/// assert_eq!(all.distances.shape(), (1, 100));
+/// assert_eq!(all.distance((0, 0), (7, 1)), Some(8.));
+/// assert!(all.predecessors.is_none());
+/// assert_eq!(all.path((0, 0), (7, 1)), None);
+///
+/// let some = find_all_pairs_dijkstra(&[(2, 3), (0, 0)], Some(&[(7, 1), (20, 20)]), neighbor_function, |_, _| OrderedFloat(1.), true);
+/// assert_eq!(some.distances.shape(), (2, 2));
+/// assert_eq!(some.distance((2, 3), (7, 1)), Some(7.));
+/// assert_eq!(some.distance((2, 3), (20, 20)), Some(f64::INFINITY));
+/// let path = some.path((2, 3), (7, 1)).unwrap();
+/// assert_eq!(path.len(), 8);
+/// assert_eq!(path.first(), Some(&(2, 3)));
+/// assert_eq!(path.last(), Some(&(7, 1)));
+/// ```
+pub fn find_all_pairs_dijkstra<T>(
+    sources: &[T],
+    targets: Option<&[T]>,
+    neighbor_function: impl Fn(T) -> Vec<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+    paths: bool,
+) -> AllPairsShortestPaths<T>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    all_pairs_dijkstra(sources, targets, neighbor_function, weight_function, paths)
 }
 
-pub fn topological_sort<T>(nodes: &[T], neighbor_function: impl Fn(T) -> Vec<T>) -> Option<Vec<T>>
+fn all_pairs_dijkstra<T>(
+    sources: &[T],
+    targets: Option<&[T]>,
+    neighbor_function: impl Adjacency<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+    paths: bool,
+) -> AllPairsShortestPaths<T>
 where
     T: Eq + Hash + Clone + Copy,
 {
-    pathfinding::directed::topological_sort::topological_sort(nodes, |&x| neighbor_function(x)).ok()
+    let (reached, index, adjacency) = weighted_adjacency(sources, &neighbor_function, &weight_function);
+    let n = reached.len();
+    let (sources, source_index) = index_elements(sources.iter().copied());
+    let (targets, target_index) = index_elements(targets.map_or_else(|| reached.clone(), <[T]>::to_vec));
+    let columns = targets.iter().map(|target| index.get(target).copied()).collect::<Vec<_>>();
+    let mut is_target = vec![false; n];
+    columns.iter().flatten().for_each(|&column| is_target[column] = true);
+    let nr_targets = is_target.iter().filter(|&&target| target).count();
+    let starts = sources.iter().map(|source| index[source]).collect::<Vec<_>>();
+
+    // The distances and predecessors from one source, until all targets are settled.
+    let dijkstra = |source: usize| {
+        let mut distances = vec![f64::INFINITY; n];
+        let mut predecessors = vec![NO_PREDECESSOR; if paths { n } else { 0 }];
+        let mut settled = vec![false; n];
+        let mut remaining = nr_targets;
+        distances[source] = 0.;
+        let mut queue = BinaryHeap::from([Reverse((OrderedFloat(0.), source))]);
+        while remaining > 0
+            && let Some(Reverse((OrderedFloat(distance), u))) = queue.pop()
+        {
+            if settled[u] {
+                continue;
+            }
+            settled[u] = true;
+            remaining -= usize::from(is_target[u]);
+            for &(v, weight) in &adjacency[u] {
+                if distance + weight < distances[v] {
+                    distances[v] = distance + weight;
+                    if paths {
+                        predecessors[v] = predecessor_index(u);
+                    }
+                    queue.push(Reverse((OrderedFloat(distances[v]), v)));
+                }
+            }
+        }
+        (distances, predecessors)
+    };
+
+    // Every thread writes the rows of its sources as soon as they are found, so that only one search per thread is held at a time.
+    let matrices = std::sync::Mutex::new((
+        DMatrix::from_element(sources.len(), targets.len(), f64::INFINITY),
+        paths.then(|| DMatrix::from_element(sources.len(), n, NO_PREDECESSOR)),
+    ));
+    let threads = std::thread::available_parallelism().map_or(1, std::num::NonZero::get);
+    std::thread::scope(|scope| {
+        let (dijkstra, starts, columns, matrices) = (&dijkstra, &starts, &columns, &matrices);
+        for thread in 0..threads {
+            scope.spawn(move || {
+                for row in (thread..starts.len()).step_by(threads) {
+                    let (row_distances, row_predecessors) = dijkstra(starts[row]);
+                    let mut matrices = matrices.lock().unwrap();
+                    for (entry, column) in matrices.0.row_mut(row).iter_mut().zip(columns) {
+                        *entry = column.map_or(f64::INFINITY, |column| row_distances[column]);
+                    }
+                    if let Some(predecessors) = &mut matrices.1 {
+                        predecessors
+                            .row_mut(row)
+                            .iter_mut()
+                            .zip(row_predecessors)
+                            .for_each(|(entry, predecessor)| *entry = predecessor);
+                    }
+                }
+            });
+        }
+    });
+    let (distances, predecessors) = matrices.into_inner().unwrap();
+    AllPairsShortestPaths {
+        sources,
+        source_index,
+        targets,
+        target_index,
+        distances,
+        nodes: reached,
+        index,
+        predecessors,
+    }
+}
+
+/// Finds the shortest (directed) cycle through element `a`, using a single run of Dijkstra's algorithm from `a`.
+///
+/// # Arguments
+/// * `a` - The starting element, which is also the element through which the cycle must pass.
+/// * `neighbor_function` - A function that returns the neighbors of a given element.
+/// * `weight_function` - A function that returns the weight of the edge between two elements.
+///
+/// # Returns
+/// * `Option<(Vec<T>, OrderedFloat<f64>)>` - An optional tuple containing the shortest cycle as a vector of elements
+///    and the total weight of the cycle as an `OrderedFloat<f64>`. Returns `None` if no cycle is found.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::find_shortest_cycle;
+/// use ordered_float::OrderedFloat;
+///
+/// let neighbor_function = |node: u32| -> Vec<u32> {
+///     match node {
+///         1 => vec![2, 3],
+///         2 => vec![3],
+///         3 => vec![5],
+///         4 => vec![2, 6],
+///         5 => vec![4],
+///         6 => vec![],
+///         _ => vec![],
+///     }
+/// };
+///
+/// let weight_function = |a: u32, b: u32| -> OrderedFloat<f64> {
+///     match (a, b) {
+///         (1, 2) => 4.0.into(),
+///         (1, 3) => 2.0.into(),
+///         (2, 3) => 5.0.into(),
+///         (3, 5) => 3.0.into(),
+///         (4, 2) => 10.0.into(),
+///         (4, 6) => 11.0.into(),
+///         (5, 4) => 4.0.into(),
+///         _ => OrderedFloat(f64::INFINITY),
+///     }
+/// };
+///
+/// let result = find_shortest_cycle(1, neighbor_function, weight_function);
+/// assert!(result.is_none());
+///
+/// let result = find_shortest_cycle(3, neighbor_function, weight_function);
+/// assert!(result.is_some());
+/// let (path, cost) = result.unwrap();
+/// assert_eq!(path, vec![3, 5, 4, 2]);
+/// assert_eq!(cost, OrderedFloat(3.0 + 4.0 + 10.0 + 5.0));
+/// ```
+pub fn find_shortest_cycle<T: Eq + Hash + Clone + Copy>(
+    a: T,
+    neighbor_function: impl Fn(T) -> Vec<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+) -> Option<(Vec<T>, OrderedFloat<f64>)> {
+    shortest_cycle(a, neighbor_function, weight_function)
+}
+
+fn shortest_cycle<T: Eq + Hash + Clone + Copy>(
+    a: T,
+    neighbor_function: impl Adjacency<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+) -> Option<(Vec<T>, OrderedFloat<f64>)> {
+    // Every edge back into `a` closes a cycle; the search stops once no shorter cycle can be found.
+    let mut distance = HashMap::from([(a, OrderedFloat(0.))]);
+    let mut predecessor = HashMap::new();
+    // The heap holds indices into `entries`, as `T` is not necessarily ordered.
+    let mut entries = vec![a];
+    let mut queue = BinaryHeap::from([Reverse((OrderedFloat(0.), 0))]);
+    let mut best: Option<(OrderedFloat<f64>, T)> = None;
+    while let Some(Reverse((cost, entry))) = queue.pop() {
+        let node = entries[entry];
+        if best.is_some_and(|(best, _)| cost >= best) {
+            break;
+        }
+        if cost > distance[&node] {
+            continue;
+        }
+        for (neighbor, weight) in weighted_neighbors(&neighbor_function, &weight_function, node) {
+            let next = cost + weight;
+            if neighbor == a {
+                if best.is_none_or(|(best, _)| next < best) {
+                    best = Some((next, node));
+                }
+            } else if distance.get(&neighbor).is_none_or(|&old| next < old) {
+                distance.insert(neighbor, next);
+                predecessor.insert(neighbor, node);
+                queue.push(Reverse((next, entries.len())));
+                entries.push(neighbor);
+            }
+        }
+    }
+
+    best.map(|(cost, last)| {
+        let mut cycle = vec![last];
+        while let Some(&previous) = predecessor.get(cycle.last().unwrap()) {
+            cycle.push(previous);
+        }
+        cycle.reverse();
+        (cycle, cost)
+    })
+}
+
+// A Dijkstra tree on an undirected graph given by indexed adjacency lists of (neighbor, edge).
+struct ShortestPathTree {
+    root: usize,
+    distance: Vec<f64>,
+    parent_edge: Vec<usize>,
+    // The first node after the root on the path to every node.
+    branch: Vec<usize>,
+}
+
+impl ShortestPathTree {
+    fn new(root: usize, adjacency: &[Vec<(usize, usize)>], weights: &[f64]) -> Self {
+        let n = adjacency.len();
+        let mut tree = Self {
+            root,
+            distance: vec![f64::INFINITY; n],
+            parent_edge: vec![NONE; n],
+            branch: vec![NONE; n],
+        };
+        tree.distance[root] = 0.;
+        tree.branch[root] = root;
+        let mut queue = BinaryHeap::from([Reverse((OrderedFloat(0.), root))]);
+        while let Some(Reverse((OrderedFloat(d), node))) = queue.pop() {
+            if d > tree.distance[node] {
+                continue;
+            }
+            for &(neighbor, edge) in &adjacency[node] {
+                let next = d + weights[edge];
+                if next < tree.distance[neighbor] {
+                    tree.distance[neighbor] = next;
+                    tree.parent_edge[neighbor] = edge;
+                    tree.branch[neighbor] = if node == root { neighbor } else { tree.branch[node] };
+                    queue.push(Reverse((OrderedFloat(next), neighbor)));
+                }
+            }
+        }
+        tree
+    }
+
+    // The candidate cycles of Horton at this root: every edge (u, v) outside the tree whose tree paths to u and v only meet at the root, with the weight of the cycle they form.
+    // Only the edges are returned, so that callers build just the cycles they need with `cycle`.
+    fn candidates<'a>(&'a self, edges: &'a [(usize, usize)], weights: &'a [f64]) -> impl Iterator<Item = (usize, f64)> + 'a {
+        edges.iter().enumerate().filter_map(|(edge, &(u, v))| {
+            let skip =
+                self.distance[u].is_infinite() || self.parent_edge[u] == edge || self.parent_edge[v] == edge || self.branch[u] == self.branch[v];
+            (!skip).then(|| (edge, self.distance[u] + weights[edge] + self.distance[v]))
+        })
+    }
+
+    // The nodes (starting at the root) and the edges of the candidate cycle closed by `edge`.
+    fn cycle(&self, edge: usize, edges: &[(usize, usize)]) -> (Vec<usize>, Vec<usize>) {
+        let walk = |mut node: usize| {
+            let (mut nodes, mut path) = (vec![], vec![]);
+            while node != self.root {
+                nodes.push(node);
+                let parent_edge = self.parent_edge[node];
+                path.push(parent_edge);
+                node = if edges[parent_edge].0 == node {
+                    edges[parent_edge].1
+                } else {
+                    edges[parent_edge].0
+                };
+            }
+            (nodes, path)
+        };
+        let (u, v) = edges[edge];
+        let (mut nodes, mut path) = walk(u);
+        let (to_v, path_v) = walk(v);
+        nodes.push(self.root);
+        nodes.reverse();
+        nodes.extend(to_v);
+        path.extend(path_v);
+        path.push(edge);
+        (nodes, path)
+    }
+}
+
+// Indexes the undirected graph reachable from `nodes`: its nodes, edges (once per pair of nodes), edge weights, and adjacency lists of (neighbor, edge).
+#[allow(clippy::type_complexity)]
+fn undirected_edges<T>(
+    nodes: &[T],
+    neighbor_function: &impl Adjacency<T>,
+    weight_function: &impl Fn(T, T) -> OrderedFloat<f64>,
+) -> (Vec<T>, Vec<(usize, usize)>, Vec<f64>, Vec<Vec<(usize, usize)>>)
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let (nodes, ids, directed_edges) = flow_edges(nodes.iter().copied(), neighbor_function);
+    let mut seen = HashSet::new();
+    let mut edges = vec![];
+    let mut weights = vec![];
+    let mut adjacency = vec![vec![]; nodes.len()];
+    for (a, b) in directed_edges {
+        let (u, v) = (ids[&a], ids[&b]);
+        if seen.insert((u.min(v), u.max(v))) {
+            adjacency[u].push((v, edges.len()));
+            adjacency[v].push((u, edges.len()));
+            edges.push((u, v));
+            weights.push(weight_function(a, b).0);
+        }
+    }
+    (nodes, edges, weights, adjacency)
+}
+
+/// Finds a shortest cycle anywhere in an undirected graph (its weighted girth).
+///
+/// # Arguments
+/// * `nodes` - The nodes of the graph. Nodes that are only reachable from them are included as well.
+/// * `neighbor_function` - A function that returns the neighbors of a given node. It must be symmetric.
+/// * `weight_function` - A function that returns the (positive) weight of the edge between two nodes.
+///
+/// # Returns
+/// * `Option<(Vec<T>, OrderedFloat<f64>)>` - The nodes of a shortest cycle (of at least three nodes) and its total weight, or `None` if the graph is a forest.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::find_girth;
+/// use ordered_float::OrderedFloat;
+///
+/// // A 3x3 grid: the shortest cycles are the four unit squares.
+/// let neighbor_function = |(x, y): (i32, i32)| -> Vec<(i32, i32)> {
+///     [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)].into_iter().filter(|&(x, y)| (0..3).contains(&x) && (0..3).contains(&y)).collect()
+/// };
+/// let (cycle, weight) = find_girth(&[(0, 0)], neighbor_function, |_, _| OrderedFloat(1.)).unwrap();
+/// assert_eq!(cycle.len(), 4);
+/// assert_eq!(weight, OrderedFloat(4.));
+///
+/// // A path has no cycles.
+/// let neighbor_function = |node: i32| -> Vec<i32> { [node - 1, node + 1].into_iter().filter(|n| (0..5).contains(n)).collect() };
+/// assert!(find_girth(&[0], neighbor_function, |_, _| OrderedFloat(1.)).is_none());
+/// ```
+pub fn find_girth<T>(
+    nodes: &[T],
+    neighbor_function: impl Fn(T) -> Vec<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+) -> Option<(Vec<T>, OrderedFloat<f64>)>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    girth(nodes, neighbor_function, weight_function)
+}
+
+fn girth<T>(
+    nodes: &[T],
+    neighbor_function: impl Adjacency<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+) -> Option<(Vec<T>, OrderedFloat<f64>)>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let (nodes, edges, weights, adjacency) = undirected_edges(nodes, &neighbor_function, &weight_function);
+    // Only the lightest candidate is kept (as its weight, root and edge), and its cycle is built at the end.
+    let mut best: Option<(f64, usize, usize)> = None;
+    for root in 0..nodes.len() {
+        let tree = ShortestPathTree::new(root, &adjacency, &weights);
+        for (edge, weight) in tree.candidates(&edges, &weights) {
+            if best.is_none_or(|(best, ..)| weight < best) {
+                best = Some((weight, root, edge));
+            }
+        }
+    }
+    let (weight, root, edge) = best?;
+    let (cycle, _) = ShortestPathTree::new(root, &adjacency, &weights).cycle(edge, &edges);
+    Some((cycle.into_iter().map(|node| nodes[node]).collect(), OrderedFloat(weight)))
+}
+
+/// Finds a minimum weight cycle basis of an undirected graph, using Horton's algorithm.
+///
+/// Every cycle in the graph is the symmetric difference (over the edges) of some cycles of the basis, and the basis has the smallest total weight among all cycle bases.
+///
+/// This suits small and medium graphs only: it keeps a shortest path tree per node and up to `n * m` candidate cycles, which takes `O(n^2 + n m)` memory, and it tests candidates for independence in `O(m^2)` time each.
+///
+/// # Arguments
+/// * `nodes` - The nodes of the graph. Nodes that are only reachable from them are included as well.
+/// * `neighbor_function` - A function that returns the neighbors of a given node. It must be symmetric.
+/// * `weight_function` - A function that returns the (positive) weight of the edge between two nodes.
+///
+/// # Returns
+/// * `Vec<(Vec<T>, OrderedFloat<f64>)>` - The cycles of the basis by increasing weight, each as its nodes and its total weight. There are `m - n + c` of them, for `m` edges, `n` nodes and `c` connected components.
+///
+/// # Source
+/// Horton, J. D. (1987). A polynomial-time algorithm to find the shortest cycle basis of a graph. SIAM Journal on Computing.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::find_minimum_cycle_basis;
+/// use ordered_float::OrderedFloat;
+///
+/// // A 4x3 grid of nodes: the basis consists of the six unit squares.
+/// let neighbor_function = |(x, y): (i32, i32)| -> Vec<(i32, i32)> {
+///     [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)].into_iter().filter(|&(x, y)| (0..4).contains(&x) && (0..3).contains(&y)).collect()
+/// };
+/// let basis = find_minimum_cycle_basis(&[(0, 0)], neighbor_function, |_, _| OrderedFloat(1.));
+/// assert_eq!(basis.len(), 17 - 12 + 1);
+/// assert!(basis.iter().all(|(cycle, weight)| cycle.len() == 4 && *weight == OrderedFloat(4.)));
+///
+/// // A triangle 0-1-2 and a square 2-3-4-5, sharing node 2, with a chord 3-5 of weight 3.
+/// // The basis holds the triangle, the square, and one of the two triangles formed by the chord.
+/// let neighbor_function = |node: u32| -> Vec<u32> {
+///     match node {
+///         0 => vec![1, 2],
+///         1 => vec![0, 2],
+///         2 => vec![0, 1, 3, 5],
+///         3 => vec![2, 4, 5],
+///         4 => vec![3, 5],
+///         _ => vec![2, 3, 4],
+///     }
+/// };
+/// let weight_function = |a: u32, b: u32| OrderedFloat(if a.min(b) == 3 && a.max(b) == 5 { 3. } else { 1. });
+/// let basis = find_minimum_cycle_basis(&[0], neighbor_function, weight_function);
+/// assert_eq!(basis.iter().map(|(_, weight)| weight.0).collect::<Vec<_>>(), vec![3., 4., 5.]);
+/// ```
+pub fn find_minimum_cycle_basis<T>(
+    nodes: &[T],
+    neighbor_function: impl Fn(T) -> Vec<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+) -> Vec<(Vec<T>, OrderedFloat<f64>)>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    minimum_cycle_basis(nodes, neighbor_function, weight_function)
+}
+
+fn minimum_cycle_basis<T>(
+    nodes: &[T],
+    neighbor_function: impl Adjacency<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+) -> Vec<(Vec<T>, OrderedFloat<f64>)>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let (nodes, edges, weights, adjacency) = undirected_edges(nodes, &neighbor_function, &weight_function);
+    let mut components = UnionFind::new(nodes.len());
+    for &(u, v) in &edges {
+        components.union(u, v);
+    }
+    let dimension = edges.len() + components.nr_sets() - nodes.len();
+
+    // The candidates are kept as their weight, root and edge; their cycles are only built when they are tested.
+    let trees = (0..nodes.len())
+        .map(|root| ShortestPathTree::new(root, &adjacency, &weights))
+        .collect::<Vec<_>>();
+    let mut candidates = trees
+        .iter()
+        .flat_map(|tree| tree.candidates(&edges, &weights).map(|(edge, weight)| (weight, tree.root, edge)))
+        .collect::<Vec<_>>();
+    candidates.sort_by(|(a, ..), (b, ..)| a.total_cmp(b));
+
+    // Greedily keep the candidates that are linearly independent (over GF(2)) of the ones kept so far, by Gaussian elimination on edge bitsets.
+    let words = edges.len().div_ceil(64);
+    let mut rows: Vec<Option<Vec<u64>>> = vec![None; edges.len()];
+    let mut basis = vec![];
+    for (weight, root, edge) in candidates {
+        if basis.len() == dimension {
+            break;
+        }
+        let (cycle, path) = trees[root].cycle(edge, &edges);
+        let mut bits = vec![0u64; words];
+        for edge in path {
+            bits[edge / 64] ^= 1 << (edge % 64);
+        }
+        while let Some(pivot) = bits
+            .iter()
+            .position(|&word| word != 0)
+            .map(|word| word * 64 + bits[word].trailing_zeros() as usize)
+        {
+            if let Some(row) = &rows[pivot] {
+                bits.iter_mut().zip(row).for_each(|(bit, row)| *bit ^= row);
+            } else {
+                rows[pivot] = Some(bits);
+                basis.push((cycle.into_iter().map(|node| nodes[node]).collect(), OrderedFloat(weight)));
+                break;
+            }
+        }
+    }
+    basis
+}
+
+/// Finds up to `k` shortest loopless paths from element `a` to element `b` using Yen's algorithm.
+///
+/// # Arguments
+/// * `a` - The starting element.
+/// * `b` - The target element.
+/// * `k` - The maximum number of paths.
+/// * `neighbor_function` - A function that returns the neighbors of a given element.
+/// * `weight_function` - A function that returns the (non-negative) weight of the edge between two elements.
+///
+/// # Returns
+/// * `Vec<(Vec<T>, OrderedFloat<f64>)>` - The paths and their total weights, ordered by increasing weight. See `KShortestPaths` to compute them lazily.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::find_k_shortest_paths;
+/// use ordered_float::OrderedFloat;
+///
+/// let neighbor_function = |node: char| -> Vec<char> {
+///     match node {
+///         'C' => vec!['D', 'E'],
+///         'D' => vec!['F'],
+///         'E' => vec!['D', 'F', 'G'],
+///         'F' => vec!['G', 'H'],
+///         'G' => vec!['H'],
+///         _ => vec![],
+///     }
+/// };
+///
+/// let weight_function = |a: char, b: char| -> OrderedFloat<f64> {
+///     match (a, b) {
+///         ('C', 'D') | ('E', 'G') => 3.0.into(),
+///         ('C', 'E') | ('E', 'F') | ('F', 'G') | ('G', 'H') => 2.0.into(),
+///         ('E', 'D') | ('F', 'H') => 1.0.into(),
+///         ('D', 'F') => 4.0.into(),
+///         _ => OrderedFloat(f64::INFINITY),
+///     }
+/// };
+///
+/// let paths = find_k_shortest_paths('C', 'H', 3, neighbor_function, weight_function);
+/// assert_eq!(paths, vec![
+///     (vec!['C', 'E', 'F', 'H'], OrderedFloat(5.)),
+///     (vec!['C', 'E', 'G', 'H'], OrderedFloat(7.)),
+///     (vec!['C', 'D', 'F', 'H'], OrderedFloat(8.)),
+/// ]);
+///
+/// // There are seven loopless paths in total.
+/// let paths = find_k_shortest_paths('C', 'H', 100, neighbor_function, weight_function);
+/// assert_eq!(paths.len(), 7);
+/// assert!(paths.windows(2).all(|w| w[0].1 <= w[1].1));
+/// ```
+pub fn find_k_shortest_paths<T: Eq + Hash + Clone + Copy>(
+    a: T,
+    b: T,
+    k: usize,
+    neighbor_function: impl Fn(T) -> Vec<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+) -> Vec<(Vec<T>, OrderedFloat<f64>)> {
+    KShortestPaths::new(a, b, neighbor_function, weight_function).take(k).collect()
+}
+
+/// An iterator over the loopless paths from element `a` to element `b` in order of increasing total weight, computed lazily with Yen's algorithm.
+///
+/// Every step runs one Dijkstra search per node of the previous path, so stopping as soon as a path is accepted avoids most of the work of `find_k_shortest_paths`.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::KShortestPaths;
+/// use ordered_float::OrderedFloat;
+///
+/// // A 4x4 grid, where paths of equal length are told apart by a validity check.
+/// let neighbor_function = |(x, y): (i32, i32)| -> Vec<(i32, i32)> {
+///     [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)].into_iter().filter(|&(x, y)| (0..4).contains(&x) && (0..4).contains(&y)).collect()
+/// };
+/// let weight_function = |_, _| OrderedFloat(1.);
+///
+/// let is_valid = |path: &Vec<(i32, i32)>| path.contains(&(2, 1));
+/// let mut paths = KShortestPaths::new((0, 0), (3, 3), neighbor_function, weight_function);
+/// let (path, cost) = paths.find(|(path, _)| is_valid(path)).unwrap();
+/// assert_eq!(cost, OrderedFloat(6.));
+/// assert!(is_valid(&path));
+///
+/// // All four paths from 0 to 3, and then nothing more.
+/// let weights = [((0, 1), 1.), ((0, 2), 2.5), ((1, 2), 1.), ((2, 1), 1.), ((1, 3), 3.), ((2, 3), 1.)];
+/// let neighbor_function = |node: usize| -> Vec<usize> { weights.iter().filter(|((a, _), _)| *a == node).map(|((_, b), _)| *b).collect() };
+/// let weight_function = |a: usize, b: usize| OrderedFloat(weights.iter().find(|(edge, _)| *edge == (a, b)).unwrap().1);
+/// let mut paths = KShortestPaths::new(0, 3, neighbor_function, weight_function);
+/// assert_eq!(paths.next(), Some((vec![0, 1, 2, 3], OrderedFloat(3.))));
+/// assert_eq!(paths.next(), Some((vec![0, 2, 3], OrderedFloat(3.5))));
+/// assert_eq!(paths.next(), Some((vec![0, 1, 3], OrderedFloat(4.))));
+/// assert_eq!(paths.next(), Some((vec![0, 2, 1, 3], OrderedFloat(6.5))));
+/// assert_eq!(paths.next(), None);
+/// assert_eq!(paths.next(), None);
+///
+/// // Compare all paths on a small random graph with brute-force enumeration.
+/// let mut seed = 42u64;
+/// let mut random = || {
+///     seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+///     (seed >> 33) as usize
+/// };
+/// let weights = (0..8).map(|_| (0..8).map(|_| (random() % 3 == 0).then(|| (random() % 5) as f64)).collect::<Vec<_>>()).collect::<Vec<_>>();
+/// let neighbor_function = |u: usize| (0..8).filter(|&v| u != v && weights[u][v].is_some()).collect::<Vec<_>>();
+/// let weight_function = |u: usize, v: usize| OrderedFloat(weights[u][v].unwrap());
+///
+/// fn enumerate(path: &mut Vec<usize>, target: usize, neighbors: &dyn Fn(usize) -> Vec<usize>, paths: &mut Vec<Vec<usize>>) {
+///     let last = *path.last().unwrap();
+///     if last == target {
+///         paths.push(path.clone());
+///         return;
+///     }
+///     for next in neighbors(last) {
+///         if !path.contains(&next) {
+///             path.push(next);
+///             enumerate(path, target, neighbors, paths);
+///             path.pop();
+///         }
+///     }
+/// }
+/// for target in 1..8 {
+///     let mut all = vec![];
+///     enumerate(&mut vec![0], target, &neighbor_function, &mut all);
+///     let mut expected = all.iter().map(|path| path.windows(2).map(|w| weight_function(w[0], w[1])).sum::<OrderedFloat<f64>>()).collect::<Vec<_>>();
+///     expected.sort();
+///     let costs = KShortestPaths::new(0, target, neighbor_function, weight_function).map(|(_, cost)| cost).collect::<Vec<_>>();
+///     assert_eq!(costs, expected);
+/// }
+/// ```
+pub struct KShortestPaths<T, A, W> {
+    a: T,
+    b: T,
+    neighbor_function: A,
+    weight_function: W,
+    // The paths returned so far.
+    found: Vec<Vec<T>>,
+    // Candidate paths, with a min-heap on their total weight (ties broken by discovery order).
+    candidates: Vec<Vec<T>>,
+    queue: BinaryHeap<Reverse<(OrderedFloat<f64>, usize)>>,
+    seen: HashSet<Vec<T>>,
+    started: bool,
+    // Set once no candidates are left, so that later calls do not search for the deviations of the last path again.
+    exhausted: bool,
+}
+
+impl<T, A, W> KShortestPaths<T, A, W>
+where
+    T: Eq + Hash + Clone + Copy,
+    A: Fn(T) -> Vec<T>,
+    W: Fn(T, T) -> OrderedFloat<f64>,
+{
+    /// Creates the iterator over the paths from `a` to `b`. No paths are computed until the first call to `next`.
+    pub fn new(a: T, b: T, neighbor_function: A, weight_function: W) -> Self {
+        Self::start(a, b, neighbor_function, weight_function)
+    }
+}
+
+impl<T, A, W> KShortestPaths<T, A, W>
+where
+    T: Eq + Hash + Clone + Copy,
+    W: Fn(T, T) -> OrderedFloat<f64>,
+{
+    fn start(a: T, b: T, neighbor_function: A, weight_function: W) -> Self {
+        Self {
+            a,
+            b,
+            neighbor_function,
+            weight_function,
+            found: vec![],
+            candidates: vec![],
+            queue: BinaryHeap::new(),
+            seen: HashSet::new(),
+            started: false,
+            exhausted: false,
+        }
+    }
+
+    fn cost(&self, path: &[T]) -> OrderedFloat<f64> {
+        path.windows(2).map(|w| (self.weight_function)(w[0], w[1])).sum()
+    }
+
+    fn push_candidate(&mut self, path: Vec<T>) {
+        if self.seen.insert(path.clone()) {
+            self.queue.push(Reverse((self.cost(&path), self.candidates.len())));
+            self.candidates.push(path);
+        }
+    }
+
+    // Adds the deviations from the last found path as candidates: for every spur node on it, the shortest path that leaves the root (the prefix up to the spur node) along an edge not used by any found path with the same root.
+    fn push_deviations(&mut self)
+    where
+        A: Adjacency<T>,
+    {
+        let Some(last) = self.found.last().cloned() else {
+            return;
+        };
+        for i in 0..last.len() - 1 {
+            let (root, spur) = (&last[..=i], last[i]);
+            let removed_edges = self
+                .found
+                .iter()
+                .filter(|path| path.len() > i + 1 && path[..=i] == *root)
+                .map(|path| path[i + 1])
+                .collect::<HashSet<_>>();
+            let removed_nodes = root[..i].iter().copied().collect::<HashSet<_>>();
+            let spur_path = pathfinding::prelude::dijkstra(
+                &spur,
+                |&elem| {
+                    weighted_neighbors(&self.neighbor_function, &self.weight_function, elem)
+                        .filter(|(neighbor, _)| !(removed_nodes.contains(neighbor) || (elem == spur && removed_edges.contains(neighbor))))
+                        .collect::<Vec<_>>()
+                },
+                |&elem| elem == self.b,
+            );
+            if let Some((spur_path, _)) = spur_path {
+                self.push_candidate([&root[..i], &spur_path[..]].concat());
+            }
+        }
+    }
+}
+
+impl<T, A, W> Iterator for KShortestPaths<T, A, W>
+where
+    T: Eq + Hash + Clone + Copy,
+    A: Adjacency<T>,
+    W: Fn(T, T) -> OrderedFloat<f64>,
+{
+    type Item = (Vec<T>, OrderedFloat<f64>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.exhausted {
+            return None;
+        }
+        if self.started {
+            self.push_deviations();
+        } else {
+            self.started = true;
+            if let Some((path, _)) = shortest_path(self.a, self.b, &self.neighbor_function, &self.weight_function) {
+                self.push_candidate(path);
+            }
+        }
+        let Some(Reverse((cost, index))) = self.queue.pop() else {
+            self.exhausted = true;
+            return None;
+        };
+        let path = std::mem::take(&mut self.candidates[index]);
+        self.found.push(path.clone());
+        Some((path, cost))
+    }
+}
+
+impl<T, A, W> std::iter::FusedIterator for KShortestPaths<T, A, W>
+where
+    T: Eq + Hash + Clone + Copy,
+    A: Adjacency<T>,
+    W: Fn(T, T) -> OrderedFloat<f64>,
+{
+}
+
+/// Finds the connected components of an undirected graph, in order of their first node in `nodes`.
+///
+/// The neighbor function must be symmetric: every component is the set of nodes reachable from its first node.
+/// On a directed graph these reachability sets depend on the order of `nodes`; use `find_wccs` or `find_sccs` there.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::find_ccs;
+/// use std::collections::HashSet;
+/// let neighbor_function_undirected = |node: u32| -> Vec<u32> {
+///     match node {
+///         1 => vec![2, 3],
+///         2 => vec![1, 3, 4],
+///         3 => vec![1, 2, 5],
+///         4 => vec![2, 5, 6],
+///         5 => vec![3, 4],
+///         6 => vec![5],
+///         _ => vec![],
+///     }
+/// };
+///
+/// let ccs = find_ccs(&vec![1, 2, 3, 4, 5, 6, 7], neighbor_function_undirected);
+/// assert_eq!(ccs[0], HashSet::from([1, 2, 3, 4, 5, 6]));
+/// assert_eq!(ccs[1], HashSet::from([7]));
+/// ```
+///
+pub fn find_ccs<T>(nodes: &[T], neighbor_function: impl Fn(T) -> Vec<T>) -> Vec<HashSet<T>>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    ccs(nodes, &neighbor_function)
+}
+
+fn ccs<T>(nodes: &[T], neighbor_function: &impl Adjacency<T>) -> Vec<HashSet<T>>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let mut visited = HashSet::new();
+    let mut ccs = vec![];
+    for &node in nodes {
+        if visited.contains(&node) {
+            continue;
+        }
+        let cc = reach(node, neighbor_function);
+        visited.extend(cc.iter().copied());
+        ccs.push(cc);
+    }
+    ccs
+}
+
+/// Finds the connected component of a graph that contains a specific node (or reachability from this specific node).
+///
+/// # Example
+/// ```
+/// use hutspot::graph::find_cc;
+/// use std::collections::HashSet;
+/// let neighbor_function = |node: u32| -> Vec<u32> {
+///     match node {
+///         1 => vec![2, 3],
+///         2 => vec![3],
+///         3 => vec![5],
+///         4 => vec![2, 6],
+///         5 => vec![4],
+///         6 => vec![],
+///         _ => vec![],
+///     }
+/// };
+///
+/// let cc = find_cc(1, neighbor_function);
+/// assert_eq!(cc, HashSet::from([1, 2, 3, 4, 5, 6]));
+///
+/// let cc = find_cc(2, neighbor_function);
+/// assert_eq!(cc, HashSet::from([2, 3, 5, 4, 6]));
+///
+/// let cc = find_cc(3, neighbor_function);
+/// assert_eq!(cc, HashSet::from([2, 3, 5, 4, 6]));
+///
+/// let cc = find_cc(4, neighbor_function);
+/// assert_eq!(cc, HashSet::from([2, 3, 5, 4, 6]));
+///
+/// let cc = find_cc(5, neighbor_function);
+/// assert_eq!(cc, HashSet::from([2, 3, 5, 4, 6]));
+///
+/// let cc = find_cc(6, neighbor_function);
+/// assert_eq!(cc, HashSet::from([6]));
+///
+/// let cc = find_cc(7, neighbor_function);
+/// assert_eq!(cc, HashSet::from([7]));
+///
+/// let neighbor_function_undirected = |node: u32| -> Vec<u32> {
+///     match node {
+///         1 => vec![2, 3],
+///         2 => vec![1, 3, 4],
+///         3 => vec![1, 2, 5],
+///         4 => vec![2, 5, 6],
+///         5 => vec![3, 4],
+///         6 => vec![5],
+///         _ => vec![],
+///     }
+/// };
+///
+/// let cc = find_cc(1, neighbor_function_undirected);
+/// assert_eq!(cc, HashSet::from([1, 2, 3, 4, 5, 6]));
+///
+/// let cc = find_cc(2, neighbor_function_undirected);
+/// assert_eq!(cc, HashSet::from([1, 2, 3, 4, 5, 6]));
+///
+/// let cc = find_cc(3, neighbor_function_undirected);
+/// assert_eq!(cc, HashSet::from([1, 2, 3, 4, 5, 6]));
+///
+/// let cc = find_cc(4, neighbor_function_undirected);
+/// assert_eq!(cc, HashSet::from([1, 2, 3, 4, 5, 6]));
+///
+/// let cc = find_cc(5, neighbor_function_undirected);
+/// assert_eq!(cc, HashSet::from([1, 2, 3, 4, 5, 6]));
+///
+/// let cc = find_cc(6, neighbor_function_undirected);
+/// assert_eq!(cc, HashSet::from([1, 2, 3, 4, 5, 6]));
+///
+/// let cc = find_cc(7, neighbor_function_undirected);
+/// assert_eq!(cc, HashSet::from([7]));
+///
+/// ```
+pub fn find_cc<T>(node: T, neighbor_function: impl Fn(T) -> Vec<T>) -> HashSet<T>
+where
+    T: Eq + Hash + Copy,
+{
+    reach(node, &neighbor_function)
+}
+
+fn reach<T>(node: T, neighbor_function: &impl Adjacency<T>) -> HashSet<T>
+where
+    T: Eq + Hash + Copy,
+{
+    pathfinding::directed::bfs::bfs_reach(node, |&x| neighbor_function.neighbors(x)).collect()
+}
+
+/// Finds the strongly connected components of a directed graph using Tarjan's algorithm.
+///
+/// # Arguments
+/// * `nodes` - The nodes to start from. Nodes that are only reachable from them are included as well.
+/// * `neighbor_function` - A function that returns the successors of a given node.
+///
+/// # Returns
+/// * `Vec<HashSet<T>>` - The strongly connected components, in topological order: every edge between two components goes from an earlier to a later one.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::find_sccs;
+/// use std::collections::HashSet;
+/// let neighbor_function = |node: u32| -> Vec<u32> {
+///     match node {
+///         1 => vec![2, 3],
+///         2 => vec![3],
+///         3 => vec![5],
+///         4 => vec![2, 6],
+///         5 => vec![4],
+///         6 => vec![],
+///         _ => vec![],
+///     }
+/// };
+///
+/// let sccs = find_sccs(&[1, 2, 3, 4, 5, 6, 7], neighbor_function);
+/// assert_eq!(sccs, vec![HashSet::from([7]), HashSet::from([1]), HashSet::from([2, 3, 4, 5]), HashSet::from([6])]);
+///
+/// // The components do not depend on the order of the nodes.
+/// let sccs = find_sccs(&[6, 5, 4, 3, 2, 1], neighbor_function);
+/// assert_eq!(sccs, vec![HashSet::from([1]), HashSet::from([2, 3, 4, 5]), HashSet::from([6])]);
+/// ```
+pub fn find_sccs<T>(nodes: &[T], neighbor_function: impl Fn(T) -> Vec<T>) -> Vec<HashSet<T>>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    sccs(nodes, &neighbor_function)
+}
+
+// The state of Tarjan's algorithm. Nodes are numbered in the order in which the depth-first search visits them, so the number of a node is its Tarjan index.
+struct Tarjan<T> {
+    ids: HashMap<T, usize>,
+    visited: Vec<T>,
+    lowlink: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<usize>,
+    // The depth-first search, as a stack of nodes with their remaining successors.
+    frames: Vec<(usize, std::vec::IntoIter<T>)>,
+}
+
+impl<T: Eq + Hash + Clone + Copy> Tarjan<T> {
+    fn visit(&mut self, node: T, neighbor_function: &impl Adjacency<T>) {
+        let id = self.visited.len();
+        self.ids.insert(node, id);
+        self.visited.push(node);
+        self.lowlink.push(id);
+        self.on_stack.push(true);
+        self.stack.push(id);
+        self.frames.push((id, neighbor_function.neighbors(node).collect::<Vec<_>>().into_iter()));
+    }
+}
+
+fn sccs<T>(nodes: &[T], neighbor_function: &impl Adjacency<T>) -> Vec<HashSet<T>>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let mut tarjan = Tarjan {
+        ids: HashMap::new(),
+        visited: vec![],
+        lowlink: vec![],
+        on_stack: vec![],
+        stack: vec![],
+        frames: vec![],
+    };
+    let mut sccs = vec![];
+
+    for &root in nodes {
+        if tarjan.ids.contains_key(&root) {
+            continue;
+        }
+        tarjan.visit(root, neighbor_function);
+
+        while let Some((v, successors)) = tarjan.frames.last_mut() {
+            let v = *v;
+            if let Some(w) = successors.next() {
+                match tarjan.ids.get(&w) {
+                    None => tarjan.visit(w, neighbor_function),
+                    Some(&w) if tarjan.on_stack[w] => tarjan.lowlink[v] = tarjan.lowlink[v].min(w),
+                    Some(_) => {}
+                }
+                continue;
+            }
+
+            tarjan.frames.pop();
+            if let Some(&(parent, _)) = tarjan.frames.last() {
+                tarjan.lowlink[parent] = tarjan.lowlink[parent].min(tarjan.lowlink[v]);
+            }
+            if tarjan.lowlink[v] == v {
+                let mut scc = HashSet::new();
+                while let Some(w) = tarjan.stack.pop() {
+                    tarjan.on_stack[w] = false;
+                    scc.insert(tarjan.visited[w]);
+                    if w == v {
+                        break;
+                    }
+                }
+                sccs.push(scc);
+            }
+        }
+    }
+
+    // Tarjan's algorithm finds every component after all components reachable from it.
+    sccs.reverse();
+    sccs
+}
+
+/// The condensation of a directed graph: the directed acyclic graph with a node for every strongly connected component.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Condensation<T: Eq + Hash> {
+    /// The strongly connected components, in topological order.
+    pub components: Vec<HashSet<T>>,
+    /// The index of the component of every node.
+    pub component_of: HashMap<T, usize>,
+    /// The edges between the components (by index), without duplicates. Every edge goes from a lower to a higher index.
+    pub graph: Graph,
+}
+
+/// Finds the condensation of a directed graph, by contracting its strongly connected components (see `find_sccs`).
+///
+/// # Example
+/// ```
+/// use hutspot::graph::find_condensation;
+/// use std::collections::HashSet;
+/// let neighbor_function = |node: u32| -> Vec<u32> {
+///     match node {
+///         1 => vec![2, 3],
+///         2 => vec![3],
+///         3 => vec![5],
+///         4 => vec![2, 6],
+///         5 => vec![4],
+///         6 => vec![],
+///         _ => vec![],
+///     }
+/// };
+///
+/// let condensation = find_condensation(&[1], neighbor_function);
+/// assert_eq!(condensation.components, vec![HashSet::from([1]), HashSet::from([2, 3, 4, 5]), HashSet::from([6])]);
+/// assert_eq!(condensation.component_of[&4], 1);
+/// assert_eq!(condensation.graph.neighbors(0), &[1]);
+/// assert_eq!(condensation.graph.neighbors(1), &[2]);
+/// assert_eq!(condensation.graph.neighbors(2), &[]);
+/// ```
+pub fn find_condensation<T>(nodes: &[T], neighbor_function: impl Fn(T) -> Vec<T>) -> Condensation<T>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    condensation(nodes, neighbor_function)
+}
+
+fn condensation<T>(nodes: &[T], neighbor_function: impl Adjacency<T>) -> Condensation<T>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let components = sccs(nodes, &neighbor_function);
+    let component_of = components
+        .iter()
+        .enumerate()
+        .flat_map(|(i, component)| component.iter().map(move |&node| (node, i)))
+        .collect::<HashMap<_, _>>();
+    let edges = components
+        .iter()
+        .enumerate()
+        .flat_map(|(i, component)| {
+            component
+                .iter()
+                .flat_map(|&node| neighbor_function.neighbors(node))
+                .map(move |neighbor| (i, neighbor))
+        })
+        .map(|(i, neighbor)| (i, component_of[&neighbor]))
+        .filter(|(i, j)| i != j)
+        .collect::<HashSet<_>>();
+    Condensation {
+        graph: Graph::from_edges(components.len(), edges, true).expect("edges connect components"),
+        components,
+        component_of,
+    }
+}
+
+/// Finds the weakly connected components of a directed graph: the connected components when ignoring the direction of the edges.
+///
+/// # Arguments
+/// * `nodes` - The nodes to start from. Nodes that are only reachable from them are included as well.
+/// * `neighbor_function` - A function that returns the successors of a given node.
+///
+/// # Returns
+/// * `Vec<HashSet<T>>` - The weakly connected components, in order of their first node in `nodes`.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::find_wccs;
+/// use std::collections::HashSet;
+/// let neighbor_function = |node: u32| -> Vec<u32> {
+///     match node {
+///         1 => vec![2, 3],
+///         2 => vec![3],
+///         3 => vec![5],
+///         4 => vec![2, 6],
+///         5 => vec![4],
+///         6 => vec![],
+///         _ => vec![],
+///     }
+/// };
+///
+/// // The result does not depend on the order of the nodes, as reachability would.
+/// let wccs = find_wccs(&[6, 5, 4, 3, 2, 1, 7], neighbor_function);
+/// assert_eq!(wccs, vec![HashSet::from([1, 2, 3, 4, 5, 6]), HashSet::from([7])]);
+/// ```
+pub fn find_wccs<T>(nodes: &[T], neighbor_function: impl Fn(T) -> Vec<T>) -> Vec<HashSet<T>>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    wccs(nodes, neighbor_function)
+}
+
+fn wccs<T>(nodes: &[T], neighbor_function: impl Adjacency<T>) -> Vec<HashSet<T>>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    // Collect all reachable nodes with their edges in both directions.
+    let mut undirected: HashMap<T, Vec<T>> = HashMap::new();
+    let mut expanded = HashSet::new();
+    let mut queue = nodes.to_vec();
+    while let Some(node) = queue.pop() {
+        if !expanded.insert(node) {
+            continue;
+        }
+        undirected.entry(node).or_default();
+        for successor in neighbor_function.neighbors(node) {
+            undirected.entry(node).or_default().push(successor);
+            undirected.entry(successor).or_default().push(node);
+            queue.push(successor);
+        }
+    }
+
+    let mut visited = HashSet::new();
+    let mut wccs = vec![];
+    for &node in nodes {
+        if visited.contains(&node) {
+            continue;
+        }
+        let wcc = reach(node, &|x: T| undirected[&x].clone());
+        visited.extend(wcc.iter().copied());
+        wccs.push(wcc);
+    }
+    wccs
+}
+
+/// A disjoint-set forest (union-find) over the elements `0..n`, with path halving and union by size.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::UnionFind;
+/// let mut sets = UnionFind::new(5);
+/// assert!(sets.union(0, 1));
+/// assert!(sets.union(3, 4));
+/// assert!(sets.union(1, 4));
+/// assert!(!sets.union(0, 3));
+/// assert!(sets.same_set(0, 4));
+/// assert!(!sets.same_set(0, 2));
+/// assert_eq!(sets.nr_sets(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    nr_sets: usize,
+}
+
+impl UnionFind {
+    /// Creates `n` singleton sets.
+    #[must_use]
+    pub fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+            nr_sets: n,
+        }
+    }
+
+    /// The representative of the set containing `x`.
+    pub fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    /// Merges the sets containing `a` and `b`. Returns `false` if they were already the same set.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.nr_sets -= 1;
+        true
+    }
+
+    pub fn same_set(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// The size of the set containing `x`.
+    pub fn set_size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+
+    #[must_use]
+    pub const fn nr_sets(&self) -> usize {
+        self.nr_sets
+    }
+}
+
+// Kruskal's algorithm on the undirected graph reachable from `nodes`. Returns the forest edges and their total weight.
+fn kruskal<T>(
+    nodes: &[T],
+    neighbor_function: &impl Adjacency<T>,
+    weight_function: &impl Fn(T, T) -> OrderedFloat<f64>,
+    maximum: bool,
+) -> (Vec<(T, T)>, OrderedFloat<f64>)
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let mut ids: HashMap<T, usize> = HashMap::new();
+    let mut queue = nodes.to_vec();
+    let mut edges = vec![];
+    while let Some(node) = queue.pop() {
+        let len = ids.len();
+        if *ids.entry(node).or_insert(len) != len {
+            continue;
+        }
+        for neighbor in neighbor_function.neighbors(node) {
+            edges.push((weight_function(node, neighbor), node, neighbor));
+            queue.push(neighbor);
+        }
+    }
+    // A stable sort keeps the result deterministic for equal weights.
+    if maximum {
+        edges.sort_by_key(|&(weight, ..)| Reverse(weight));
+    } else {
+        edges.sort_by_key(|&(weight, ..)| weight);
+    }
+
+    let mut sets = UnionFind::new(ids.len());
+    let forest = edges.into_iter().filter(|&(_, u, v)| sets.union(ids[&u], ids[&v])).collect::<Vec<_>>();
+    let total = forest.iter().map(|&(weight, ..)| weight).sum();
+    (forest.into_iter().map(|(_, u, v)| (u, v)).collect(), total)
+}
+
+/// Finds a minimum spanning forest of an undirected graph using Kruskal's algorithm.
+///
+/// # Arguments
+/// * `nodes` - The nodes of the graph. Nodes that are only reachable from them are included as well.
+/// * `neighbor_function` - A function that returns the neighbors of a given node. It must be symmetric.
+/// * `weight_function` - A function that returns the weight of the edge between two nodes.
+///
+/// # Returns
+/// * `(Vec<(T, T)>, OrderedFloat<f64>)` - The edges of the forest (a spanning tree for every connected component) and their total weight.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::{find_maximum_spanning_forest, find_minimum_spanning_forest};
+/// use ordered_float::OrderedFloat;
+///
+/// // A square 1-2-3-4 with diagonal 1-3, and a separate edge 5-6.
+/// let neighbor_function = |node: u32| -> Vec<u32> {
+///     match node {
+///         1 => vec![2, 3, 4],
+///         2 => vec![1, 3],
+///         3 => vec![1, 2, 4],
+///         4 => vec![1, 3],
+///         5 => vec![6],
+///         6 => vec![5],
+///         _ => vec![],
+///     }
+/// };
+/// let weight_function = |a: u32, b: u32| -> OrderedFloat<f64> {
+///     match (a.min(b), a.max(b)) {
+///         (1, 2) => 1.0.into(),
+///         (2, 3) => 2.0.into(),
+///         (3, 4) => 3.0.into(),
+///         (1, 4) => 4.0.into(),
+///         (1, 3) => 5.0.into(),
+///         (5, 6) => 6.0.into(),
+///         _ => OrderedFloat(f64::INFINITY),
+///     }
+/// };
+///
+/// let (forest, weight) = find_minimum_spanning_forest(&[1, 5], neighbor_function, weight_function);
+/// assert_eq!(forest.len(), 4);
+/// assert_eq!(weight, OrderedFloat(1. + 2. + 3. + 6.));
+///
+/// let (forest, weight) = find_maximum_spanning_forest(&[1, 5], neighbor_function, weight_function);
+/// assert_eq!(forest.len(), 4);
+/// assert_eq!(weight, OrderedFloat(5. + 4. + 2. + 6.));
+/// ```
+pub fn find_minimum_spanning_forest<T>(
+    nodes: &[T],
+    neighbor_function: impl Fn(T) -> Vec<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+) -> (Vec<(T, T)>, OrderedFloat<f64>)
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    kruskal(nodes, &neighbor_function, &weight_function, false)
+}
+
+/// Finds a maximum spanning forest of an undirected graph using Kruskal's algorithm. See `find_minimum_spanning_forest`.
+pub fn find_maximum_spanning_forest<T>(
+    nodes: &[T],
+    neighbor_function: impl Fn(T) -> Vec<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+) -> (Vec<(T, T)>, OrderedFloat<f64>)
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    kruskal(nodes, &neighbor_function, &weight_function, true)
+}
+
+/// Finds a minimum spanning tree of the connected component of `root` in an undirected graph using Prim's algorithm.
+///
+/// # Arguments
+/// * `root` - The node where the tree is grown from.
+/// * `neighbor_function` - A function that returns the neighbors of a given node. It must be symmetric.
+/// * `weight_function` - A function that returns the weight of the edge between two nodes.
+///
+/// # Returns
+/// * `(Vec<(T, T)>, OrderedFloat<f64>)` - The edges of the tree, each directed away from `root`, in the order in which they were added, and their total weight.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::find_minimum_spanning_tree;
+/// use ordered_float::OrderedFloat;
+///
+/// // A 3x3 grid with weights increasing to the right and to the top.
+/// let neighbor_function = |(x, y): (i32, i32)| -> Vec<(i32, i32)> {
+///     [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)].into_iter().filter(|&(x, y)| (0..3).contains(&x) && (0..3).contains(&y)).collect()
+/// };
+/// let weight_function = |a: (i32, i32), b: (i32, i32)| OrderedFloat(f64::from(a.0 + b.0 + 10 * (a.1 + b.1)));
+///
+/// let (tree, weight) = find_minimum_spanning_tree((1, 1), neighbor_function, weight_function);
+/// assert_eq!(tree.len(), 8);
+/// assert_eq!(tree[0], ((1, 1), (1, 0)));
+/// assert_eq!(weight, hutspot::graph::find_minimum_spanning_forest(&[(0, 0)], neighbor_function, weight_function).1);
+/// ```
+pub fn find_minimum_spanning_tree<T>(
+    root: T,
+    neighbor_function: impl Fn(T) -> Vec<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+) -> (Vec<(T, T)>, OrderedFloat<f64>)
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    minimum_spanning_tree(root, neighbor_function, weight_function)
+}
+
+fn minimum_spanning_tree<T>(
+    root: T,
+    neighbor_function: impl Adjacency<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+) -> (Vec<(T, T)>, OrderedFloat<f64>)
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let mut in_tree = HashSet::from([root]);
+    let mut tree = vec![];
+    let mut total = OrderedFloat(0.);
+    // Candidate edges leaving the tree, ordered by weight (ties broken by discovery order).
+    let mut candidates = vec![];
+    let mut queue = BinaryHeap::new();
+    let mut node = root;
+    loop {
+        for neighbor in neighbor_function.neighbors(node).filter(|neighbor| !in_tree.contains(neighbor)) {
+            queue.push(Reverse((weight_function(node, neighbor), candidates.len())));
+            candidates.push((node, neighbor));
+        }
+        let Some((weight, (u, v))) = std::iter::from_fn(|| queue.pop())
+            .map(|Reverse((weight, index))| (weight, candidates[index]))
+            .find(|(_, (_, v))| !in_tree.contains(v))
+        else {
+            break;
+        };
+        in_tree.insert(v);
+        tree.push((u, v));
+        total += weight;
+        node = v;
+    }
+    (tree, total)
+}
+
+/// The tree-cotree decomposition of a graph embedded on a surface. See `find_tree_cotree`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeCotree<V> {
+    /// Indices of the edges that form a spanning forest of the primal graph.
+    pub tree: Vec<usize>,
+    /// Indices of the edges whose duals form a spanning forest of the dual graph, disjoint from `tree`.
+    pub cotree: Vec<usize>,
+    /// Indices of the remaining edges. On a closed orientable surface of genus g there are exactly 2g of them.
+    pub generators: Vec<usize>,
+    /// For every generator, the cycle of vertices it closes with `tree`. The cycle starts at the first endpoint of the generator edge and ends at its second endpoint.
+    pub cycles: Vec<Vec<V>>,
+}
+
+/// Computes a tree-cotree decomposition of a graph embedded on a surface (Eppstein). The cycles closed by the leftover edges form a basis of the first homology group of the surface.
+///
+/// # Arguments
+/// * `edges` - The edges of the graph as `(u, v, f, g)`, where `u` and `v` are the endpoint vertices and `f` and `g` the two faces on either side of the edge. For surfaces with boundary, use one virtual face for everything outside of the surface.
+/// * `weight_function` - A function that returns the weight of an edge, given its index. Short edges are preferred in the tree, long edges in the cotree.
+///
+/// # Returns
+/// * `TreeCotree<V>` - The tree is a minimum spanning forest of the primal graph, the cotree a maximum spanning forest of the dual graph without the duals of the tree edges.
+///
+/// # Source
+/// Eppstein, D. (2003). Dynamic generators of topologically embedded graphs. SODA.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::find_tree_cotree;
+/// use ordered_float::OrderedFloat;
+///
+/// // A 4x3 grid of quads, glued into a torus. Face (i, j) has corner (i, j) as its lower left corner.
+/// let (n, m) = (4, 3);
+/// let mut edges = vec![];
+/// for i in 0..n {
+///     for j in 0..m {
+///         edges.push(((i, j), ((i + 1) % n, j), (i, j), (i, (j + m - 1) % m)));
+///         edges.push(((i, j), (i, (j + 1) % m), (i, j), ((i + n - 1) % n, j)));
+///     }
+/// }
+///
+/// let decomposition = find_tree_cotree(&edges, |_| OrderedFloat(1.));
+/// assert_eq!(decomposition.tree.len(), n * m - 1);
+/// assert_eq!(decomposition.cotree.len(), n * m - 1);
+/// assert_eq!(decomposition.generators.len(), 2);
+///
+/// for (&generator, cycle) in decomposition.generators.iter().zip(&decomposition.cycles) {
+///     let (u, v, ..) = edges[generator];
+///     assert_eq!((cycle[0], cycle[cycle.len() - 1]), (u, v));
+///     // Consecutive vertices of the cycle are connected by tree edges.
+///     for pair in cycle.windows(2) {
+///         assert!(decomposition.tree.iter().any(|&e| {
+///             let (a, b, ..) = edges[e];
+///             (a, b) == (pair[0], pair[1]) || (b, a) == (pair[0], pair[1])
+///         }));
+///     }
+/// }
+/// ```
+pub fn find_tree_cotree<V, F>(edges: &[(V, V, F, F)], weight_function: impl Fn(usize) -> OrderedFloat<f64>) -> TreeCotree<V>
+where
+    V: Eq + Hash + Clone + Copy,
+    F: Eq + Hash + Clone + Copy,
+{
+    let mut vertex_ids: HashMap<V, usize> = HashMap::new();
+    let mut face_ids: HashMap<F, usize> = HashMap::new();
+    for &(u, v, f, g) in edges {
+        for vertex in [u, v] {
+            let len = vertex_ids.len();
+            vertex_ids.entry(vertex).or_insert(len);
+        }
+        for face in [f, g] {
+            let len = face_ids.len();
+            face_ids.entry(face).or_insert(len);
+        }
+    }
+
+    // A stable sort keeps the result deterministic for equal weights.
+    let mut order = (0..edges.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&e| weight_function(e));
+
+    let mut primal = UnionFind::new(vertex_ids.len());
+    let mut in_tree = vec![false; edges.len()];
+    for &e in &order {
+        let (u, v, ..) = edges[e];
+        in_tree[e] = primal.union(vertex_ids[&u], vertex_ids[&v]);
+    }
+
+    let mut dual = UnionFind::new(face_ids.len());
+    let mut in_cotree = vec![false; edges.len()];
+    for &e in order.iter().rev().filter(|&&e| !in_tree[e]) {
+        let (.., f, g) = edges[e];
+        in_cotree[e] = dual.union(face_ids[&f], face_ids[&g]);
+    }
+
+    let tree = (0..edges.len()).filter(|&e| in_tree[e]).collect::<Vec<_>>();
+    let cotree = (0..edges.len()).filter(|&e| in_cotree[e]).collect::<Vec<_>>();
+    let generators = (0..edges.len()).filter(|&e| !in_tree[e] && !in_cotree[e]).collect::<Vec<_>>();
+
+    // Root every tree of the forest, to walk from both endpoints of a generator to their common ancestor.
+    let mut tree_neighbors: HashMap<V, Vec<V>> = HashMap::new();
+    for &e in &tree {
+        let (u, v, ..) = edges[e];
+        tree_neighbors.entry(u).or_default().push(v);
+        tree_neighbors.entry(v).or_default().push(u);
+    }
+    let mut parent: HashMap<V, (V, usize)> = HashMap::new();
+    for &root in vertex_ids.keys() {
+        if parent.contains_key(&root) {
+            continue;
+        }
+        parent.insert(root, (root, 0));
+        let mut stack = vec![root];
+        while let Some(node) = stack.pop() {
+            let depth = parent[&node].1;
+            for &neighbor in tree_neighbors.get(&node).into_iter().flatten() {
+                if let std::collections::hash_map::Entry::Vacant(entry) = parent.entry(neighbor) {
+                    entry.insert((node, depth + 1));
+                    stack.push(neighbor);
+                }
+            }
+        }
+    }
+
+    let cycles = generators
+        .iter()
+        .map(|&e| {
+            let (mut u, mut v, ..) = edges[e];
+            let (mut from_u, mut from_v) = (vec![u], vec![v]);
+            while u != v {
+                if parent[&u].1 >= parent[&v].1 {
+                    u = parent[&u].0;
+                    from_u.push(u);
+                } else {
+                    v = parent[&v].0;
+                    from_v.push(v);
+                }
+            }
+            from_v.pop();
+            from_u.extend(from_v.into_iter().rev());
+            from_u
+        })
+        .collect();
+
+    TreeCotree {
+        tree,
+        cotree,
+        generators,
+        cycles,
+    }
+}
+
+/// The algorithm used by `find_max_flow` and `find_max_flow_multi`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MaxFlowAlgorithm {
+    /// Dinic's blocking flow algorithm. Fast on unit capacity graphs.
+    Dinic,
+    /// FIFO push-relabel with the global relabeling and gap heuristics. Usually the fastest on large graphs.
+    #[default]
+    PushRelabel,
+}
+
+/// A maximum flow and a corresponding minimum cut. See `find_max_flow`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaxFlow<T: Eq + Hash> {
+    /// The value of the flow, equal to the capacity of the minimum cut.
+    pub value: OrderedFloat<f64>,
+    /// The (net) flow over every edge that carries flow.
+    pub flows: HashMap<(T, T), OrderedFloat<f64>>,
+    /// The nodes on the source side of a minimum cut: those reachable from the source(s) in the residual graph.
+    pub source_side: HashSet<T>,
+}
+
+// A residual network with paired arcs: arc `a ^ 1` is the reverse of arc `a`, and even arcs are the original ones.
+struct FlowNetwork {
+    arcs: Vec<Vec<usize>>,
+    to: Vec<usize>,
+    capacity: Vec<f64>,
+    residual: Vec<f64>,
+    cost: Vec<f64>,
+}
+
+impl FlowNetwork {
+    fn new(nr_nodes: usize) -> Self {
+        Self {
+            arcs: vec![vec![]; nr_nodes],
+            to: vec![],
+            capacity: vec![],
+            residual: vec![],
+            cost: vec![],
+        }
+    }
+
+    fn add_arc(&mut self, from: usize, to: usize, capacity: f64, cost: f64) {
+        for (tail, head, capacity, cost) in [(from, to, capacity, cost), (to, from, 0., -cost)] {
+            self.arcs[tail].push(self.to.len());
+            self.to.push(head);
+            self.capacity.push(capacity);
+            self.residual.push(capacity);
+            self.cost.push(cost);
+        }
+    }
+
+    // Breadth-first search in the residual graph, from `start` (or towards `start` if `reverse`).
+    fn levels(&self, start: usize, reverse: bool) -> Vec<usize> {
+        let mut level = vec![usize::MAX; self.arcs.len()];
+        level[start] = 0;
+        let mut queue = std::collections::VecDeque::from([start]);
+        while let Some(node) = queue.pop_front() {
+            for &arc in &self.arcs[node] {
+                let usable = if reverse { self.residual[arc ^ 1] } else { self.residual[arc] } > 0.;
+                if usable && level[self.to[arc]] == usize::MAX {
+                    level[self.to[arc]] = level[node] + 1;
+                    queue.push_back(self.to[arc]);
+                }
+            }
+        }
+        level
+    }
+
+    fn dinic(&mut self, source: usize, sink: usize) -> f64 {
+        let mut total = 0.;
+        loop {
+            let mut level = self.levels(source, false);
+            if level[sink] == usize::MAX {
+                return total;
+            }
+            // Find a blocking flow with an iterative depth-first search, keeping the current path as a stack of arcs.
+            let mut current = vec![0; self.arcs.len()];
+            let mut path: Vec<usize> = vec![];
+            let mut node = source;
+            loop {
+                if node == sink {
+                    let bottleneck = path.iter().map(|&arc| self.residual[arc]).fold(f64::INFINITY, f64::min);
+                    for &arc in &path {
+                        self.residual[arc] -= bottleneck;
+                        self.residual[arc ^ 1] += bottleneck;
+                    }
+                    total += bottleneck;
+                    // Retreat to the tail of the first saturated arc.
+                    let saturated = path.iter().position(|&arc| self.residual[arc] <= 0.).unwrap_or(0);
+                    path.truncate(saturated);
+                    node = path.last().map_or(source, |&arc| self.to[arc]);
+                    continue;
+                }
+                let next = self.arcs[node][current[node]..]
+                    .iter()
+                    .position(|&arc| self.residual[arc] > 0. && level[self.to[arc]] == level[node] + 1);
+                if let Some(offset) = next {
+                    current[node] += offset;
+                    let arc = self.arcs[node][current[node]];
+                    path.push(arc);
+                    node = self.to[arc];
+                } else {
+                    // Dead end: remove the node from the level graph and retreat.
+                    current[node] = self.arcs[node].len();
+                    level[node] = usize::MAX;
+                    let Some(arc) = path.pop() else {
+                        break;
+                    };
+                    node = self.to[arc ^ 1];
+                    current[node] += 1;
+                }
+            }
+        }
+    }
+
+    // Exact distance labels: the distance to the sink in the residual graph, or n plus the distance to the source for nodes that cannot reach the sink, or 2n for nodes that can reach neither.
+    fn labels(&self, source: usize, sink: usize) -> Vec<usize> {
+        let n = self.arcs.len();
+        let mut height = vec![2 * n; n];
+        height[source] = n;
+        for (start, offset) in [(sink, 0), (source, n)] {
+            height[start] = offset;
+            let mut queue = std::collections::VecDeque::from([start]);
+            while let Some(node) = queue.pop_front() {
+                for &arc in &self.arcs[node] {
+                    let from = self.to[arc];
+                    if self.residual[arc ^ 1] > 0. && height[from] == 2 * n {
+                        height[from] = height[node] + 1;
+                        queue.push_back(from);
+                    }
+                }
+            }
+        }
+        height
+    }
+
+    #[allow(clippy::while_float)]
+    fn push_relabel(&mut self, source: usize, sink: usize) -> f64 {
+        let n = self.arcs.len();
+        let mut excess = vec![0.; n];
+        let mut current = vec![0; n];
+        let mut queue = std::collections::VecDeque::new();
+
+        for index in 0..self.arcs[source].len() {
+            let arc = self.arcs[source][index];
+            let (to, amount) = (self.to[arc], self.residual[arc]);
+            if amount > 0. {
+                self.residual[arc] = 0.;
+                self.residual[arc ^ 1] += amount;
+                if excess[to] <= 0. && to != sink && to != source {
+                    queue.push_back(to);
+                }
+                excess[to] += amount;
+            }
+        }
+
+        let mut height = vec![];
+        let mut count = vec![];
+        // Start with exact labels, and recompute them after every n relabels (the global relabeling heuristic).
+        let mut relabels = n;
+        while let Some(node) = queue.pop_front() {
+            if relabels >= n {
+                relabels = 0;
+                height = self.labels(source, sink);
+                count = vec![0; 2 * n + 1];
+                for &h in &height {
+                    count[h] += 1;
+                }
+                current.fill(0);
+            }
+            while excess[node] > 0. {
+                if current[node] == self.arcs[node].len() {
+                    // Relabel. A node that needs a height of 2n can no longer reach the source in the residual graph, so its excess is left over from rounding errors.
+                    let old = height[node];
+                    let new = self.arcs[node]
+                        .iter()
+                        .filter(|&&arc| self.residual[arc] > 0.)
+                        .map(|&arc| height[self.to[arc]] + 1)
+                        .min()
+                        .unwrap_or(2 * n);
+                    if old >= 2 * n || new >= 2 * n {
+                        excess[node] = 0.;
+                        break;
+                    }
+                    relabels += 1;
+                    height[node] = new;
+                    current[node] = 0;
+                    count[old] -= 1;
+                    count[new] += 1;
+                    // Gap heuristic: nodes above an empty level can no longer reach the sink.
+                    if count[old] == 0 && old < n {
+                        for v in 0..n {
+                            if height[v] > old && height[v] < n && v != source {
+                                count[height[v]] -= 1;
+                                height[v] = n + 1;
+                                count[n + 1] += 1;
+                            }
+                        }
+                    }
+                    continue;
+                }
+                let arc = self.arcs[node][current[node]];
+                let to = self.to[arc];
+                if self.residual[arc] > 0. && height[node] == height[to] + 1 {
+                    let amount = excess[node].min(self.residual[arc]);
+                    self.residual[arc] -= amount;
+                    self.residual[arc ^ 1] += amount;
+                    excess[node] -= amount;
+                    if excess[to] <= 0. && to != sink && to != source {
+                        queue.push_back(to);
+                    }
+                    excess[to] += amount;
+                } else {
+                    current[node] += 1;
+                }
+            }
+        }
+        excess[sink]
+    }
+}
+
+// Collects the nodes reachable from `starts`, their indices, and the edges between them (without self-loops).
+#[allow(clippy::type_complexity)]
+fn flow_edges<T>(starts: impl Iterator<Item = T>, neighbor_function: &impl Adjacency<T>) -> (Vec<T>, HashMap<T, usize>, Vec<(T, T)>)
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let mut nodes = vec![];
+    let mut ids: HashMap<T, usize> = HashMap::new();
+    let mut edges = vec![];
+    let mut stack = starts.collect::<Vec<_>>();
+    while let Some(node) = stack.pop() {
+        if ids.contains_key(&node) {
+            continue;
+        }
+        ids.insert(node, nodes.len());
+        nodes.push(node);
+        for neighbor in neighbor_function.neighbors(node) {
+            if neighbor != node {
+                edges.push((node, neighbor));
+                stack.push(neighbor);
+            }
+        }
+    }
+    (nodes, ids, edges)
+}
+
+// Builds the flow network of everything reachable from `sources`, with a virtual super source and super sink when needed, and solves it.
+fn max_flow<T>(
+    sources: &[T],
+    sinks: &[T],
+    neighbor_function: &impl Adjacency<T>,
+    capacity_function: &impl Fn(T, T) -> OrderedFloat<f64>,
+    algorithm: MaxFlowAlgorithm,
+) -> MaxFlow<T>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let (nodes, ids, edges) = flow_edges(sources.iter().chain(sinks).copied(), neighbor_function);
+    let edges = edges.into_iter().map(|(u, v)| (u, v, capacity_function(u, v).0)).collect::<Vec<_>>();
+
+    let n = nodes.len();
+    let single = sources.len() == 1 && sinks.len() == 1;
+    let mut network = FlowNetwork::new(if single { n } else { n + 2 });
+    for &(u, v, capacity) in &edges {
+        network.add_arc(ids[&u], ids[&v], capacity, 0.);
+    }
+    let (source, sink) = if single {
+        (ids[&sources[0]], ids[&sinks[0]])
+    } else {
+        // The super arcs get more than the total capacity of their terminal, so they are never saturated.
+        let mut out_capacity = vec![0.; n];
+        let mut in_capacity = vec![0.; n];
+        for &(u, v, capacity) in &edges {
+            out_capacity[ids[&u]] += capacity;
+            in_capacity[ids[&v]] += capacity;
+        }
+        for source in sources {
+            network.add_arc(n, ids[source], out_capacity[ids[source]].mul_add(2., 1.), 0.);
+        }
+        for sink in sinks {
+            network.add_arc(ids[sink], n + 1, in_capacity[ids[sink]].mul_add(2., 1.), 0.);
+        }
+        (n, n + 1)
+    };
+
+    let value = if source == sink {
+        0.
+    } else {
+        match algorithm {
+            MaxFlowAlgorithm::Dinic => network.dinic(source, sink),
+            MaxFlowAlgorithm::PushRelabel => network.push_relabel(source, sink),
+        }
+    };
+
+    let mut flows: HashMap<(T, T), OrderedFloat<f64>> = HashMap::new();
+    for (index, &(u, v, capacity)) in edges.iter().enumerate() {
+        let flow = capacity - network.residual[2 * index];
+        if flow > 0. {
+            *flows.entry((u, v)).or_default() += flow;
+        }
+    }
+    // Cancel flow that goes back and forth between two nodes.
+    let keys = flows.keys().copied().collect::<Vec<_>>();
+    for (u, v) in keys {
+        if let (Some(&forward), Some(&backward)) = (flows.get(&(u, v)), flows.get(&(v, u))) {
+            let common = forward.min(backward);
+            flows.insert((u, v), forward - common);
+            flows.insert((v, u), backward - common);
+        }
+    }
+    flows.retain(|_, flow| flow.0 > 0.);
+
+    let level = network.levels(source, false);
+    let source_side = (0..n).filter(|&id| level[id] != usize::MAX).map(|id| nodes[id]).collect();
+
+    MaxFlow {
+        value: OrderedFloat(value),
+        flows,
+        source_side,
+    }
+}
+
+/// Finds a maximum flow from `source` to `sink`, and a minimum cut separating them.
+///
+/// # Arguments
+/// * `source` - The source node.
+/// * `sink` - The sink node.
+/// * `neighbor_function` - A function that returns the (outgoing) neighbors of a given node. For undirected graphs it must be symmetric.
+/// * `capacity_function` - A function that returns the capacity of the edge between two nodes. Capacities must be finite and non-negative.
+/// * `algorithm` - The algorithm to use.
+///
+/// # Returns
+/// * `MaxFlow<T>` - The flow value, the flow over every edge, and the source side of a minimum cut.
+///
+/// # Source
+/// Dinitz, Y. (1970). Algorithm for solution of a problem of maximum flow in networks with power estimation.
+/// Goldberg, A. V., & Tarjan, R. E. (1988). A new approach to the maximum-flow problem. Journal of the ACM.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::{find_max_flow, MaxFlowAlgorithm};
+/// use ordered_float::OrderedFloat;
+///
+/// // The flow network from CLRS, with source 0 and sink 5.
+/// let edges = [(0, 1, 16.), (0, 2, 13.), (2, 1, 4.), (1, 3, 12.), (3, 2, 9.), (2, 4, 14.), (4, 3, 7.), (3, 5, 20.), (4, 5, 4.)];
+/// let neighbor_function = |node: usize| -> Vec<usize> { edges.iter().filter(|e| e.0 == node).map(|e| e.1).collect() };
+/// let capacity_function = |a: usize, b: usize| -> OrderedFloat<f64> { edges.iter().find(|e| (e.0, e.1) == (a, b)).map_or(OrderedFloat(0.), |e| OrderedFloat(e.2)) };
+///
+/// for algorithm in [MaxFlowAlgorithm::Dinic, MaxFlowAlgorithm::PushRelabel] {
+///     let flow = find_max_flow(0, 5, neighbor_function, capacity_function, algorithm);
+///     assert_eq!(flow.value, OrderedFloat(23.));
+///     assert_eq!(flow.source_side, [0, 1, 2, 4].into_iter().collect());
+///
+///     // The flow respects capacities and is conserved in every node other than the source and sink.
+///     for (&(a, b), &f) in &flow.flows {
+///         assert!(f <= capacity_function(a, b));
+///     }
+///     for node in 1..5 {
+///         let inflow: f64 = flow.flows.iter().filter(|((_, b), _)| *b == node).map(|(_, f)| f.0).sum();
+///         let outflow: f64 = flow.flows.iter().filter(|((a, _), _)| *a == node).map(|(_, f)| f.0).sum();
+///         assert_eq!(inflow, outflow);
+///     }
+/// }
+///
+/// // A sink that cannot be reached gets no flow, and the source side holds everything reachable.
+/// let neighbor_function = |node: usize| -> Vec<usize> { if node == 0 { vec![1] } else { vec![] } };
+/// for algorithm in [MaxFlowAlgorithm::Dinic, MaxFlowAlgorithm::PushRelabel] {
+///     let flow = find_max_flow(0, 2, neighbor_function, |_, _| OrderedFloat(5.), algorithm);
+///     assert_eq!(flow.value, OrderedFloat(0.));
+///     assert_eq!(flow.source_side, [0, 1].into_iter().collect());
+/// }
+/// ```
+///
+/// Both algorithms agree on random graphs, and the value equals the capacity of the cut:
+/// ```
+/// use hutspot::graph::{find_max_flow, MaxFlowAlgorithm};
+/// use ordered_float::OrderedFloat;
+///
+/// let mut seed: u64 = 42;
+/// let mut random = move || {
+///     seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+///     seed >> 33
+/// };
+/// for _ in 0..100 {
+///     let n = 2 + random() as usize % 10;
+///     let capacities = (0..n * n).map(|_| if random() % 3 == 0 { (random() % 10) as f64 } else { 0. }).collect::<Vec<_>>();
+///     let neighbor_function = |a: usize| -> Vec<usize> { (0..n).filter(|&b| capacities[a * n + b] > 0.).collect() };
+///     let capacity_function = |a: usize, b: usize| OrderedFloat(capacities[a * n + b]);
+///
+///     let dinic = find_max_flow(0, n - 1, neighbor_function, capacity_function, MaxFlowAlgorithm::Dinic);
+///     let push_relabel = find_max_flow(0, n - 1, neighbor_function, capacity_function, MaxFlowAlgorithm::PushRelabel);
+///     assert_eq!(dinic.value, push_relabel.value);
+///     for flow in [dinic, push_relabel] {
+///         assert!(!flow.source_side.contains(&(n - 1)));
+///         let cut: f64 = flow.source_side.iter().flat_map(|&a| neighbor_function(a).into_iter().filter(|b| !flow.source_side.contains(b)).map(move |b| capacity_function(a, b).0)).sum();
+///         assert_eq!(flow.value, OrderedFloat(cut));
+///     }
+/// }
+/// ```
+pub fn find_max_flow<T>(
+    source: T,
+    sink: T,
+    neighbor_function: impl Fn(T) -> Vec<T>,
+    capacity_function: impl Fn(T, T) -> OrderedFloat<f64>,
+    algorithm: MaxFlowAlgorithm,
+) -> MaxFlow<T>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    max_flow(&[source], &[sink], &neighbor_function, &capacity_function, algorithm)
+}
+
+/// Finds a maximum flow from a set of sources to a set of sinks, and a minimum cut separating them. See `find_max_flow`.
+///
+/// # Arguments
+/// * `sources` - The source nodes.
+/// * `sinks` - The sink nodes, disjoint from the sources.
+/// * `neighbor_function` - A function that returns the (outgoing) neighbors of a given node. For undirected graphs it must be symmetric.
+/// * `capacity_function` - A function that returns the capacity of the edge between two nodes. Capacities must be finite and non-negative.
+/// * `algorithm` - The algorithm to use.
+///
+/// # Returns
+/// * `MaxFlow<T>` - The flow value, the flow over every edge, and the source side of a minimum cut (which contains all sources).
+///
+/// # Example
+/// ```
+/// use hutspot::graph::{find_max_flow_multi, MaxFlowAlgorithm};
+/// use ordered_float::OrderedFloat;
+///
+/// // Segment a path of 6 nodes, labeled from the left and the right, with a weak link between 2 and 3.
+/// let neighbor_function = |node: i32| -> Vec<i32> { [node - 1, node + 1].into_iter().filter(|n| (0..6).contains(n)).collect() };
+/// let capacity_function = |a: i32, b: i32| OrderedFloat(if a.min(b) == 2 { 1. } else { 5. });
+///
+/// let flow = find_max_flow_multi(&[0, 1], &[4, 5], neighbor_function, capacity_function, MaxFlowAlgorithm::default());
+/// assert_eq!(flow.value, OrderedFloat(1.));
+/// assert_eq!(flow.source_side, [0, 1, 2].into_iter().collect());
+/// ```
+pub fn find_max_flow_multi<T>(
+    sources: &[T],
+    sinks: &[T],
+    neighbor_function: impl Fn(T) -> Vec<T>,
+    capacity_function: impl Fn(T, T) -> OrderedFloat<f64>,
+    algorithm: MaxFlowAlgorithm,
+) -> MaxFlow<T>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    max_flow(sources, sinks, &neighbor_function, &capacity_function, algorithm)
+}
+
+/// The algorithm used by `find_min_cost_flow` and `find_min_cost_max_flow`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MinCostFlowAlgorithm {
+    /// Successive shortest paths for small instances, network simplex for large instances.
+    #[default]
+    Auto,
+    /// Successive shortest paths with node potentials. Fast when the total supply is small.
+    SuccessiveShortestPaths,
+    /// Primal network simplex with block search pivoting.
+    NetworkSimplex,
+}
+
+/// Errors that can occur when solving a minimum cost flow problem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MinCostFlowError {
+    /// The supplies and demands do not sum to zero.
+    Unbalanced,
+    /// The supplies cannot be routed to the demands within the capacities.
+    Infeasible,
+    /// The cost can be made arbitrarily low, because a cycle of negative cost has an unlimited capacity.
+    Unbounded,
+}
+
+impl fmt::Display for MinCostFlowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unbalanced => write!(f, "supplies and demands do not sum to zero"),
+            Self::Infeasible => write!(f, "supplies cannot be routed to demands within the capacities"),
+            Self::Unbounded => write!(f, "a cycle of negative cost has an unlimited capacity"),
+        }
+    }
+}
+
+impl std::error::Error for MinCostFlowError {}
+
+/// A minimum cost flow. See `find_min_cost_flow`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinCostFlow<T: Eq + Hash> {
+    /// The total amount of flow sent from the supplies to the demands.
+    pub value: OrderedFloat<f64>,
+    /// The total cost of the flow.
+    pub cost: OrderedFloat<f64>,
+    /// The flow over every edge that carries flow.
+    pub flows: HashMap<(T, T), OrderedFloat<f64>>,
+}
+
+// Instances with more arcs than this are solved with the network simplex method by `MinCostFlowAlgorithm::Auto`.
+const NETWORK_SIMPLEX_THRESHOLD: usize = 5000;
+
+// The tolerance for leftover supply, relative to the total supply.
+const FLOW_TOLERANCE: f64 = 1e-9;
+
+// Successive shortest paths on arcs `(from, to, capacity, cost)`. Arcs with a negative cost are saturated up front and replaced by their (positive cost) reverse, so that Dijkstra can be used from the start.
+fn successive_shortest_paths(supply: &[f64], arcs: &[(usize, usize, f64, f64)]) -> Result<Vec<f64>, MinCostFlowError> {
+    let n = supply.len();
+    // Negative arcs with an unlimited capacity cannot be saturated. If they are not on a negative cycle of unlimited arcs, some optimal flow is a spanning tree solution,
+    // whose flows are at most the total supply plus all finite capacities, so that bound can be used as their capacity instead.
+    if arcs.iter().any(|&(.., capacity, cost)| capacity.is_infinite() && cost < 0.) {
+        let mut adjacency = vec![vec![]; n];
+        let mut cheapest: HashMap<(usize, usize), f64> = HashMap::new();
+        for &(u, v, capacity, cost) in arcs {
+            if capacity.is_infinite() {
+                adjacency[u].push(v);
+                cheapest.entry((u, v)).and_modify(|old| *old = old.min(cost)).or_insert(cost);
+            }
+        }
+        let nodes = (0..n).collect::<Vec<_>>();
+        if negative_shortest_path_forest(
+            &nodes,
+            &|u: usize| adjacency[u].clone(),
+            &|u, v| OrderedFloat(cheapest[&(u, v)]),
+            NegativeWeightAlgorithm::Spfa,
+        )
+        .is_err()
+        {
+            return Err(MinCostFlowError::Unbounded);
+        }
+    }
+    let bound = supply.iter().filter(|&&amount| amount > 0.).sum::<f64>()
+        + arcs
+            .iter()
+            .map(|&(_, _, capacity, _)| capacity)
+            .filter(|capacity| capacity.is_finite())
+            .sum::<f64>()
+        + 1.;
+
+    let mut supply = supply.to_vec();
+    let mut network = FlowNetwork::new(n + 2);
+    for &(u, v, capacity, cost) in arcs {
+        if cost < 0. {
+            let capacity = capacity.min(bound);
+            supply[u] -= capacity;
+            supply[v] += capacity;
+            network.add_arc(v, u, capacity, -cost);
+        } else {
+            network.add_arc(u, v, capacity, cost);
+        }
+    }
+    let (source, sink) = (n, n + 1);
+    for (v, &amount) in supply.iter().enumerate() {
+        if amount > 0. {
+            network.add_arc(source, v, amount, 0.);
+        } else if amount < 0. {
+            network.add_arc(v, sink, -amount, 0.);
+        }
+    }
+    let total: f64 = supply.iter().filter(|&&amount| amount > 0.).sum();
+
+    let mut routed = 0.;
+    let mut potential = vec![0.; n + 2];
+    loop {
+        let mut distance = vec![f64::INFINITY; n + 2];
+        let mut predecessor = vec![usize::MAX; n + 2];
+        distance[source] = 0.;
+        let mut queue = BinaryHeap::from([Reverse((OrderedFloat(0.), source))]);
+        while let Some(Reverse((OrderedFloat(d), node))) = queue.pop() {
+            if d > distance[node] {
+                continue;
+            }
+            for &arc in &network.arcs[node] {
+                let to = network.to[arc];
+                // Reduced costs are non-negative up to rounding errors.
+                let reduced = (network.cost[arc] + potential[node] - potential[to]).max(0.);
+                if network.residual[arc] > 0. && d + reduced < distance[to] {
+                    distance[to] = d + reduced;
+                    predecessor[to] = arc;
+                    queue.push(Reverse((OrderedFloat(distance[to]), to)));
+                }
+            }
+        }
+        if distance[sink].is_infinite() {
+            break;
+        }
+        for (p, d) in potential.iter_mut().zip(&distance) {
+            *p += d.min(distance[sink]);
+        }
+
+        let mut path = vec![];
+        let mut node = sink;
+        while node != source {
+            path.push(predecessor[node]);
+            node = network.to[predecessor[node] ^ 1];
+        }
+        let bottleneck = path.iter().map(|&arc| network.residual[arc]).fold(f64::INFINITY, f64::min);
+        for &arc in &path {
+            network.residual[arc] -= bottleneck;
+            network.residual[arc ^ 1] += bottleneck;
+        }
+        routed += bottleneck;
+    }
+    if total - routed > FLOW_TOLERANCE * total {
+        return Err(MinCostFlowError::Infeasible);
+    }
+
+    Ok(arcs
+        .iter()
+        .enumerate()
+        .map(|(index, &(.., cost))| {
+            // The reverse arc holds the flow, which also works for unlimited capacities.
+            let flow = network.residual[2 * index + 1];
+            if cost < 0. { network.capacity[2 * index] - flow } else { flow }
+        })
+        .collect())
+}
+
+// Primal network simplex on arcs `(from, to, capacity, cost)`, starting from a spanning tree of big-M artificial arcs between every node and an artificial root. The tree is kept strongly feasible to prevent cycling.
+#[allow(clippy::many_single_char_names, clippy::too_many_lines, clippy::cast_precision_loss)]
+fn network_simplex(supply: &[f64], arcs: &[(usize, usize, f64, f64)]) -> Result<Vec<f64>, MinCostFlowError> {
+    // Arc states: at the lower bound, at the upper bound, or in the tree. Directions of tree arcs relative to the child node.
+    const LOWER: f64 = 1.;
+    const UPPER: f64 = -1.;
+    const TREE: f64 = 0.;
+    const UP: f64 = 1.;
+    const DOWN: f64 = -1.;
+
+    let n = supply.len();
+    let m = arcs.len();
+    let root = n;
+    let max_cost = arcs.iter().map(|&(.., cost)| cost.abs()).fold(0., f64::max);
+    let artificial_cost = (max_cost + 1.) * (n as f64 + 1.);
+    let tolerance = artificial_cost * 1e-12;
+
+    let mut from = arcs.iter().map(|&(u, ..)| u).collect::<Vec<_>>();
+    let mut to = arcs.iter().map(|&(_, v, ..)| v).collect::<Vec<_>>();
+    let mut capacity = arcs.iter().map(|&(_, _, capacity, _)| capacity).collect::<Vec<_>>();
+    let mut cost = arcs.iter().map(|&(.., cost)| cost).collect::<Vec<_>>();
+    let mut flow = vec![0.; m];
+    let mut state = vec![LOWER; m];
+
+    let mut parent = vec![root; n + 1];
+    let mut pred = vec![usize::MAX; n + 1];
+    let mut direction = vec![UP; n + 1];
+    let mut depth = vec![1; n + 1];
+    let mut potential = vec![0.; n + 1];
+    let mut children = vec![vec![]; n + 1];
+    depth[root] = 0;
+    for (v, &amount) in supply.iter().enumerate() {
+        pred[v] = m + v;
+        if amount > 0. {
+            from.push(v);
+            to.push(root);
+            flow.push(amount);
+            direction[v] = UP;
+            potential[v] = -artificial_cost;
+        } else {
+            from.push(root);
+            to.push(v);
+            flow.push(-amount);
+            direction[v] = DOWN;
+            potential[v] = artificial_cost;
+        }
+        capacity.push(f64::INFINITY);
+        cost.push(artificial_cost);
+        state.push(TREE);
+        children[root].push(v);
+    }
+
+    let nr_arcs = m + n;
+    let block_size = nr_arcs.isqrt().max(10);
+    let mut next_arc = 0;
+    loop {
+        // Block search: scan blocks of arcs and pick the most violating arc of the first block that has one.
+        let mut entering = None;
+        let mut best = -tolerance;
+        let mut scanned = 0;
+        while scanned < nr_arcs {
+            for _ in 0..block_size.min(nr_arcs - scanned) {
+                let arc = next_arc;
+                next_arc = (next_arc + 1) % nr_arcs;
+                let violation = state[arc] * (cost[arc] + potential[from[arc]] - potential[to[arc]]);
+                if violation < best {
+                    best = violation;
+                    entering = Some(arc);
+                }
+            }
+            scanned += block_size;
+            if entering.is_some() {
+                break;
+            }
+        }
+        let Some(entering) = entering else {
+            break;
+        };
+
+        // The cycle sends flow from `first` over the entering arc to `second`, and back through the tree via their common ancestor `apex`.
+        let (first, second) = if state[entering] == LOWER {
+            (from[entering], to[entering])
+        } else {
+            (to[entering], from[entering])
+        };
+        let (mut a, mut b) = (first, second);
+        while a != b {
+            if depth[a] >= depth[b] {
+                a = parent[a];
+            } else {
+                b = parent[b];
+            }
+        }
+        let apex = a;
+
+        // Find the leaving arc: the last blocking arc in the direction of the cycle, starting from the apex.
+        let mut delta = capacity[entering];
+        let mut leaving = None;
+        let mut u = first;
+        while u != apex {
+            let e = pred[u];
+            let residual = if direction[u] == UP { flow[e] } else { capacity[e] - flow[e] };
+            if residual < delta {
+                delta = residual;
+                leaving = Some((u, true));
+            }
+            u = parent[u];
+        }
+        let mut u = second;
+        while u != apex {
+            let e = pred[u];
+            let residual = if direction[u] == UP { capacity[e] - flow[e] } else { flow[e] };
+            if residual <= delta {
+                delta = residual;
+                leaving = Some((u, false));
+            }
+            u = parent[u];
+        }
+        if delta.is_infinite() {
+            // A negative cycle without a blocking arc: artificial arcs are too expensive to be on it, so it consists of unlimited edges.
+            return Err(MinCostFlowError::Unbounded);
+        }
+
+        // Augment along the cycle.
+        if delta > 0. {
+            let signed = state[entering] * delta;
+            flow[entering] += signed;
+            let mut u = from[entering];
+            while u != apex {
+                flow[pred[u]] -= direction[u] * signed;
+                u = parent[u];
+            }
+            let mut u = to[entering];
+            while u != apex {
+                flow[pred[u]] += direction[u] * signed;
+                u = parent[u];
+            }
+        }
+
+        let Some((out, on_first_side)) = leaving else {
+            // The entering arc itself is blocking: it moves to its other bound.
+            state[entering] = -state[entering];
+            continue;
+        };
+
+        // Replace the leaving arc by the entering arc, reversing the tree path between them.
+        let (u_in, v_in) = if on_first_side { (first, second) } else { (second, first) };
+        let leaving_arc = pred[out];
+        let at_lower = (direction[out] == UP) == on_first_side;
+        state[leaving_arc] = if at_lower { LOWER } else { UPPER };
+        flow[leaving_arc] = if at_lower { 0. } else { capacity[leaving_arc] };
+        state[entering] = TREE;
+
+        let mut path = vec![u_in];
+        while *path.last().unwrap() != out {
+            path.push(parent[*path.last().unwrap()]);
+        }
+        let old_parent = parent[out];
+        children[old_parent].retain(|&c| c != out);
+        for i in (0..path.len()).rev() {
+            let node = path[i];
+            let (new_parent, arc) = if i == 0 { (v_in, entering) } else { (path[i - 1], pred[path[i - 1]]) };
+            if i + 1 < path.len() {
+                children[path[i + 1]].retain(|&c| c != node);
+            }
+            parent[node] = new_parent;
+            pred[node] = arc;
+            direction[node] = if from[arc] == node { UP } else { DOWN };
+        }
+        for i in 0..path.len() {
+            let new_parent = parent[path[i]];
+            children[new_parent].push(path[i]);
+        }
+
+        // Update depths and potentials in the moved subtree.
+        let mut stack = vec![u_in];
+        while let Some(node) = stack.pop() {
+            let p = parent[node];
+            depth[node] = depth[p] + 1;
+            potential[node] = if direction[node] == UP {
+                potential[p] - cost[pred[node]]
+            } else {
+                potential[p] + cost[pred[node]]
+            };
+            stack.extend(children[node].iter().copied());
+        }
+    }
+
+    let total: f64 = supply.iter().filter(|&&amount| amount > 0.).sum();
+    let leftover: f64 = flow[m..].iter().sum();
+    if leftover > FLOW_TOLERANCE * total {
+        return Err(MinCostFlowError::Infeasible);
+    }
+    flow.truncate(m);
+    Ok(flow)
+}
+
+// Solves the minimum cost flow problem on everything reachable from the nodes with a supply or demand.
+fn min_cost_flow<T>(
+    supplies: &[(T, f64)],
+    neighbor_function: &impl Adjacency<T>,
+    capacity_function: &impl Fn(T, T) -> OrderedFloat<f64>,
+    cost_function: &impl Fn(T, T) -> OrderedFloat<f64>,
+    algorithm: MinCostFlowAlgorithm,
+) -> Result<MinCostFlow<T>, MinCostFlowError>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let total: f64 = supplies.iter().map(|&(_, amount)| amount.abs()).sum();
+    if supplies.iter().map(|&(_, amount)| amount).sum::<f64>().abs() > FLOW_TOLERANCE * total {
+        return Err(MinCostFlowError::Unbalanced);
+    }
+
+    let (nodes, ids, edges) = flow_edges(supplies.iter().map(|&(node, _)| node), neighbor_function);
+    let mut supply = vec![0.; nodes.len()];
+    for &(node, amount) in supplies {
+        supply[ids[&node]] += amount;
+    }
+    let arcs = edges
+        .iter()
+        .map(|&(u, v)| (ids[&u], ids[&v], capacity_function(u, v).0, cost_function(u, v).0))
+        .collect::<Vec<_>>();
+
+    let simplex = match algorithm {
+        MinCostFlowAlgorithm::Auto => arcs.len() > NETWORK_SIMPLEX_THRESHOLD,
+        MinCostFlowAlgorithm::SuccessiveShortestPaths => false,
+        MinCostFlowAlgorithm::NetworkSimplex => true,
+    };
+    let flow = if simplex {
+        network_simplex(&supply, &arcs)?
+    } else {
+        successive_shortest_paths(&supply, &arcs)?
+    };
+
+    let mut flows: HashMap<(T, T), OrderedFloat<f64>> = HashMap::new();
+    let mut cost = 0.;
+    for (&(u, v), (&amount, &(.., arc_cost))) in edges.iter().zip(flow.iter().zip(&arcs)) {
+        if amount > 0. {
+            *flows.entry((u, v)).or_default() += amount;
+            cost = amount.mul_add(arc_cost, cost);
+        }
+    }
+
+    Ok(MinCostFlow {
+        value: OrderedFloat(supply.iter().filter(|&&amount| amount > 0.).sum()),
+        cost: OrderedFloat(cost),
+        flows,
+    })
+}
+
+/// Finds a flow of minimum cost that routes the supplies of some nodes to the demands of others.
+///
+/// # Arguments
+/// * `supplies` - The supply (positive) or demand (negative) of nodes. Other nodes have neither. They must sum to zero.
+/// * `neighbor_function` - A function that returns the (outgoing) neighbors of a given node. Only nodes reachable from the nodes in `supplies` are considered.
+/// * `capacity_function` - A function that returns the capacity of the edge between two nodes. Capacities must be non-negative, and may be infinite for unlimited edges.
+/// * `cost_function` - A function that returns the cost per unit of flow over the edge between two nodes. Costs may be negative.
+/// * `algorithm` - The algorithm to use.
+///
+/// # Returns
+/// * `Result<MinCostFlow<T>, MinCostFlowError>` - The total supply, the cost of the flow and the flow over every edge, or an error if the supplies are unbalanced or cannot be routed, or if the cost is unbounded.
+///
+/// # Source
+/// Ahuja, R. K., Magnanti, T. L., & Orlin, J. B. (1993). Network Flows: Theory, Algorithms, and Applications. Chapters 9 and 11.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::{find_min_cost_flow, MinCostFlowAlgorithm, MinCostFlowError};
+/// use ordered_float::OrderedFloat;
+///
+/// // A transportation problem: two warehouses (0, 1) supply three shops (2, 3, 4).
+/// let costs = [[4., 6., 9.], [5., 3., 8.]];
+/// let neighbor_function = |node: usize| -> Vec<usize> { if node < 2 { vec![2, 3, 4] } else { vec![] } };
+/// let capacity_function = |_, _| OrderedFloat(100.);
+/// let cost_function = |a: usize, b: usize| OrderedFloat(costs[a][b - 2]);
+/// let supplies = [(0, 30.), (1, 40.), (2, -20.), (3, -30.), (4, -20.)];
+///
+/// for algorithm in [MinCostFlowAlgorithm::SuccessiveShortestPaths, MinCostFlowAlgorithm::NetworkSimplex] {
+///     let flow = find_min_cost_flow(&supplies, neighbor_function, capacity_function, cost_function, algorithm).unwrap();
+///     assert_eq!(flow.value, OrderedFloat(70.));
+///     assert_eq!(flow.cost, OrderedFloat(20. * 4. + 30. * 3. + 10. * 9. + 10. * 8.));
+///     assert_eq!(flow.flows[&(1, 3)], OrderedFloat(30.));
+/// }
+///
+/// // Shop 3 cannot receive more than 10 from each warehouse.
+/// let capacity_function = |_, _| OrderedFloat(10.);
+/// let flow = find_min_cost_flow(&supplies, neighbor_function, capacity_function, cost_function, MinCostFlowAlgorithm::default());
+/// assert_eq!(flow.unwrap_err(), MinCostFlowError::Infeasible);
+///
+/// let flow = find_min_cost_flow(&[(0, 1.)], neighbor_function, capacity_function, cost_function, MinCostFlowAlgorithm::default());
+/// assert_eq!(flow.unwrap_err(), MinCostFlowError::Unbalanced);
+///
+/// // Unlimited edges 0 -> 1 -> {2, 3} and 2 -> 1, where the edge 2 -> 1 pays 5 per unit: the cycle 1 -> 2 -> 1 can carry any amount.
+/// let neighbor_function = |node: usize| -> Vec<usize> {
+///     match node {
+///         0 => vec![1],
+///         1 => vec![2, 3],
+///         2 => vec![1],
+///         _ => vec![],
+///     }
+/// };
+/// let capacity_function = |_, _| OrderedFloat(f64::INFINITY);
+/// let cost_function = |a: usize, b: usize| OrderedFloat(if (a, b) == (2, 1) { -5. } else { 1. });
+/// for algorithm in [MinCostFlowAlgorithm::SuccessiveShortestPaths, MinCostFlowAlgorithm::NetworkSimplex] {
+///     let flow = find_min_cost_flow(&[(0, 1.), (3, -1.)], neighbor_function, capacity_function, cost_function, algorithm);
+///     assert_eq!(flow.unwrap_err(), MinCostFlowError::Unbounded);
+/// }
+///
+/// // With 2 -> 3 instead of 2 -> 1, there is no cycle, and the detour through 2 pays off.
+/// let neighbor_function = |node: usize| -> Vec<usize> { if node == 2 { vec![3] } else { neighbor_function(node) } };
+/// let cost_function = |a: usize, b: usize| OrderedFloat(if (a, b) == (1, 2) { -5. } else { 1. });
+/// for algorithm in [MinCostFlowAlgorithm::SuccessiveShortestPaths, MinCostFlowAlgorithm::NetworkSimplex] {
+///     let flow = find_min_cost_flow(&[(0, 1.), (3, -1.)], neighbor_function, capacity_function, cost_function, algorithm).unwrap();
+///     assert_eq!(flow.cost, OrderedFloat(-3.));
+///     assert_eq!(flow.flows[&(1, 2)], OrderedFloat(1.));
+/// }
+///
+/// // Edges 0 -> 1 and 1 -> 2, and 2 -> 1 with a negative cost: the cycle 1 -> 2 -> 1 pays off until 2 -> 1 is saturated.
+/// let edges = [((0, 1), 2., 1.), ((1, 2), 3., 1.), ((2, 1), 1., -4.)];
+/// let neighbor_function = |node: usize| -> Vec<usize> { edges.iter().filter(|((a, _), ..)| *a == node).map(|((_, b), ..)| *b).collect() };
+/// let capacity_function = |a: usize, b: usize| OrderedFloat(edges.iter().find(|(edge, ..)| *edge == (a, b)).unwrap().1);
+/// let cost_function = |a: usize, b: usize| OrderedFloat(edges.iter().find(|(edge, ..)| *edge == (a, b)).unwrap().2);
+/// for algorithm in [MinCostFlowAlgorithm::SuccessiveShortestPaths, MinCostFlowAlgorithm::NetworkSimplex] {
+///     let flow = find_min_cost_flow(&[(0, 2.), (2, -2.)], neighbor_function, capacity_function, cost_function, algorithm).unwrap();
+///     assert_eq!(flow.cost, OrderedFloat(2. + 3. - 4.));
+///     assert_eq!((flow.flows[&(1, 2)], flow.flows[&(2, 1)]), (OrderedFloat(3.), OrderedFloat(1.)));
+/// }
+/// ```
+///
+/// Both algorithms agree on random graphs with negative costs:
+/// ```
+/// use hutspot::graph::{find_min_cost_flow, MinCostFlowAlgorithm};
+/// use ordered_float::OrderedFloat;
+///
+/// let mut seed: u64 = 7;
+/// let mut random = move || {
+///     seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+///     seed >> 33
+/// };
+/// for _ in 0..200 {
+///     let n = 2 + random() as usize % 12;
+///     let edges = (0..n * n).map(|_| if random() % 3 == 0 { Some(((random() % 10) as f64, (random() % 21) as f64 - 5.)) } else { None }).collect::<Vec<_>>();
+///     let neighbor_function = |a: usize| -> Vec<usize> { (0..n).filter(|&b| edges[a * n + b].is_some()).collect() };
+///     let capacity_function = |a: usize, b: usize| OrderedFloat(edges[a * n + b].unwrap().0);
+///     let cost_function = |a: usize, b: usize| OrderedFloat(edges[a * n + b].unwrap().1);
+///     let amount = (random() % 10) as f64;
+///     let supplies = [(0, amount), (n - 1, -amount)];
+///
+///     let ssp = find_min_cost_flow(&supplies, neighbor_function, capacity_function, cost_function, MinCostFlowAlgorithm::SuccessiveShortestPaths);
+///     let simplex = find_min_cost_flow(&supplies, neighbor_function, capacity_function, cost_function, MinCostFlowAlgorithm::NetworkSimplex);
+///     match (ssp, simplex) {
+///         (Ok(a), Ok(b)) => assert_eq!(a.cost, b.cost),
+///         (a, b) => assert_eq!(a.unwrap_err(), b.unwrap_err()),
+///     }
+/// }
+/// ```
+pub fn find_min_cost_flow<T>(
+    supplies: &[(T, f64)],
+    neighbor_function: impl Fn(T) -> Vec<T>,
+    capacity_function: impl Fn(T, T) -> OrderedFloat<f64>,
+    cost_function: impl Fn(T, T) -> OrderedFloat<f64>,
+    algorithm: MinCostFlowAlgorithm,
+) -> Result<MinCostFlow<T>, MinCostFlowError>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    min_cost_flow(supplies, &neighbor_function, &capacity_function, &cost_function, algorithm)
+}
+
+/// Finds a maximum flow from `source` to `sink` of minimum cost. See `find_min_cost_flow`.
+///
+/// # Arguments
+/// * `source` - The source node.
+/// * `sink` - The sink node.
+/// * `neighbor_function` - A function that returns the (outgoing) neighbors of a given node.
+/// * `capacity_function` - A function that returns the capacity of the edge between two nodes. Capacities must be finite and non-negative.
+/// * `cost_function` - A function that returns the cost per unit of flow over the edge between two nodes. Costs may be negative.
+/// * `algorithm` - The algorithm to use.
+///
+/// # Returns
+/// * `Result<MinCostFlow<T>, MinCostFlowError>` - The value of the maximum flow, its cost and the flow over every edge.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::{find_min_cost_max_flow, MinCostFlowAlgorithm};
+/// use ordered_float::OrderedFloat;
+///
+/// // Two routes from 0 to 3: a cheap one through 1 and an expensive one through 2.
+/// let neighbor_function = |node: usize| -> Vec<usize> {
+///     match node {
+///         0 => vec![1, 2],
+///         1 | 2 => vec![3],
+///         _ => vec![],
+///     }
+/// };
+/// let capacity_function = |a: usize, b: usize| OrderedFloat(if a == 1 || b == 1 { 2. } else { 3. });
+/// let cost_function = |a: usize, b: usize| OrderedFloat(if a == 2 || b == 2 { 5. } else { 1. });
+///
+/// let flow = find_min_cost_max_flow(0, 3, neighbor_function, capacity_function, cost_function, MinCostFlowAlgorithm::default()).unwrap();
+/// assert_eq!(flow.value, OrderedFloat(5.));
+/// assert_eq!(flow.cost, OrderedFloat(2. * 2. + 3. * 10.));
+/// ```
+pub fn find_min_cost_max_flow<T>(
+    source: T,
+    sink: T,
+    neighbor_function: impl Fn(T) -> Vec<T>,
+    capacity_function: impl Fn(T, T) -> OrderedFloat<f64>,
+    cost_function: impl Fn(T, T) -> OrderedFloat<f64>,
+    algorithm: MinCostFlowAlgorithm,
+) -> Result<MinCostFlow<T>, MinCostFlowError>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    min_cost_max_flow(source, sink, neighbor_function, capacity_function, cost_function, algorithm)
+}
+
+fn min_cost_max_flow<T>(
+    source: T,
+    sink: T,
+    neighbor_function: impl Adjacency<T>,
+    capacity_function: impl Fn(T, T) -> OrderedFloat<f64>,
+    cost_function: impl Fn(T, T) -> OrderedFloat<f64>,
+    algorithm: MinCostFlowAlgorithm,
+) -> Result<MinCostFlow<T>, MinCostFlowError>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let value = max_flow(&[source], &[sink], &neighbor_function, &capacity_function, MaxFlowAlgorithm::default())
+        .value
+        .0;
+    min_cost_flow(
+        &[(source, value), (sink, -value)],
+        &neighbor_function,
+        &capacity_function,
+        &cost_function,
+        algorithm,
+    )
+}
+
+// Should do this for each connected component (degree of freedom!)
+/// Splits the nodes of an undirected graph into two sets, such that every edge connects a node of one set to a node of the other.
+///
+/// # Arguments
+/// * `nodes` - The nodes of the graph.
+/// * `neighbor_function` - A function that returns the neighbors of a given node. It must be symmetric.
+///
+/// # Returns
+/// * `Option<(HashSet<T>, HashSet<T>)>` - The two sets, or `None` if the graph is not bipartite.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::two_color;
+/// use std::collections::HashSet;
+///
+/// // The types of the closure are inferred, also when it calls methods on its argument.
+/// let nodes: [u32; 4] = [0, 1, 2, 3];
+/// let (a, b) = two_color(&nodes, |n| (n.saturating_sub(1)..=(n + 1).min(3)).filter(|&m| m != n).collect()).unwrap();
+/// let sets = [HashSet::from([0, 2]), HashSet::from([1, 3])];
+/// assert!([a.clone(), b.clone()] == sets || [b, a] == sets);
+///
+/// assert!(two_color(&nodes, |n| nodes.iter().copied().filter(|&m| m != n).collect()).is_none());
+/// ```
+pub fn two_color<T>(nodes: &[T], neighbor_function: impl Fn(T) -> Vec<T>) -> Option<(HashSet<T>, HashSet<T>)>
+where
+    T: Eq + Hash + Clone + Copy + Debug,
+{
+    bipartition(nodes, neighbor_function)
+}
+
+fn bipartition<T>(nodes: &[T], neighbor_function: impl Adjacency<T>) -> Option<(HashSet<T>, HashSet<T>)>
+where
+    T: Eq + Hash + Clone + Copy + Debug,
+{
+    let mut pool = nodes.to_vec();
+    let mut color1 = HashSet::new();
+    let mut color2 = HashSet::new();
+
+    while let Some(s) = pool.pop() {
+        let mut queue = vec![s];
+
+        while let Some(node) = queue.pop() {
+            pool.retain(|x| x != &node);
+            if color1.contains(&node) || color2.contains(&node) {
+                continue;
+            }
+
+            let neighbors = neighbor_function.neighbors(node).collect::<Vec<_>>();
+
+            if neighbors.iter().any(|x| color1.contains(x)) {
+                if neighbors.iter().any(|x| color2.contains(x)) {
+                    return None;
+                }
+                color2.insert(node);
+            } else if neighbors.iter().any(|x| color2.contains(x)) {
+                if neighbors.iter().any(|x| color1.contains(x)) {
+                    return None;
+                }
+
+                color1.insert(node);
+            } else {
+                // Degree of freedom.
+                color2.insert(node);
+            }
+
+            queue.extend(neighbors);
+        }
+    }
+    Some((color1, color2))
+}
+
+/// The order in which `find_greedy_coloring` colors the nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColoringOrder {
+    /// The order of the given nodes, followed by the nodes that are only reachable from them.
+    Given,
+    /// By decreasing degree (Welsh-Powell).
+    #[default]
+    LargestFirst,
+    /// The reverse of repeatedly removing a node of smallest degree (Matula-Beck). Uses at most one color more than the degeneracy of the graph, so planar graphs get at most six colors.
+    SmallestLast,
+}
+
+// Indexes the nodes reachable from `nodes` (in the given order first), with symmetric adjacency lists without duplicates or self-loops.
+fn undirected_adjacency<T>(nodes: &[T], neighbor_function: &impl Adjacency<T>) -> (Vec<T>, Vec<Vec<usize>>)
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let (reached, ids, edges) = flow_edges(nodes.iter().rev().copied(), neighbor_function);
+    let mut order = (0..reached.len()).collect::<Vec<_>>();
+    let given = nodes.iter().enumerate().map(|(i, node)| (ids[node], i)).collect::<HashMap<_, _>>();
+    order.sort_by_key(|i| given.get(i).copied().unwrap_or(usize::MAX));
+    let mut position = vec![0; reached.len()];
+    for (i, &node) in order.iter().enumerate() {
+        position[node] = i;
+    }
+
+    let mut adjacency = vec![HashSet::new(); reached.len()];
+    for (u, v) in edges {
+        let (u, v) = (position[ids[&u]], position[ids[&v]]);
+        adjacency[u].insert(v);
+        adjacency[v].insert(u);
+    }
+    (
+        order.into_iter().map(|i| reached[i]).collect(),
+        adjacency.into_iter().map(|neighbors| neighbors.into_iter().collect()).collect(),
+    )
+}
+
+// Gives every node (in the given order) the smallest color that none of its colored neighbors has.
+fn greedy_coloring(order: &[usize], adjacency: &[Vec<usize>]) -> Vec<usize> {
+    let mut colors = vec![NONE; adjacency.len()];
+    let mut used = vec![NONE; adjacency.len() + 1];
+    for &node in order {
+        for &neighbor in &adjacency[node] {
+            if colors[neighbor] != NONE {
+                used[colors[neighbor]] = node;
+            }
+        }
+        colors[node] = (0..=adjacency[node].len()).find(|&color| used[color] != node).unwrap();
+    }
+    colors
+}
+
+/// Colors the nodes of an undirected graph so that neighbors get different colors, by giving every node (in the given order) the smallest color that none of its neighbors has.
+///
+/// This is fast but does not minimize the number of colors. See `find_dsatur_coloring` for better colorings, and `find_k_coloring` for exact ones.
+///
+/// # Arguments
+/// * `nodes` - The nodes of the graph. Nodes that are only reachable from them are colored as well.
+/// * `neighbor_function` - A function that returns the neighbors of a given node. Edges are taken as undirected, and self-loops are ignored.
+/// * `order` - The order in which the nodes are colored.
+///
+/// # Returns
+/// * `HashMap<T, usize>` - The color of every node. The colors are `0..k`, so `color as f32 / k as f32` can be used with `color::map`.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::{find_greedy_coloring, ColoringOrder};
+///
+/// // A 4x4 grid needs two colors, and smallest-last finds them.
+/// let neighbor_function = |(x, y): (i32, i32)| -> Vec<(i32, i32)> {
+///     [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)].into_iter().filter(|&(x, y)| (0..4).contains(&x) && (0..4).contains(&y)).collect()
+/// };
+/// for order in [ColoringOrder::Given, ColoringOrder::LargestFirst, ColoringOrder::SmallestLast] {
+///     let colors = find_greedy_coloring(&[(0, 0)], neighbor_function, order);
+///     assert_eq!(colors.len(), 16);
+///     assert!(colors.iter().all(|(&node, color)| neighbor_function(node).iter().all(|neighbor| colors[neighbor] != *color)));
+/// }
+/// let colors = find_greedy_coloring(&[(0, 0)], neighbor_function, ColoringOrder::SmallestLast);
+/// assert_eq!(colors.values().max(), Some(&1));
+/// ```
+pub fn find_greedy_coloring<T>(nodes: &[T], neighbor_function: impl Fn(T) -> Vec<T>, order: ColoringOrder) -> HashMap<T, usize>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    ordered_greedy_coloring(nodes, neighbor_function, order)
+}
+
+fn ordered_greedy_coloring<T>(nodes: &[T], neighbor_function: impl Adjacency<T>, order: ColoringOrder) -> HashMap<T, usize>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let (nodes, adjacency) = undirected_adjacency(nodes, &neighbor_function);
+    let n = nodes.len();
+    let sequence = match order {
+        ColoringOrder::Given => (0..n).collect(),
+        ColoringOrder::LargestFirst => {
+            let mut sequence = (0..n).collect::<Vec<_>>();
+            sequence.sort_by_key(|&node| Reverse(adjacency[node].len()));
+            sequence
+        }
+        ColoringOrder::SmallestLast => {
+            // Buckets of nodes by their degree among the remaining nodes, with lazily removed stale entries.
+            let mut degree = adjacency.iter().map(Vec::len).collect::<Vec<_>>();
+            let mut buckets = vec![vec![]; n];
+            for node in 0..n {
+                buckets[degree[node]].push(node);
+            }
+            let mut removed = vec![false; n];
+            let mut sequence = Vec::with_capacity(n);
+            let mut smallest = 0;
+            while sequence.len() < n {
+                let Some(node) = buckets[smallest].pop() else {
+                    smallest += 1;
+                    continue;
+                };
+                if removed[node] || degree[node] != smallest {
+                    continue;
+                }
+                removed[node] = true;
+                sequence.push(node);
+                for &neighbor in &adjacency[node] {
+                    if !removed[neighbor] {
+                        degree[neighbor] -= 1;
+                        buckets[degree[neighbor]].push(neighbor);
+                        smallest = smallest.min(degree[neighbor]);
+                    }
+                }
+            }
+            sequence.reverse();
+            sequence
+        }
+    };
+    nodes.into_iter().zip(greedy_coloring(&sequence, &adjacency)).collect()
+}
+
+/// Colors the nodes of an undirected graph so that neighbors get different colors, using the degree of saturation heuristic of Brélaz.
+///
+/// The next node to color is always the one whose neighbors already have the most distinct colors (ties broken by degree), which usually needs fewer colors than `find_greedy_coloring`.
+/// It is exact for bipartite graphs, cycles and wheels.
+///
+/// # Arguments
+/// * `nodes` - The nodes of the graph. Nodes that are only reachable from them are colored as well.
+/// * `neighbor_function` - A function that returns the neighbors of a given node. Edges are taken as undirected, and self-loops are ignored.
+///
+/// # Returns
+/// * `HashMap<T, usize>` - The color of every node. The colors are `0..k`, so `color as f32 / k as f32` can be used with `color::map`.
+///
+/// # Source
+/// Brélaz, D. (1979). New methods to color the vertices of a graph. Communications of the ACM.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::find_dsatur_coloring;
+///
+/// // A wheel: a cycle of five nodes around a hub needs four colors.
+/// let neighbor_function = |node: u32| -> Vec<u32> {
+///     match node {
+///         0 => vec![1, 2, 3, 4, 5],
+///         _ => vec![0, (node % 5) + 1, ((node + 3) % 5) + 1],
+///     }
+/// };
+/// let colors = find_dsatur_coloring(&[0], neighbor_function);
+/// assert_eq!(colors.len(), 6);
+/// assert_eq!(colors.values().max(), Some(&3));
+/// assert!(colors.iter().all(|(&node, color)| neighbor_function(node).iter().all(|neighbor| colors[neighbor] != *color)));
+/// ```
+pub fn find_dsatur_coloring<T>(nodes: &[T], neighbor_function: impl Fn(T) -> Vec<T>) -> HashMap<T, usize>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    dsatur_coloring(nodes, neighbor_function)
+}
+
+fn dsatur_coloring<T>(nodes: &[T], neighbor_function: impl Adjacency<T>) -> HashMap<T, usize>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let (nodes, adjacency) = undirected_adjacency(nodes, &neighbor_function);
+    let n = nodes.len();
+    let mut colors = vec![NONE; n];
+    // The distinct colors of the colored neighbors of every node.
+    let mut saturation = vec![HashSet::new(); n];
+    let mut queue = (0..n).map(|node| (0, adjacency[node].len(), Reverse(node))).collect::<BinaryHeap<_>>();
+    while let Some((degree, _, Reverse(node))) = queue.pop() {
+        if colors[node] != NONE || degree != saturation[node].len() {
+            continue;
+        }
+        colors[node] = (0..=saturation[node].len()).find(|color| !saturation[node].contains(color)).unwrap();
+        for &neighbor in &adjacency[node] {
+            if colors[neighbor] == NONE && saturation[neighbor].insert(colors[node]) {
+                queue.push((saturation[neighbor].len(), adjacency[neighbor].len(), Reverse(neighbor)));
+            }
+        }
+    }
+    nodes.into_iter().zip(colors).collect()
+}
+
+// Backtracking state of `find_k_coloring`: the color of every node, and for every node how many of its neighbors have each color.
+struct KColoring<'a> {
+    adjacency: &'a [Vec<usize>],
+    k: usize,
+    colors: Vec<usize>,
+    conflicts: Vec<Vec<usize>>,
+    saturation: Vec<usize>,
+}
+
+impl KColoring<'_> {
+    fn set(&mut self, node: usize, color: usize, add: bool) {
+        self.colors[node] = if add { color } else { NONE };
+        let adjacency = self.adjacency;
+        for &neighbor in &adjacency[node] {
+            let count = &mut self.conflicts[neighbor][color];
+            if add {
+                *count += 1;
+                if *count == 1 {
+                    self.saturation[neighbor] += 1;
+                }
+            } else {
+                *count -= 1;
+                if *count == 0 {
+                    self.saturation[neighbor] -= 1;
+                }
+            }
+        }
+    }
+
+    // Colors the remaining nodes, most constrained first, where `used` colors are in use so far. New colors are only tried in order, to skip permutations of the same coloring.
+    fn solve(&mut self, used: usize) -> bool {
+        let Some(node) = (0..self.colors.len())
+            .filter(|&node| self.colors[node] == NONE)
+            .max_by_key(|&node| (self.saturation[node], self.adjacency[node].len()))
+        else {
+            return true;
+        };
+        for color in 0..self.k.min(used + 1) {
+            if self.conflicts[node][color] == 0 {
+                self.set(node, color, true);
+                if self.solve(used.max(color + 1)) {
+                    return true;
+                }
+                self.set(node, color, false);
+            }
+        }
+        false
+    }
+}
+
+/// Colors the nodes of an undirected graph with at most `k` colors so that neighbors get different colors, or finds that this is impossible, using backtracking.
+///
+/// This is exact, but takes exponential time in the worst case, so it is meant for small graphs (or for confirming the colorings of `find_dsatur_coloring`).
+///
+/// # Arguments
+/// * `nodes` - The nodes of the graph. Nodes that are only reachable from them are colored as well.
+/// * `neighbor_function` - A function that returns the neighbors of a given node. Edges are taken as undirected, and self-loops are ignored.
+/// * `k` - The number of colors.
+///
+/// # Returns
+/// * `Option<HashMap<T, usize>>` - The color (in `0..k`) of every node, or `None` if the graph cannot be colored with `k` colors.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::find_k_coloring;
+///
+/// // The Petersen graph: an outer 5-cycle, an inner pentagram, and spokes between them. It needs three colors.
+/// let neighbor_function = |node: u32| -> Vec<u32> {
+///     if node < 5 {
+///         vec![(node + 1) % 5, (node + 4) % 5, node + 5]
+///     } else {
+///         vec![5 + (node + 2) % 5, 5 + (node + 3) % 5, node - 5]
+///     }
+/// };
+/// assert!(find_k_coloring(&[0], neighbor_function, 2).is_none());
+/// let colors = find_k_coloring(&[0], neighbor_function, 3).unwrap();
+/// assert_eq!(colors.len(), 10);
+/// assert!(colors.values().all(|&color| color < 3));
+/// assert!(colors.iter().all(|(&node, color)| neighbor_function(node).iter().all(|neighbor| colors[neighbor] != *color)));
+/// ```
+pub fn find_k_coloring<T>(nodes: &[T], neighbor_function: impl Fn(T) -> Vec<T>, k: usize) -> Option<HashMap<T, usize>>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    k_coloring(nodes, neighbor_function, k)
+}
+
+fn k_coloring<T>(nodes: &[T], neighbor_function: impl Adjacency<T>, k: usize) -> Option<HashMap<T, usize>>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let (nodes, adjacency) = undirected_adjacency(nodes, &neighbor_function);
+    let n = nodes.len();
+    let mut state = KColoring {
+        adjacency: &adjacency,
+        k,
+        colors: vec![NONE; n],
+        conflicts: vec![vec![0; k]; n],
+        saturation: vec![0; n],
+    };
+    state.solve(0).then(|| nodes.into_iter().zip(state.colors).collect())
+}
+
+/// A maximum matching in a bipartite graph, and a minimum vertex cover. See `find_bipartite_matching`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BipartiteMatching<T: Eq + Hash> {
+    /// The matched pairs, as (left node, right node).
+    pub pairs: Vec<(T, T)>,
+    /// A minimum vertex cover: every edge has an endpoint in it, and it has as many nodes as there are pairs (König's theorem).
+    pub vertex_cover: HashSet<T>,
+}
+
+// Indexes the left nodes and their neighbors (the right nodes), ignoring edges between left nodes.
+fn bipartite_adjacency<'a, T>(left: impl IntoIterator<Item = &'a T>, neighbor_function: &impl Adjacency<T>) -> (Vec<T>, Vec<T>, Vec<Vec<usize>>)
+where
+    T: Eq + Hash + Clone + Copy + 'a,
+{
+    let left = left.into_iter().copied().collect::<Vec<_>>();
+    let left_set = left.iter().copied().collect::<HashSet<_>>();
+    let mut right = vec![];
+    let mut right_ids: HashMap<T, usize> = HashMap::new();
+    let adjacency = left
+        .iter()
+        .map(|&node| {
+            neighbor_function
+                .neighbors(node)
+                .filter(|neighbor| !left_set.contains(neighbor))
+                .map(|neighbor| {
+                    *right_ids.entry(neighbor).or_insert_with(|| {
+                        right.push(neighbor);
+                        right.len() - 1
+                    })
+                })
+                .collect()
+        })
+        .collect();
+    (left, right, adjacency)
+}
+
+/// Finds a maximum (cardinality) matching in a bipartite graph using the Hopcroft-Karp algorithm, and a minimum vertex cover.
+///
+/// # Arguments
+/// * `left` - The nodes on one side of the graph, for example the first set returned by `two_color`. The other side consists of their neighbors.
+/// * `neighbor_function` - A function that returns the neighbors of a given node. Edges between two left nodes are ignored.
+///
+/// # Returns
+/// * `BipartiteMatching<T>` - The matched pairs and a minimum vertex cover.
+///
+/// # Source
+/// Hopcroft, J. E., & Karp, R. M. (1973). An n^5/2 algorithm for maximum matchings in bipartite graphs. SIAM Journal on Computing.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::{find_bipartite_matching, two_color};
+///
+/// // A path 1-2-3-4-5-6 with an extra edge 1-4.
+/// let neighbor_function = |node: u32| -> Vec<u32> {
+///     match node {
+///         1 => vec![2, 4],
+///         4 => vec![3, 5, 1],
+///         6 => vec![5],
+///         _ => vec![node - 1, node + 1],
+///     }
+/// };
+/// let (left, right) = two_color(&[1, 2, 3, 4, 5, 6], neighbor_function).unwrap();
+///
+/// let matching = find_bipartite_matching(&left, neighbor_function);
+/// assert_eq!(matching.pairs.len(), 3);
+/// for &(a, b) in &matching.pairs {
+///     assert!(left.contains(&a) && right.contains(&b) && neighbor_function(a).contains(&b));
+/// }
+/// assert_eq!(matching.vertex_cover.len(), 3);
+/// for a in 1..=6 {
+///     for b in neighbor_function(a) {
+///         assert!(matching.vertex_cover.contains(&a) || matching.vertex_cover.contains(&b));
+///     }
+/// }
+///
+/// // An explicit partition works as well.
+/// let matching = find_bipartite_matching(&[1, 3, 5], neighbor_function);
+/// assert_eq!(matching.pairs.len(), 3);
+/// ```
+pub fn find_bipartite_matching<'a, T>(left: impl IntoIterator<Item = &'a T>, neighbor_function: impl Fn(T) -> Vec<T>) -> BipartiteMatching<T>
+where
+    T: Eq + Hash + Clone + Copy + 'a,
+{
+    bipartite_matching(left, neighbor_function)
+}
+
+fn bipartite_matching<'a, T>(left: impl IntoIterator<Item = &'a T>, neighbor_function: impl Adjacency<T>) -> BipartiteMatching<T>
+where
+    T: Eq + Hash + Clone + Copy + 'a,
+{
+    let (left, right, adjacency) = bipartite_adjacency(left, &neighbor_function);
+    let mut mate_left = vec![NONE; left.len()];
+    let mut mate_right = vec![NONE; right.len()];
+    let mut distance = vec![NONE; left.len()];
+
+    loop {
+        // Layer the graph by alternating paths from the free left nodes.
+        let mut queue = std::collections::VecDeque::new();
+        for u in 0..left.len() {
+            distance[u] = if mate_left[u] == NONE { 0 } else { NONE };
+            if mate_left[u] == NONE {
+                queue.push_back(u);
+            }
+        }
+        let mut found = false;
+        while let Some(u) = queue.pop_front() {
+            for &v in &adjacency[u] {
+                let w = mate_right[v];
+                if w == NONE {
+                    found = true;
+                } else if distance[w] == NONE {
+                    distance[w] = distance[u] + 1;
+                    queue.push_back(w);
+                }
+            }
+        }
+        if !found {
+            break;
+        }
+
+        // Find a maximal set of vertex-disjoint shortest augmenting paths, with an iterative depth-first search.
+        let mut current = vec![0; left.len()];
+        for start in 0..left.len() {
+            if mate_left[start] != NONE {
+                continue;
+            }
+            let mut stack = vec![start];
+            while let Some(&u) = stack.last() {
+                if current[u] == adjacency[u].len() {
+                    distance[u] = NONE;
+                    stack.pop();
+                    continue;
+                }
+                let v = adjacency[u][current[u]];
+                current[u] += 1;
+                let w = mate_right[v];
+                if w == NONE {
+                    for &u in &stack {
+                        let v = adjacency[u][current[u] - 1];
+                        mate_left[u] = v;
+                        mate_right[v] = u;
+                    }
+                    break;
+                } else if distance[w] == distance[u] + 1 {
+                    stack.push(w);
+                }
+            }
+        }
+    }
+
+    // König's theorem: with Z the nodes reachable from free left nodes by alternating paths, (left \ Z) + (right & Z) is a minimum vertex cover.
+    let mut reached_left = vec![false; left.len()];
+    let mut reached_right = vec![false; right.len()];
+    let mut stack = (0..left.len()).filter(|&u| mate_left[u] == NONE).collect::<Vec<_>>();
+    while let Some(u) = stack.pop() {
+        if std::mem::replace(&mut reached_left[u], true) {
+            continue;
+        }
+        for &v in &adjacency[u] {
+            if !reached_right[v] && mate_left[u] != v {
+                reached_right[v] = true;
+                if mate_right[v] != NONE {
+                    stack.push(mate_right[v]);
+                }
+            }
+        }
+    }
+    let vertex_cover = (0..left.len())
+        .filter(|&u| !reached_left[u])
+        .map(|u| left[u])
+        .chain((0..right.len()).filter(|&v| reached_right[v]).map(|v| right[v]))
+        .collect();
+
+    BipartiteMatching {
+        pairs: (0..left.len())
+            .filter(|&u| mate_left[u] != NONE)
+            .map(|u| (left[u], right[mate_left[u]]))
+            .collect(),
+        vertex_cover,
+    }
+}
+
+/// Finds a minimum cost assignment in a bipartite graph, using the shortest augmenting path (Hungarian) algorithm of Jonker and Volgenant.
+///
+/// The assignment is a matching of maximum cardinality, with minimum total cost among those.
+///
+/// # Arguments
+/// * `left` - The nodes on one side of the graph, for example the first set returned by `two_color`. The other side consists of their neighbors.
+/// * `neighbor_function` - A function that returns the neighbors of a given node. Edges between two left nodes are ignored. For a complete assignment problem, return all right nodes.
+/// * `cost_function` - A function that returns the cost of assigning a left node to a right node. Costs may be negative.
+///
+/// # Returns
+/// * `(Vec<(T, T)>, OrderedFloat<f64>)` - The assigned pairs, as (left node, right node), and their total cost.
+///
+/// # Source
+/// Jonker, R., & Volgenant, A. (1987). A shortest augmenting path algorithm for dense and sparse linear assignment problems. Computing.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::find_assignment;
+/// use ordered_float::OrderedFloat;
+///
+/// // Assign three workers (0, 1, 2) to four jobs (10, 11, 12, 13).
+/// let costs = [[9., 2., 7., 8.], [6., 4., 3., 7.], [5., 8., 1., 8.]];
+/// let neighbor_function = |_| vec![10, 11, 12, 13];
+/// let cost_function = |worker: usize, job: usize| OrderedFloat(costs[worker][job - 10]);
+///
+/// let (pairs, cost) = find_assignment(&[0, 1, 2], neighbor_function, cost_function);
+/// assert_eq!(cost, OrderedFloat(2. + 6. + 1.));
+/// assert!(pairs.contains(&(1, 10)));
+///
+/// // Cardinality comes first: assigning worker 0 to job 10 alone would be cheaper, but leaves worker 1 without a job.
+/// let neighbor_function = |worker: usize| if worker == 0 { vec![10, 11] } else { vec![10] };
+/// let cost_function = |worker: usize, job: usize| OrderedFloat(match (worker, job) {
+///     (0, 10) => -5.,
+///     (0, _) => 3.,
+///     _ => 0.,
+/// });
+/// let (pairs, cost) = find_assignment(&[0, 1], neighbor_function, cost_function);
+/// assert_eq!((pairs.len(), cost), (2, OrderedFloat(3.)));
+///
+/// // More workers than jobs: the cheapest worker gets the job.
+/// let (pairs, cost) = find_assignment(&[0, 1, 2], |_| vec![10], |worker: usize, _| OrderedFloat([4., 2., 7.][worker]));
+/// assert_eq!((pairs, cost), (vec![(1, 10)], OrderedFloat(2.)));
+/// ```
+///
+/// The cost is optimal on random instances, compared against all permutations:
+/// ```
+/// use hutspot::graph::find_assignment;
+/// use itertools::Itertools;
+/// use ordered_float::OrderedFloat;
+///
+/// let mut seed: u64 = 3;
+/// let mut random = move || {
+///     seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+///     seed >> 33
+/// };
+/// for _ in 0..100 {
+///     let n = 1 + random() as usize % 6;
+///     let costs = (0..n * n).map(|_| (random() % 20) as f64 - 5.).collect::<Vec<_>>();
+///     let (pairs, cost) = find_assignment(&(0..n).collect::<Vec<_>>(), |_| (n..2 * n).collect(), |a, b| OrderedFloat(costs[a * n + b - n]));
+///     assert_eq!(pairs.len(), n);
+///
+///     let best = (0..n).permutations(n).map(|p| (0..n).map(|a| costs[a * n + p[a]]).sum::<f64>()).fold(f64::INFINITY, f64::min);
+///     assert_eq!(cost, OrderedFloat(best));
+/// }
+/// ```
+#[allow(clippy::many_single_char_names)]
+pub fn find_assignment<'a, T>(
+    left: impl IntoIterator<Item = &'a T>,
+    neighbor_function: impl Fn(T) -> Vec<T>,
+    cost_function: impl Fn(T, T) -> OrderedFloat<f64>,
+) -> (Vec<(T, T)>, OrderedFloat<f64>)
+where
+    T: Eq + Hash + Clone + Copy + 'a,
+{
+    assignment(left, neighbor_function, cost_function)
+}
+
+#[allow(clippy::many_single_char_names)]
+fn assignment<'a, T>(
+    left: impl IntoIterator<Item = &'a T>,
+    neighbor_function: impl Adjacency<T>,
+    cost_function: impl Fn(T, T) -> OrderedFloat<f64>,
+) -> (Vec<(T, T)>, OrderedFloat<f64>)
+where
+    T: Eq + Hash + Clone + Copy + 'a,
+{
+    let (left, right, adjacency) = bipartite_adjacency(left, &neighbor_function);
+    let mut costs = vec![vec![None; right.len()]; left.len()];
+    for (u, neighbors) in adjacency.iter().enumerate() {
+        for &v in neighbors {
+            costs[u][v] = Some(cost_function(left[u], right[v]).0);
+        }
+    }
+    // Missing edges get a cost that outweighs any difference in cost between two assignments, so that cardinality comes first.
+    let missing = costs.iter().flatten().flatten().map(|cost| cost.abs()).sum::<f64>().mul_add(2., 1.);
+    // The algorithm assigns every row, so rows are the smaller side.
+    let transposed = left.len() > right.len();
+    let (n, m) = if transposed {
+        (right.len(), left.len())
+    } else {
+        (left.len(), right.len())
+    };
+    let cost = |i: usize, j: usize| if transposed { costs[j][i] } else { costs[i][j] }.unwrap_or(missing);
+
+    // Potentials of rows (u) and columns (v), with column m as a virtual column to start augmenting paths from.
+    let mut u = vec![0.; n];
+    let mut v = vec![0.; m + 1];
+    let mut row_of = vec![NONE; m + 1];
+    let mut way = vec![m; m + 1];
+    for i in 0..n {
+        row_of[m] = i;
+        let mut j0 = m;
+        let mut minimum = vec![f64::INFINITY; m + 1];
+        let mut used = vec![false; m + 1];
+        while row_of[j0] != NONE {
+            used[j0] = true;
+            let i0 = row_of[j0];
+            let mut delta = f64::INFINITY;
+            let mut j1 = m;
+            for j in (0..m).filter(|&j| !used[j]) {
+                let reduced = cost(i0, j) - u[i0] - v[j];
+                if reduced < minimum[j] {
+                    minimum[j] = reduced;
+                    way[j] = j0;
+                }
+                if minimum[j] < delta {
+                    delta = minimum[j];
+                    j1 = j;
+                }
+            }
+            for j in 0..=m {
+                if used[j] {
+                    u[row_of[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    minimum[j] -= delta;
+                }
+            }
+            j0 = j1;
+        }
+        // Augment along the alternating path back to the virtual column.
+        while j0 != m {
+            let j1 = way[j0];
+            row_of[j0] = row_of[j1];
+            j0 = j1;
+        }
+    }
+
+    let pairs = (0..m)
+        .filter(|&j| row_of[j] != NONE && (if transposed { costs[j][row_of[j]] } else { costs[row_of[j]][j] }).is_some())
+        .map(|j| if transposed { (j, row_of[j]) } else { (row_of[j], j) })
+        .collect::<Vec<_>>();
+    let total = pairs.iter().map(|&(a, b)| costs[a][b].unwrap_or_default()).sum();
+    (pairs.into_iter().map(|(a, b)| (left[a], right[b])).collect(), OrderedFloat(total))
+}
+
+// Maximum weight matching on a general graph with the primal-dual blossom algorithm, in O(n^3).
+// Edge endpoints are numbered `2k` and `2k + 1` for edge `k`. Blossoms get the ids `n..2n`, vertices are trivial blossoms.
+struct Blossoms<'a> {
+    n: usize,
+    edges: &'a [(usize, usize, f64)],
+    endpoint: Vec<usize>,
+    neighbor_endpoints: Vec<Vec<usize>>,
+    // The remote endpoint of the matched edge of every vertex.
+    mate: Vec<usize>,
+    // 0 for unlabeled, 1 for S, 2 for T, 5 temporarily during `scan_blossom`.
+    label: Vec<u8>,
+    // The remote endpoint of the edge through which a vertex or blossom got its label.
+    label_end: Vec<usize>,
+    in_blossom: Vec<usize>,
+    parent: Vec<usize>,
+    children: Vec<Vec<usize>>,
+    base: Vec<usize>,
+    // For every child of a blossom, the endpoint of the edge to the next child.
+    child_endpoints: Vec<Vec<usize>>,
+    // The least-slack edge to a different S-blossom (for S-blossoms) or from an S-blossom (for free vertices).
+    best_edge: Vec<usize>,
+    blossom_best_edges: Vec<Option<Vec<usize>>>,
+    unused: Vec<usize>,
+    dual: Vec<f64>,
+    allowed: Vec<bool>,
+    queue: Vec<usize>,
+}
+
+#[allow(clippy::many_single_char_names)]
+impl<'a> Blossoms<'a> {
+    fn new(n: usize, edges: &'a [(usize, usize, f64)]) -> Self {
+        let max_weight = edges.iter().map(|&(.., weight)| weight).fold(0., f64::max);
+        let mut neighbor_endpoints = vec![vec![]; n];
+        for (k, &(i, j, _)) in edges.iter().enumerate() {
+            neighbor_endpoints[i].push(2 * k + 1);
+            neighbor_endpoints[j].push(2 * k);
+        }
+        Self {
+            n,
+            edges,
+            endpoint: edges.iter().flat_map(|&(i, j, _)| [i, j]).collect(),
+            neighbor_endpoints,
+            mate: vec![NONE; n],
+            label: vec![0; 2 * n],
+            label_end: vec![NONE; 2 * n],
+            in_blossom: (0..n).collect(),
+            parent: vec![NONE; 2 * n],
+            children: vec![vec![]; 2 * n],
+            base: (0..n).chain(std::iter::repeat_n(NONE, n)).collect(),
+            child_endpoints: vec![vec![]; 2 * n],
+            best_edge: vec![NONE; 2 * n],
+            blossom_best_edges: vec![None; 2 * n],
+            unused: (n..2 * n).collect(),
+            dual: std::iter::repeat_n(max_weight, n).chain(std::iter::repeat_n(0., n)).collect(),
+            allowed: vec![false; edges.len()],
+            queue: vec![],
+        }
+    }
+
+    fn slack(&self, k: usize) -> f64 {
+        let (i, j, weight) = self.edges[k];
+        2.0f64.mul_add(-weight, self.dual[i] + self.dual[j])
+    }
+
+    fn leaves(&self, b: usize) -> Vec<usize> {
+        let mut leaves = vec![];
+        let mut stack = vec![b];
+        while let Some(b) = stack.pop() {
+            if b < self.n {
+                leaves.push(b);
+            } else {
+                stack.extend(self.children[b].iter().rev());
+            }
+        }
+        leaves
+    }
+
+    fn assign_label(&mut self, w: usize, t: u8, p: usize) {
+        let b = self.in_blossom[w];
+        self.label[w] = t;
+        self.label[b] = t;
+        self.label_end[w] = p;
+        self.label_end[b] = p;
+        self.best_edge[w] = NONE;
+        self.best_edge[b] = NONE;
+        if t == 1 {
+            let leaves = self.leaves(b);
+            self.queue.extend(leaves);
+        } else {
+            // A T-blossom: its mate becomes an S-blossom.
+            let base = self.base[b];
+            let mate = self.mate[base];
+            self.assign_label(self.endpoint[mate], 1, mate ^ 1);
+        }
+    }
+
+    // Traces back from `v` and `w` to find either a new blossom (returning its base) or an augmenting path (returning `NONE`).
+    fn scan_blossom(&mut self, mut v: usize, mut w: usize) -> usize {
+        let mut path = vec![];
+        let mut base = NONE;
+        while v != NONE || w != NONE {
+            let mut b = self.in_blossom[v];
+            if self.label[b] & 4 != 0 {
+                base = self.base[b];
+                break;
+            }
+            path.push(b);
+            self.label[b] = 5;
+            if self.label_end[b] == NONE {
+                v = NONE;
+            } else {
+                v = self.endpoint[self.label_end[b]];
+                b = self.in_blossom[v];
+                v = self.endpoint[self.label_end[b]];
+            }
+            if w != NONE {
+                std::mem::swap(&mut v, &mut w);
+            }
+        }
+        for b in path {
+            self.label[b] = 1;
+        }
+        base
+    }
+
+    fn add_blossom(&mut self, base: usize, k: usize) {
+        let (mut v, mut w, _) = self.edges[k];
+        let bb = self.in_blossom[base];
+        let mut bv = self.in_blossom[v];
+        let mut bw = self.in_blossom[w];
+        let b = self.unused.pop().expect("there are at most n / 2 blossoms");
+        self.base[b] = base;
+        self.parent[b] = NONE;
+        self.parent[bb] = b;
+        let mut path = vec![];
+        let mut endpoints = vec![];
+        while bv != bb {
+            self.parent[bv] = b;
+            path.push(bv);
+            endpoints.push(self.label_end[bv]);
+            v = self.endpoint[self.label_end[bv]];
+            bv = self.in_blossom[v];
+        }
+        path.push(bb);
+        path.reverse();
+        endpoints.reverse();
+        endpoints.push(2 * k);
+        while bw != bb {
+            self.parent[bw] = b;
+            path.push(bw);
+            endpoints.push(self.label_end[bw] ^ 1);
+            w = self.endpoint[self.label_end[bw]];
+            bw = self.in_blossom[w];
+        }
+        self.label[b] = 1;
+        self.label_end[b] = self.label_end[bb];
+        self.dual[b] = 0.;
+        for v in self.leaves_of(&path) {
+            if self.label[self.in_blossom[v]] == 2 {
+                self.queue.push(v);
+            }
+            self.in_blossom[v] = b;
+        }
+
+        let mut best_edge_to = vec![NONE; 2 * self.n];
+        for &bv in &path {
+            let lists = self.blossom_best_edges[bv].take().map_or_else(
+                || {
+                    self.leaves(bv)
+                        .into_iter()
+                        .map(|v| self.neighbor_endpoints[v].iter().map(|p| p / 2).collect())
+                        .collect()
+                },
+                |list| vec![list],
+            );
+            for k in lists.into_iter().flatten() {
+                let (mut i, mut j, _) = self.edges[k];
+                if self.in_blossom[j] == b {
+                    std::mem::swap(&mut i, &mut j);
+                }
+                let bj = self.in_blossom[j];
+                if bj != b && self.label[bj] == 1 && (best_edge_to[bj] == NONE || self.slack(k) < self.slack(best_edge_to[bj])) {
+                    best_edge_to[bj] = k;
+                }
+            }
+            self.best_edge[bv] = NONE;
+        }
+        let best_edges = best_edge_to.into_iter().filter(|&k| k != NONE).collect::<Vec<_>>();
+        self.best_edge[b] = best_edges
+            .iter()
+            .copied()
+            .min_by(|&k, &l| self.slack(k).total_cmp(&self.slack(l)))
+            .unwrap_or(NONE);
+        self.blossom_best_edges[b] = Some(best_edges);
+        self.children[b] = path;
+        self.child_endpoints[b] = endpoints;
+    }
+
+    fn leaves_of(&self, blossoms: &[usize]) -> Vec<usize> {
+        blossoms.iter().flat_map(|&b| self.leaves(b)).collect()
+    }
+
+    // Children and their endpoints are indexed cyclically.
+    fn child(&self, b: usize, j: isize) -> usize {
+        self.children[b][j.rem_euclid(self.children[b].len().cast_signed()).cast_unsigned()]
+    }
+
+    fn child_endpoint(&self, b: usize, j: isize) -> usize {
+        self.child_endpoints[b][j.rem_euclid(self.child_endpoints[b].len().cast_signed()).cast_unsigned()]
+    }
+
+    // The position of child `t` in blossom `b`, the direction in which to walk to the base along an even-length path, and the offset of the endpoints along that direction.
+    fn walk(&self, b: usize, t: usize) -> (isize, isize, isize) {
+        let i = self.children[b].iter().position(|&c| c == t).expect("t is a child of b").cast_signed();
+        if i & 1 == 1 {
+            (i - self.children[b].len().cast_signed(), 1, 0)
+        } else {
+            (i, -1, 1)
+        }
+    }
+
+    fn expand_blossom(&mut self, b: usize, end_stage: bool) {
+        for s in self.children[b].clone() {
+            self.parent[s] = NONE;
+            if s < self.n {
+                self.in_blossom[s] = s;
+            } else if end_stage && self.dual[s] == 0. {
+                self.expand_blossom(s, end_stage);
+            } else {
+                for v in self.leaves(s) {
+                    self.in_blossom[v] = s;
+                }
+            }
+        }
+
+        if !end_stage && self.label[b] == 2 {
+            // Relabel the children on the even-length path from the entry child to the base.
+            let entry_child = self.in_blossom[self.endpoint[self.label_end[b] ^ 1]];
+            let (mut j, step, trick) = self.walk(b, entry_child);
+            let mut p = self.label_end[b];
+            while j != 0 {
+                self.label[self.endpoint[p ^ 1]] = 0;
+                let q = self.child_endpoint(b, j - trick);
+                self.label[self.endpoint[q ^ trick.cast_unsigned() ^ 1]] = 0;
+                self.assign_label(self.endpoint[p ^ 1], 2, p);
+                self.allowed[q / 2] = true;
+                j += step;
+                p = self.child_endpoint(b, j - trick) ^ trick.cast_unsigned();
+                self.allowed[p / 2] = true;
+                j += step;
+            }
+            let bv = self.child(b, j);
+            let v = self.endpoint[p ^ 1];
+            self.label[v] = 2;
+            self.label[bv] = 2;
+            self.label_end[v] = p;
+            self.label_end[bv] = p;
+            self.best_edge[bv] = NONE;
+            j += step;
+            // Children on the odd-length path keep a T label only if one of their vertices was reached.
+            while self.child(b, j) != entry_child {
+                let bv = self.child(b, j);
+                j += step;
+                if self.label[bv] == 1 {
+                    continue;
+                }
+                if let Some(v) = self.leaves(bv).into_iter().find(|&v| self.label[v] != 0) {
+                    self.label[v] = 0;
+                    self.label[self.endpoint[self.mate[self.base[bv]]]] = 0;
+                    self.assign_label(v, 2, self.label_end[v]);
+                }
+            }
+        }
+
+        self.label[b] = 0;
+        self.label_end[b] = NONE;
+        self.children[b].clear();
+        self.child_endpoints[b].clear();
+        self.base[b] = NONE;
+        self.blossom_best_edges[b] = None;
+        self.best_edge[b] = NONE;
+        self.unused.push(b);
+    }
+
+    // Swaps matched and unmatched edges on the path from vertex `v` to the base of blossom `b`, making `v` the new base.
+    fn augment_blossom(&mut self, b: usize, v: usize) {
+        let mut t = v;
+        while self.parent[t] != b {
+            t = self.parent[t];
+        }
+        if t >= self.n {
+            self.augment_blossom(t, v);
+        }
+        let (mut j, step, trick) = self.walk(b, t);
+        let i = self.children[b].iter().position(|&c| c == t).expect("t is a child of b");
+        while j != 0 {
+            j += step;
+            let t = self.child(b, j);
+            let p = self.child_endpoint(b, j - trick) ^ trick.cast_unsigned();
+            if t >= self.n {
+                self.augment_blossom(t, self.endpoint[p]);
+            }
+            j += step;
+            let t = self.child(b, j);
+            if t >= self.n {
+                self.augment_blossom(t, self.endpoint[p ^ 1]);
+            }
+            self.mate[self.endpoint[p]] = p ^ 1;
+            self.mate[self.endpoint[p ^ 1]] = p;
+        }
+        self.children[b].rotate_left(i);
+        self.child_endpoints[b].rotate_left(i);
+        self.base[b] = self.base[self.children[b][0]];
+    }
+
+    fn augment_matching(&mut self, k: usize) {
+        let (v, w, _) = self.edges[k];
+        for (mut s, mut p) in [(v, 2 * k + 1), (w, 2 * k)] {
+            loop {
+                let bs = self.in_blossom[s];
+                if bs >= self.n {
+                    self.augment_blossom(bs, s);
+                }
+                self.mate[s] = p;
+                if self.label_end[bs] == NONE {
+                    break;
+                }
+                let t = self.endpoint[self.label_end[bs]];
+                let bt = self.in_blossom[t];
+                s = self.endpoint[self.label_end[bt]];
+                let j = self.endpoint[self.label_end[bt] ^ 1];
+                if bt >= self.n {
+                    self.augment_blossom(bt, j);
+                }
+                self.mate[j] = self.label_end[bt];
+                p = self.label_end[bt] ^ 1;
+            }
+        }
+    }
+
+    // Runs one stage: grows alternating trees until the matching is augmented. Returns false if the matching is optimal.
+    #[allow(clippy::too_many_lines)]
+    fn stage(&mut self, max_cardinality: bool) -> bool {
+        let n = self.n;
+        self.label.fill(0);
+        self.best_edge.fill(NONE);
+        for best_edges in &mut self.blossom_best_edges[n..] {
+            *best_edges = None;
+        }
+        self.allowed.fill(false);
+        self.queue.clear();
+        for v in 0..n {
+            if self.mate[v] == NONE && self.label[self.in_blossom[v]] == 0 {
+                self.assign_label(v, 1, NONE);
+            }
+        }
+
+        loop {
+            while let Some(v) = self.queue.pop() {
+                for index in 0..self.neighbor_endpoints[v].len() {
+                    let p = self.neighbor_endpoints[v][index];
+                    let k = p / 2;
+                    let w = self.endpoint[p];
+                    if self.in_blossom[v] == self.in_blossom[w] {
+                        continue;
+                    }
+                    let mut slack = 0.;
+                    if !self.allowed[k] {
+                        slack = self.slack(k);
+                        if slack <= 0. {
+                            self.allowed[k] = true;
+                        }
+                    }
+                    let bw = self.in_blossom[w];
+                    if self.allowed[k] {
+                        if self.label[bw] == 0 {
+                            self.assign_label(w, 2, p ^ 1);
+                        } else if self.label[bw] == 1 {
+                            let base = self.scan_blossom(v, w);
+                            if base == NONE {
+                                self.augment_matching(k);
+                                return true;
+                            }
+                            self.add_blossom(base, k);
+                        } else if self.label[w] == 0 {
+                            // w is inside a T-blossom but was not yet reached.
+                            self.label[w] = 2;
+                            self.label_end[w] = p ^ 1;
+                        }
+                    } else if self.label[bw] == 1 {
+                        let b = self.in_blossom[v];
+                        if self.best_edge[b] == NONE || slack < self.slack(self.best_edge[b]) {
+                            self.best_edge[b] = k;
+                        }
+                    } else if self.label[w] == 0 && (self.best_edge[w] == NONE || slack < self.slack(self.best_edge[w])) {
+                        self.best_edge[w] = k;
+                    }
+                }
+            }
+
+            // No augmenting path with tight edges: update the dual variables.
+            // Type 1: a vertex dual reaches zero. 2: an edge from S to free gets tight. 3: an edge between S-blossoms gets tight. 4: a T-blossom dual reaches zero.
+            let mut delta = (!max_cardinality).then(|| (self.dual[..n].iter().copied().fold(f64::INFINITY, f64::min), 1, NONE));
+            for v in 0..n {
+                if self.label[self.in_blossom[v]] == 0 && self.best_edge[v] != NONE {
+                    let d = self.slack(self.best_edge[v]);
+                    if delta.is_none_or(|(delta, ..)| d < delta) {
+                        delta = Some((d, 2, self.best_edge[v]));
+                    }
+                }
+            }
+            for b in 0..2 * n {
+                if self.parent[b] == NONE && self.label[b] == 1 && self.best_edge[b] != NONE {
+                    let d = self.slack(self.best_edge[b]) / 2.;
+                    if delta.is_none_or(|(delta, ..)| d < delta) {
+                        delta = Some((d, 3, self.best_edge[b]));
+                    }
+                }
+            }
+            for b in n..2 * n {
+                if self.base[b] != NONE && self.parent[b] == NONE && self.label[b] == 2 && delta.is_none_or(|(delta, ..)| self.dual[b] < delta) {
+                    delta = Some((self.dual[b], 4, b));
+                }
+            }
+            // Only in maximum cardinality mode: no further improvement is possible, do a final dual update.
+            let (delta, kind, target) = delta.unwrap_or_else(|| (self.dual[..n].iter().copied().fold(f64::INFINITY, f64::min).max(0.), 1, NONE));
+
+            for v in 0..n {
+                match self.label[self.in_blossom[v]] {
+                    1 => self.dual[v] -= delta,
+                    2 => self.dual[v] += delta,
+                    _ => {}
+                }
+            }
+            for b in n..2 * n {
+                if self.base[b] != NONE && self.parent[b] == NONE {
+                    match self.label[b] {
+                        1 => self.dual[b] += delta,
+                        2 => self.dual[b] -= delta,
+                        _ => {}
+                    }
+                }
+            }
+
+            match kind {
+                1 => return false,
+                2 => {
+                    self.allowed[target] = true;
+                    let (i, j, _) = self.edges[target];
+                    self.queue.push(if self.label[self.in_blossom[i]] == 0 { j } else { i });
+                }
+                3 => {
+                    self.allowed[target] = true;
+                    self.queue.push(self.edges[target].0);
+                }
+                _ => self.expand_blossom(target, false),
+            }
+        }
+    }
+
+    // Returns the mate of every vertex, or `NONE` for unmatched vertices.
+    fn solve(mut self, max_cardinality: bool) -> Vec<usize> {
+        for _ in 0..self.n {
+            if !self.stage(max_cardinality) {
+                break;
+            }
+            // Expand all S-blossoms with a zero dual variable.
+            for b in self.n..2 * self.n {
+                if self.parent[b] == NONE && self.base[b] != NONE && self.label[b] == 1 && self.dual[b] == 0. {
+                    self.expand_blossom(b, true);
+                }
+            }
+        }
+        self.mate.iter().map(|&p| if p == NONE { NONE } else { self.endpoint[p] }).collect()
+    }
+}
+
+// Finds a maximum weight matching on the undirected graph reachable from `nodes`. Returns the pairs, their total weight, and whether the matching is perfect.
+fn weighted_matching<T>(
+    nodes: &[T],
+    neighbor_function: &impl Adjacency<T>,
+    weight_function: &impl Fn(T, T) -> OrderedFloat<f64>,
+    max_cardinality: bool,
+) -> (Vec<(T, T)>, OrderedFloat<f64>, bool)
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let (nodes, ids, directed_edges) = flow_edges(nodes.iter().copied(), neighbor_function);
+    let mut seen = HashSet::new();
+    let edges = directed_edges
+        .into_iter()
+        .filter(|&(u, v)| seen.insert((ids[&u].min(ids[&v]), ids[&u].max(ids[&v]))))
+        .map(|(u, v)| (ids[&u], ids[&v], weight_function(u, v).0))
+        .collect::<Vec<_>>();
+    let mate = Blossoms::new(nodes.len(), &edges).solve(max_cardinality);
+
+    let pairs = (0..nodes.len())
+        .filter(|&v| mate[v] != NONE && v < mate[v])
+        .map(|v| (nodes[v], nodes[mate[v]]))
+        .collect::<Vec<_>>();
+    let weight = pairs.iter().map(|&(u, v)| weight_function(u, v)).sum();
+    let perfect = mate.iter().all(|&m| m != NONE);
+    (pairs, weight, perfect)
+}
+
+/// Finds a maximum (cardinality) matching in an undirected graph, using Edmonds' blossom algorithm.
+///
+/// # Arguments
+/// * `nodes` - The nodes of the graph. Nodes that are only reachable from them are included as well.
+/// * `neighbor_function` - A function that returns the neighbors of a given node. It must be symmetric.
+///
+/// # Returns
+/// * `Vec<(T, T)>` - The matched pairs.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::find_maximum_matching;
+///
+/// // A 5-cycle with a pendant node 5 attached to 0: the odd cycle must be shrunk to find the perfect matching.
+/// let neighbor_function = |node: u32| -> Vec<u32> {
+///     match node {
+///         0 => vec![1, 4, 5],
+///         5 => vec![0],
+///         _ => vec![(node + 1) % 5, (node + 4) % 5],
+///     }
+/// };
+/// let matching = find_maximum_matching(&[1], neighbor_function);
+/// assert_eq!(matching.len(), 3);
+/// assert!(matching.contains(&(0, 5)) || matching.contains(&(5, 0)));
+/// ```
+pub fn find_maximum_matching<T>(nodes: &[T], neighbor_function: impl Fn(T) -> Vec<T>) -> Vec<(T, T)>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    weighted_matching(nodes, &neighbor_function, &|_, _| OrderedFloat(1.), true).0
+}
+
+/// Finds a maximum weight matching in an undirected graph, using the primal-dual blossom algorithm.
+///
+/// # Arguments
+/// * `nodes` - The nodes of the graph. Nodes that are only reachable from them are included as well.
+/// * `neighbor_function` - A function that returns the neighbors of a given node. It must be symmetric.
+/// * `weight_function` - A function that returns the weight of the edge between two nodes. Edges with a negative weight are never used.
+///
+/// # Returns
+/// * `(Vec<(T, T)>, OrderedFloat<f64>)` - The matched pairs and their total weight.
+///
+/// # Source
+/// Galil, Z. (1986). Efficient algorithms for finding maximum matching in graphs. ACM Computing Surveys.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::find_maximum_weight_matching;
+/// use ordered_float::OrderedFloat;
+///
+/// // A path 0-1-2-3 where the middle edge is heavier than the two outer edges together.
+/// let neighbor_function = |node: i32| -> Vec<i32> { [node - 1, node + 1].into_iter().filter(|n| (0..4).contains(n)).collect() };
+/// let weight_function = |a: i32, b: i32| OrderedFloat(if a.min(b) == 1 { 5. } else { 2. });
+///
+/// let (matching, weight) = find_maximum_weight_matching(&[0], neighbor_function, weight_function);
+/// assert_eq!(matching.len(), 1);
+/// assert_eq!(weight, OrderedFloat(5.));
+/// ```
+///
+/// The weight is optimal on random graphs, compared against all matchings:
+/// ```
+/// use hutspot::graph::{find_maximum_weight_matching, find_maximum_weight_perfect_matching};
+/// use ordered_float::OrderedFloat;
+///
+/// fn best(edges: &[(usize, usize, f64)], used: u32, perfect: bool, n: usize) -> Option<f64> {
+///     let Some(&(u, v, w)) = edges.first() else {
+///         return (!perfect || used.count_ones() as usize == n).then_some(0.);
+///     };
+///     let skip = best(&edges[1..], used, perfect, n);
+///     let take = (used & (1 << u | 1 << v) == 0).then(|| best(&edges[1..], used | 1 << u | 1 << v, perfect, n).map(|rest| rest + w)).flatten();
+///     [skip, take].into_iter().flatten().reduce(f64::max)
+/// }
+///
+/// let mut seed: u64 = 1;
+/// let mut random = move || {
+///     seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+///     seed >> 33
+/// };
+/// for _ in 0..300 {
+///     let n = 1 + random() as usize % 8;
+///     let mut weights = vec![None; n * n];
+///     for a in 0..n {
+///         for b in a + 1..n {
+///             if random() % 2 == 0 {
+///                 let w = (random() % 20) as f64 - 4.;
+///                 weights[a * n + b] = Some(w);
+///                 weights[b * n + a] = Some(w);
+///             }
+///         }
+///     }
+///     let edges = (0..n).flat_map(|a| (a + 1..n).map(move |b| (a, b))).filter_map(|(a, b)| weights[a * n + b].map(|w| (a, b, w))).collect::<Vec<_>>();
+///     let neighbor_function = |a: usize| -> Vec<usize> { (0..n).filter(|&b| weights[a * n + b].is_some()).collect() };
+///     let weight_function = |a: usize, b: usize| OrderedFloat(weights[a * n + b].unwrap());
+///     let nodes = (0..n).collect::<Vec<_>>();
+///
+///     let (_, weight) = find_maximum_weight_matching(&nodes, neighbor_function, weight_function);
+///     assert_eq!(Some(weight.0), best(&edges, 0, false, n));
+///
+///     let perfect = find_maximum_weight_perfect_matching(&nodes, neighbor_function, weight_function);
+///     assert_eq!(perfect.map(|(_, weight)| weight.0), best(&edges, 0, true, n));
+/// }
+/// ```
+pub fn find_maximum_weight_matching<T>(
+    nodes: &[T],
+    neighbor_function: impl Fn(T) -> Vec<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+) -> (Vec<(T, T)>, OrderedFloat<f64>)
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let (pairs, weight, _) = weighted_matching(nodes, &neighbor_function, &weight_function, false);
+    (pairs, weight)
+}
+
+/// Finds a maximum weight perfect matching in an undirected graph, using the primal-dual blossom algorithm. Negate the weights to find a minimum weight perfect matching.
+///
+/// # Arguments
+/// * `nodes` - The nodes of the graph. Nodes that are only reachable from them are included as well, and must be matched too.
+/// * `neighbor_function` - A function that returns the neighbors of a given node. It must be symmetric.
+/// * `weight_function` - A function that returns the weight of the edge between two nodes. Weights may be negative.
+///
+/// # Returns
+/// * `Option<(Vec<(T, T)>, OrderedFloat<f64>)>` - The matched pairs and their total weight, or `None` if there is no perfect matching.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::find_maximum_weight_perfect_matching;
+/// use ordered_float::OrderedFloat;
+///
+/// // Pair up the triangles of a strip into quads, preferring to pair 1 and 2 (weight 5).
+/// let neighbor_function = |node: i32| -> Vec<i32> { [node - 1, node + 1].into_iter().filter(|n| (0..4).contains(n)).collect() };
+/// let weight_function = |a: i32, b: i32| OrderedFloat(if a.min(b) == 1 { 5. } else { 2. });
+///
+/// // Perfect matchings must use the two outer edges.
+/// let (matching, weight) = find_maximum_weight_perfect_matching(&[0], neighbor_function, weight_function).unwrap();
+/// assert_eq!(matching.len(), 2);
+/// assert_eq!(weight, OrderedFloat(4.));
+///
+/// // A path with an odd number of nodes has no perfect matching.
+/// let neighbor_function = |node: i32| -> Vec<i32> { [node - 1, node + 1].into_iter().filter(|n| (0..3).contains(n)).collect() };
+/// assert!(find_maximum_weight_perfect_matching(&[0], neighbor_function, weight_function).is_none());
+/// ```
+#[allow(clippy::type_complexity)]
+pub fn find_maximum_weight_perfect_matching<T>(
+    nodes: &[T],
+    neighbor_function: impl Fn(T) -> Vec<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+) -> Option<(Vec<(T, T)>, OrderedFloat<f64>)>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let (pairs, weight, perfect) = weighted_matching(nodes, &neighbor_function, &weight_function, true);
+    perfect.then_some((pairs, weight))
+}
+
+/// The error returned when a directed graph has a cycle where none is allowed (any cycle for `topological_sort`, a negative one for `find_shortest_path_forest_negative`), with the cycle as witness.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleError<T> {
+    /// The nodes of a cycle, in order: every node has an edge to the next one, and the last one to the first one.
+    pub cycle: Vec<T>,
+}
+
+impl<T: Debug> fmt::Display for CycleError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "graph contains a cycle: {:?}", self.cycle)
+    }
+}
+
+impl<T: Debug> std::error::Error for CycleError<T> {}
+
+/// Sorts the nodes of a directed acyclic graph topologically, so that every edge goes from an earlier to a later node.
+///
+/// # Arguments
+/// * `nodes` - The nodes of the graph. Nodes that are only reachable from them are included as well.
+/// * `neighbor_function` - A function that returns the (outgoing) neighbors of a given node.
+///
+/// # Returns
+/// * `Result<Vec<T>, CycleError<T>>` - The sorted nodes, or a cycle if the graph is not acyclic.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::topological_sort;
+///
+/// let neighbor_function = |node: u32| -> Vec<u32> {
+///     match node {
+///         1 => vec![2, 3],
+///         2 => vec![3],
+///         3 => vec![5],
+///         4 => vec![6],
+///         5 => vec![4],
+///         _ => vec![],
+///     }
+/// };
+/// assert_eq!(topological_sort(&[1], neighbor_function), Ok(vec![1, 2, 3, 5, 4, 6]));
+///
+/// // With an edge from 4 back to 2, there is a cycle.
+/// let neighbor_function = |node: u32| -> Vec<u32> { if node == 4 { vec![2, 6] } else { neighbor_function(node) } };
+/// let error = topological_sort(&[1], neighbor_function).unwrap_err();
+/// assert_eq!(error.cycle, vec![2, 3, 5, 4]);
+/// ```
+pub fn topological_sort<T>(nodes: &[T], neighbor_function: impl Fn(T) -> Vec<T>) -> Result<Vec<T>, CycleError<T>>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    topological_order(nodes, &neighbor_function)
+}
+
+// Depth-first search that finishes every node after all its neighbors; a neighbor that is still on the stack closes a cycle.
+fn topological_order<T>(nodes: &[T], neighbor_function: &impl Adjacency<T>) -> Result<Vec<T>, CycleError<T>>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    // Whether every visited node is finished (or still on the stack).
+    let mut finished = HashMap::new();
+    let mut order = vec![];
+    for &root in nodes {
+        if finished.contains_key(&root) {
+            continue;
+        }
+        finished.insert(root, false);
+        let mut stack = vec![(root, neighbor_function.neighbors(root))];
+        while let Some((node, neighbors)) = stack.last_mut() {
+            let node = *node;
+            let Some(neighbor) = neighbors.next() else {
+                finished.insert(node, true);
+                order.push(node);
+                stack.pop();
+                continue;
+            };
+            match finished.get(&neighbor) {
+                None => {
+                    finished.insert(neighbor, false);
+                    stack.push((neighbor, neighbor_function.neighbors(neighbor)));
+                }
+                Some(false) => {
+                    let start = stack.iter().position(|(node, _)| *node == neighbor).unwrap();
+                    return Err(CycleError {
+                        cycle: stack[start..].iter().map(|(node, _)| *node).collect(),
+                    });
+                }
+                Some(true) => {}
+            }
+        }
+    }
+    order.reverse();
+    Ok(order)
+}
+
+/// Groups the nodes of a directed acyclic graph into layers by depth, as in Kahn's algorithm.
+///
+/// The first layer holds the nodes without incoming edges, and every other node is in the layer after the deepest of its predecessors. Every edge goes from an earlier to a later layer.
+///
+/// # Arguments
+/// * `nodes` - The nodes of the graph. Nodes that are only reachable from them are included as well.
+/// * `neighbor_function` - A function that returns the (outgoing) neighbors of a given node.
+///
+/// # Returns
+/// * `Result<Vec<Vec<T>>, CycleError<T>>` - The layers, or a cycle if the graph is not acyclic.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::topological_layers;
+///
+/// // A diamond 0 -> {1, 2} -> 3, with a shortcut 0 -> 3.
+/// let neighbor_function = |node: u32| -> Vec<u32> {
+///     match node {
+///         0 => vec![1, 2, 3],
+///         1 | 2 => vec![3],
+///         _ => vec![],
+///     }
+/// };
+/// let mut layers = topological_layers(&[0], neighbor_function).unwrap();
+/// layers[1].sort_unstable();
+/// assert_eq!(layers, vec![vec![0], vec![1, 2], vec![3]]);
+/// ```
+pub fn topological_layers<T>(nodes: &[T], neighbor_function: impl Fn(T) -> Vec<T>) -> Result<Vec<Vec<T>>, CycleError<T>>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    topological_layering(nodes, neighbor_function)
+}
+
+fn topological_layering<T>(nodes: &[T], neighbor_function: impl Adjacency<T>) -> Result<Vec<Vec<T>>, CycleError<T>>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let order = topological_order(nodes, &neighbor_function)?;
+    let mut depth: HashMap<T, usize> = HashMap::new();
+    let mut layers: Vec<Vec<T>> = vec![];
+    for node in order {
+        let d = depth.get(&node).copied().unwrap_or(0);
+        if d == layers.len() {
+            layers.push(vec![]);
+        }
+        layers[d].push(node);
+        for neighbor in neighbor_function.neighbors(node) {
+            let entry = depth.entry(neighbor).or_insert(0);
+            *entry = (*entry).max(d + 1);
+        }
+    }
+    Ok(layers)
+}
+
+/// Finds a longest (heaviest) path in a directed acyclic graph, such as the critical path of a schedule where the edges are dependencies weighted by duration.
+///
+/// # Arguments
+/// * `nodes` - The nodes of the graph. Nodes that are only reachable from them are included as well.
+/// * `neighbor_function` - A function that returns the (outgoing) neighbors of a given node.
+/// * `weight_function` - A function that returns the weight of the edge between two nodes. Weights may be negative.
+///
+/// # Returns
+/// * `Result<(Vec<T>, OrderedFloat<f64>), CycleError<T>>` - A longest path (which may start and end at any node) and its total weight, or a cycle if the graph is not acyclic.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::find_longest_path_dag;
+/// use ordered_float::OrderedFloat;
+///
+/// // Tasks 0..5 and a finish 5, where an edge (a, b) means that b starts after a is done, weighted by the duration of a.
+/// let durations = [3., 2., 4., 1., 2.];
+/// let neighbor_function = |node: usize| -> Vec<usize> {
+///     match node {
+///         0 => vec![1, 2],
+///         1 => vec![3],
+///         2 => vec![3, 4],
+///         3 | 4 => vec![5],
+///         _ => vec![],
+///     }
+/// };
+/// let weight_function = |a: usize, _: usize| OrderedFloat(durations[a]);
+///
+/// let (path, length) = find_longest_path_dag(&[0], neighbor_function, weight_function).unwrap();
+/// assert_eq!(path, vec![0, 2, 4, 5]);
+/// assert_eq!(length, OrderedFloat(9.));
+/// ```
+pub fn find_longest_path_dag<T>(
+    nodes: &[T],
+    neighbor_function: impl Fn(T) -> Vec<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+) -> Result<(Vec<T>, OrderedFloat<f64>), CycleError<T>>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    longest_path_dag(nodes, neighbor_function, weight_function)
+}
+
+fn longest_path_dag<T>(
+    nodes: &[T],
+    neighbor_function: impl Adjacency<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+) -> Result<(Vec<T>, OrderedFloat<f64>), CycleError<T>>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let order = topological_order(nodes, &neighbor_function)?;
+    // The weight of the longest path ending at every node, and the previous node on it.
+    let mut longest: HashMap<T, (OrderedFloat<f64>, Option<T>)> = order.iter().map(|&node| (node, (OrderedFloat(0.), None))).collect();
+    let mut best: Option<(OrderedFloat<f64>, T)> = None;
+    for &node in &order {
+        let (length, _) = longest[&node];
+        if best.is_none_or(|(best, _)| length > best) {
+            best = Some((length, node));
+        }
+        for neighbor in neighbor_function.neighbors(node) {
+            let next = length + weight_function(node, neighbor);
+            let entry = longest.get_mut(&neighbor).unwrap();
+            if next > entry.0 {
+                *entry = (next, Some(node));
+            }
+        }
+    }
+
+    Ok(best.map_or_else(
+        || (vec![], OrderedFloat(0.)),
+        |(length, last)| {
+            let mut path = vec![last];
+            while let Some(previous) = longest[path.last().unwrap()].1 {
+                path.push(previous);
+            }
+            path.reverse();
+            (path, length)
+        },
+    ))
+}
+
+/// Whether all edges of a graph can be traversed exactly once in a single walk. See `check_eulerian`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Eulerian<T> {
+    /// In a closed walk (an Eulerian circuit), which can start at any node with edges.
+    Circuit,
+    /// In a walk from the first node to the second (an Eulerian path), but not in a closed walk.
+    Path(T, T),
+    /// Not in a single walk.
+    Neither,
+}
+
+// Indexes the nodes reachable from `nodes` and their edges, once per pair of nodes for undirected graphs, without self-loops.
+#[allow(clippy::type_complexity)]
+fn euler_edges<T>(nodes: &[T], neighbor_function: &impl Adjacency<T>, directed: bool) -> (Vec<T>, HashMap<T, usize>, Vec<(usize, usize)>)
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let (reached, ids, edges) = flow_edges(nodes.iter().rev().copied(), neighbor_function);
+    let mut seen = HashSet::new();
+    let edges = edges
+        .into_iter()
+        .map(|(u, v)| (ids[&u], ids[&v]))
+        .filter(|&(u, v)| directed || seen.insert((u.min(v), u.max(v))))
+        .collect();
+    (reached, ids, edges)
+}
+
+// Checks the degrees and connectivity of a graph with `n` nodes, returning the start and end of an Eulerian path (equal for a circuit), or `None` if there is none.
+fn euler_ends(n: usize, edges: &[(usize, usize)], directed: bool) -> Option<(usize, usize)> {
+    let mut components = UnionFind::new(n);
+    // The out-degree minus the in-degree of every node, or its degree for undirected graphs.
+    let mut balance = vec![0isize; n];
+    for &(u, v) in edges {
+        components.union(u, v);
+        balance[u] += 1;
+        balance[v] += if directed { -1 } else { 1 };
+    }
+    let Some(&(first, _)) = edges.first() else {
+        return (n > 0).then_some((0, 0));
+    };
+    if edges.iter().any(|&(u, _)| !components.same_set(u, first)) {
+        return None;
+    }
+
+    if directed {
+        let starts = (0..n).filter(|&node| balance[node] == 1).collect::<Vec<_>>();
+        let ends = (0..n).filter(|&node| balance[node] == -1).collect::<Vec<_>>();
+        if balance.iter().any(|balance| balance.abs() > 1) {
+            return None;
+        }
+        match (starts.as_slice(), ends.as_slice()) {
+            ([], []) => Some((first, first)),
+            ([start], [end]) => Some((*start, *end)),
+            _ => None,
+        }
+    } else {
+        match (0..n).filter(|&node| balance[node] % 2 == 1).collect::<Vec<_>>().as_slice() {
+            [] => Some((first, first)),
+            [start, end] => Some((*start, *end)),
+            _ => None,
+        }
+    }
+}
+
+// Hierholzer's algorithm: walks unused edges until getting stuck, and splices in the detours found while backtracking. Every edge may occur more than once.
+fn hierholzer(n: usize, edges: &[(usize, usize)], directed: bool, start: usize) -> Vec<usize> {
+    let mut adjacency = vec![vec![]; n];
+    for (edge, &(u, v)) in edges.iter().enumerate() {
+        adjacency[u].push((v, edge));
+        if !directed {
+            adjacency[v].push((u, edge));
+        }
+    }
+    let mut used = vec![false; edges.len()];
+    let mut next = vec![0; n];
+    let mut stack = vec![start];
+    let mut walk = vec![];
+    while let Some(&node) = stack.last() {
+        while next[node] < adjacency[node].len() && used[adjacency[node][next[node]].1] {
+            next[node] += 1;
+        }
+        if let Some(&(neighbor, edge)) = adjacency[node].get(next[node]) {
+            used[edge] = true;
+            stack.push(neighbor);
+        } else {
+            walk.push(node);
+            stack.pop();
+        }
+    }
+    walk.reverse();
+    walk
+}
+
+/// Checks whether all edges of a graph can be traversed exactly once in a single walk.
+///
+/// For undirected graphs, this is the case if there are zero (for a circuit) or two (for a path) nodes of odd degree.
+/// For directed graphs, every node must have as many outgoing as incoming edges (for a circuit), except for one node with one more outgoing edge and one with one more incoming edge (for a path).
+/// In both cases, all edges must be connected.
+///
+/// # Arguments
+/// * `nodes` - The nodes of the graph. Nodes that are only reachable from them are included as well.
+/// * `neighbor_function` - A function that returns the neighbors of a given node. For undirected graphs, it must be symmetric. Self-loops are ignored.
+/// * `directed` - Whether the graph is directed.
+///
+/// # Returns
+/// * `Eulerian<T>` - Whether there is an Eulerian circuit, an Eulerian path (and between which nodes), or neither.
+///
+/// # Example
+/// ```
+/// use hutspot::graph::{check_eulerian, Eulerian};
+///
+/// // A square 0-1-2-3 with a diagonal 0-2, which makes 0 and 2 odd.
+/// let neighbor_function = |node: u32| -> Vec<u32> {
+///     match node {
+///         0 | 2 => vec![1, 3, 2 - node],
+///         _ => vec![0, 2],
+///     }
+/// };
+/// assert!(matches!(check_eulerian(&[0], neighbor_function, false), Eulerian::Path(0, 2) | Eulerian::Path(2, 0)));
+///
+/// // Directed, the square is a circuit, and the diagonal 0 -> 2 leaves a path from 0 to 2.
+/// let neighbor_function = |node: u32| -> Vec<u32> { if node == 0 { vec![1, 2] } else { vec![(node + 1) % 4] } };
+/// assert_eq!(check_eulerian(&[0], neighbor_function, true), Eulerian::Path(0, 2));
+/// let neighbor_function = |node: u32| -> Vec<u32> { vec![(node + 1) % 4] };
+/// assert_eq!(check_eulerian(&[0], neighbor_function, true), Eulerian::Circuit);
+/// ```
+pub fn check_eulerian<T>(nodes: &[T], neighbor_function: impl Fn(T) -> Vec<T>, directed: bool) -> Eulerian<T>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    eulerian(nodes, neighbor_function, directed)
+}
+
+fn eulerian<T>(nodes: &[T], neighbor_function: impl Adjacency<T>, directed: bool) -> Eulerian<T>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let (reached, _, edges) = euler_edges(nodes, &neighbor_function, directed);
+    match euler_ends(reached.len(), &edges, directed) {
+        Some((start, end)) if start == end => Eulerian::Circuit,
+        Some((start, end)) => Eulerian::Path(reached[start], reached[end]),
+        None => Eulerian::Neither,
+    }
+}
+
+/// Finds a walk that traverses every edge of a graph exactly once, using Hierholzer's algorithm.
+///
+/// # Arguments
+/// * `nodes` - The nodes of the graph. Nodes that are only reachable from them are included as well.
+/// * `neighbor_function` - A function that returns the neighbors of a given node. For undirected graphs, it must be symmetric. Self-loops are ignored.
+/// * `directed` - Whether the graph is directed.
+///
+/// # Returns
+/// * `Option<Vec<T>>` - The nodes of the walk, with one more node than there are edges, or `None` if there is no such walk (see `check_eulerian`).
+///   If there is an Eulerian circuit, the walk is closed (its last node is its first node).
+///
+/// # Example
+/// ```
+/// use hutspot::graph::find_eulerian_path;
+///
+/// // The house of Nikolaus: a square 0-1-2-3 with both diagonals and a roof 4 on top of 2-3. Only 0 and 1 have odd degree.
+/// let neighbor_function = |node: u32| -> Vec<u32> {
+///     match node {
+///         0 => vec![1, 2, 3],
+///         1 => vec![0, 2, 3],
+///         2 => vec![0, 1, 3, 4],
+///         3 => vec![0, 1, 2, 4],
+///         _ => vec![2, 3],
+///     }
+/// };
+/// let walk = find_eulerian_path(&[0], neighbor_function, false).unwrap();
+/// assert_eq!(walk.len(), 9);
+/// assert!((walk[0], walk[8]) == (0, 1) || (walk[0], walk[8]) == (1, 0));
+///
+/// // Every edge is traversed once.
+/// let mut edges = walk.windows(2).map(|pair| (pair[0].min(pair[1]), pair[0].max(pair[1]))).collect::<Vec<_>>();
+/// edges.sort_unstable();
+/// edges.dedup();
+/// assert_eq!(edges.len(), 8);
+/// ```
+pub fn find_eulerian_path<T>(nodes: &[T], neighbor_function: impl Fn(T) -> Vec<T>, directed: bool) -> Option<Vec<T>>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    eulerian_path(nodes, neighbor_function, directed)
+}
+
+fn eulerian_path<T>(nodes: &[T], neighbor_function: impl Adjacency<T>, directed: bool) -> Option<Vec<T>>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let (reached, _, edges) = euler_edges(nodes, &neighbor_function, directed);
+    let (start, _) = euler_ends(reached.len(), &edges, directed)?;
+    Some(
+        hierholzer(reached.len(), &edges, directed, start)
+            .into_iter()
+            .map(|node| reached[node])
+            .collect(),
+    )
+}
+
+/// Finds a closed walk that traverses every edge of a graph exactly once, using Hierholzer's algorithm.
+///
+/// # Arguments
+/// * `nodes` - The nodes of the graph. Nodes that are only reachable from them are included as well.
+/// * `neighbor_function` - A function that returns the neighbors of a given node. For undirected graphs, it must be symmetric. Self-loops are ignored.
+/// * `directed` - Whether the graph is directed.
+///
+/// # Returns
+/// * `Option<Vec<T>>` - The nodes of the walk, starting and ending at the same node, or `None` if there is no Eulerian circuit (see `check_eulerian`).
+///
+/// # Example
+/// ```
+/// use hutspot::graph::find_eulerian_circuit;
+///
+/// // Two directed triangles 0 -> 1 -> 2 -> 0 and 0 -> 3 -> 4 -> 0, sharing node 0.
+/// let neighbor_function = |node: u32| -> Vec<u32> {
+///     match node {
+///         0 => vec![1, 3],
+///         1 => vec![2],
+///         3 => vec![4],
+///         _ => vec![0],
+///     }
+/// };
+/// let walk = find_eulerian_circuit(&[0], neighbor_function, true).unwrap();
+/// assert_eq!(walk.len(), 7);
+/// assert_eq!(walk.first(), walk.last());
+/// assert!(walk.windows(2).all(|pair| neighbor_function(pair[0]).contains(&pair[1])));
+///
+/// // Without the edge 4 -> 0, there is no circuit.
+/// let neighbor_function = |node: u32| -> Vec<u32> { if node == 4 { vec![] } else { neighbor_function(node) } };
+/// assert!(find_eulerian_circuit(&[0], neighbor_function, true).is_none());
+/// ```
+pub fn find_eulerian_circuit<T>(nodes: &[T], neighbor_function: impl Fn(T) -> Vec<T>, directed: bool) -> Option<Vec<T>>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    eulerian_circuit(nodes, neighbor_function, directed)
+}
+
+fn eulerian_circuit<T>(nodes: &[T], neighbor_function: impl Adjacency<T>, directed: bool) -> Option<Vec<T>>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let (reached, _, edges) = euler_edges(nodes, &neighbor_function, directed);
+    let (start, end) = euler_ends(reached.len(), &edges, directed)?;
+    (start == end).then(|| {
+        hierholzer(reached.len(), &edges, directed, start)
+            .into_iter()
+            .map(|node| reached[node])
+            .collect()
+    })
+}
+
+/// Finds a shortest closed walk that traverses every edge of a graph at least once (the Chinese postman problem).
+///
+/// Edges are traversed more than once along shortest paths between the nodes that keep the graph from being Eulerian:
+/// for undirected graphs, the nodes of odd degree are paired up by a minimum weight perfect matching, and for directed graphs, the nodes with more incoming than outgoing edges are connected to the others by a minimum cost flow.
+///
+/// # Arguments
+/// * `nodes` - The nodes of the graph. Nodes that are only reachable from them are included as well.
+/// * `neighbor_function` - A function that returns the neighbors of a given node. For undirected graphs, it must be symmetric. Self-loops are ignored.
+/// * `weight_function` - A function that returns the (non-negative) weight of the edge between two nodes.
+/// * `directed` - Whether the graph is directed.
+///
+/// # Returns
+/// * `Option<(Vec<T>, OrderedFloat<f64>)>` - The nodes of the walk (starting and ending at the same node) and its total weight,
+///   or `None` if there is no such walk, because the edges are not connected (or, for directed graphs, not strongly connected).
+///
+/// # Example
+/// ```
+/// use hutspot::graph::find_chinese_postman_tour;
+/// use ordered_float::OrderedFloat;
+///
+/// // A path 0-1-2 with a triangle 2-3-4: the path has to be traversed twice.
+/// let neighbor_function = |node: u32| -> Vec<u32> {
+///     match node {
+///         0 => vec![1],
+///         1 => vec![0, 2],
+///         2 => vec![1, 3, 4],
+///         3 => vec![2, 4],
+///         _ => vec![2, 3],
+///     }
+/// };
+/// let (walk, weight) = find_chinese_postman_tour(&[0], neighbor_function, |_, _| OrderedFloat(1.), false).unwrap();
+/// assert_eq!(weight, OrderedFloat(7.));
+/// assert_eq!(walk.len(), 8);
+/// assert_eq!(walk.first(), walk.last());
+///
+/// // A directed square 0 -> 1 -> 2 -> 3 -> 0 with a diagonal 0 -> 2: the walk goes around twice, and once uses the diagonal.
+/// let neighbor_function = |node: u32| -> Vec<u32> { if node == 0 { vec![1, 2] } else { vec![(node + 1) % 4] } };
+/// let (walk, weight) = find_chinese_postman_tour(&[0], neighbor_function, |_, _| OrderedFloat(1.), true).unwrap();
+/// assert_eq!(weight, OrderedFloat(7.));
+/// assert_eq!(walk.first(), walk.last());
+/// ```
+pub fn find_chinese_postman_tour<T>(
+    nodes: &[T],
+    neighbor_function: impl Fn(T) -> Vec<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+    directed: bool,
+) -> Option<(Vec<T>, OrderedFloat<f64>)>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    chinese_postman_tour(nodes, neighbor_function, weight_function, directed)
+}
+
+fn chinese_postman_tour<T>(
+    nodes: &[T],
+    neighbor_function: impl Adjacency<T>,
+    weight_function: impl Fn(T, T) -> OrderedFloat<f64>,
+    directed: bool,
+) -> Option<(Vec<T>, OrderedFloat<f64>)>
+where
+    T: Eq + Hash + Clone + Copy,
+{
+    let (reached, ids, mut edges) = euler_edges(nodes, &neighbor_function, directed);
+    let n = reached.len();
+    let mut balance = vec![0isize; n];
+    for &(u, v) in &edges {
+        balance[u] += 1;
+        balance[v] += if directed { -1 } else { 1 };
+    }
+
+    if directed {
+        // Nodes with more incoming edges need extra walks to nodes with more outgoing edges, at most as many as the total imbalance per edge.
+        #[allow(clippy::cast_precision_loss)]
+        let supplies = (0..n)
+            .filter(|&node| balance[node] != 0)
+            .map(|node| (reached[node], -balance[node] as f64))
+            .collect::<Vec<_>>();
+        if !supplies.is_empty() {
+            let total = supplies.iter().map(|&(_, supply)| supply.abs()).sum::<f64>();
+            let flow = min_cost_flow(
+                &supplies,
+                &neighbor_function,
+                &|_, _| OrderedFloat(total),
+                &weight_function,
+                MinCostFlowAlgorithm::default(),
+            )
+            .ok()?;
+            for (&(u, v), amount) in &flow.flows {
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                edges.extend(std::iter::repeat_n((ids[&u], ids[&v]), amount.round() as usize));
+            }
+        }
+    } else {
+        // Pair up the nodes of odd degree by shortest paths of minimum total weight, and traverse those paths twice.
+        let odd = (0..n)
+            .filter(|&node| balance[node] % 2 == 1)
+            .map(|node| reached[node])
+            .collect::<Vec<_>>();
+        let forests = odd
+            .iter()
+            .map(|&node| shortest_path_forest(&[node], &neighbor_function, &weight_function, OrderedFloat(f64::INFINITY)))
+            .collect::<Vec<_>>();
+        let indices = (0..odd.len()).collect::<Vec<_>>();
+        let (pairs, _, perfect) = weighted_matching(
+            &indices,
+            &|i: usize| {
+                indices
+                    .iter()
+                    .copied()
+                    .filter(|&j| j != i && forests[i].distances.contains_key(&odd[j]))
+                    .collect::<Vec<_>>()
+            },
+            &|i: usize, j: usize| -forests[i].distances[&odd[j]],
+            true,
+        );
+        if !perfect {
+            return None;
+        }
+        for (i, j) in pairs {
+            let path = forests[i].path(odd[j]).unwrap();
+            edges.extend(path.windows(2).map(|pair| (ids[&pair[0]], ids[&pair[1]])));
+        }
+    }
+
+    let (start, end) = euler_ends(n, &edges, directed)?;
+    if start != end {
+        return None;
+    }
+    let walk = hierholzer(n, &edges, directed, start)
+        .into_iter()
+        .map(|node| reached[node])
+        .collect::<Vec<_>>();
+    let weight = walk.windows(2).map(|pair| weight_function(pair[0], pair[1])).sum();
+    Some((walk, weight))
+}
+
+// The algorithms of this module on a `Graph`, which read its stored edge weights instead of calling a weight function, and run over all of its nodes.
+impl Graph {
+    fn all_nodes(&self) -> Vec<usize> {
+        self.nodes().collect()
+    }
+
+    // The graph with all edges reversed, for the backward side of the bidirectional searches.
+    fn reversed(&self) -> Self {
+        let edges = self
+            .nodes()
+            .flat_map(|u| self.weighted_neighbors(u).map(move |(v, weight)| (v, u, weight.0)));
+        Self::from_weighted_edges(self.nr_nodes(), edges, true).expect("edges of the graph connect its nodes")
+    }
+
+    /// Like `find_shortest_path`, with the edge weights of this graph.
+    #[must_use]
+    pub fn find_shortest_path(&self, a: usize, b: usize) -> Option<(Vec<usize>, OrderedFloat<f64>)> {
+        shortest_path(a, b, self, &self.weight_function())
+    }
+
+    /// Like `find_shortest_path_astar`, with the edge weights of this graph.
+    #[must_use]
+    pub fn find_shortest_path_astar(
+        &self,
+        a: usize,
+        b: usize,
+        heuristic_function: impl Fn(usize, usize) -> OrderedFloat<f64>,
+    ) -> Option<(Vec<usize>, OrderedFloat<f64>)> {
+        shortest_path_astar(a, b, self, self.weight_function(), heuristic_function)
+    }
+
+    /// Like `find_shortest_path_bidirectional`, with the edge weights of this graph. Directed graphs are reversed first, for the backward search.
+    #[must_use]
+    pub fn find_shortest_path_bidirectional(&self, a: usize, b: usize) -> Option<(Vec<usize>, OrderedFloat<f64>)> {
+        let reversed;
+        let reverse = if self.directed {
+            reversed = self.reversed();
+            &reversed
+        } else {
+            self
+        };
+        bidirectional(a, b, self, reverse, &self.weight_function(), &|_| OrderedFloat(0.))
+    }
+
+    /// Like `find_shortest_path_astar_bidirectional`, with the edge weights of this graph. Directed graphs are reversed first, for the backward search.
+    #[must_use]
+    pub fn find_shortest_path_astar_bidirectional(
+        &self,
+        a: usize,
+        b: usize,
+        heuristic_function: impl Fn(usize, usize) -> OrderedFloat<f64>,
+    ) -> Option<(Vec<usize>, OrderedFloat<f64>)> {
+        let reversed;
+        let reverse = if self.directed {
+            reversed = self.reversed();
+            &reversed
+        } else {
+            self
+        };
+        let potential = |node| (heuristic_function(node, b) - heuristic_function(a, node)) / 2.;
+        bidirectional(a, b, self, reverse, &self.weight_function(), &potential)
+    }
+
+    /// Like `find_shortest_path_forest`, with the edge weights of this graph.
+    #[must_use]
+    pub fn find_shortest_path_forest(&self, seeds: &[usize], cutoff: Option<f64>) -> ShortestPathForest<usize> {
+        shortest_path_forest(seeds, self, &self.weight_function(), OrderedFloat(cutoff.unwrap_or(f64::INFINITY)))
+    }
+
+    /// Like `find_shortest_path_forest_negative`, with the edge weights of this graph.
+    pub fn find_shortest_path_forest_negative(
+        &self,
+        seeds: &[usize],
+        algorithm: NegativeWeightAlgorithm,
+    ) -> Result<ShortestPathForest<usize>, CycleError<usize>> {
+        negative_shortest_path_forest(seeds, self, &self.weight_function(), algorithm)
+    }
+
+    /// Like `find_shortest_path_bellman_ford`, with the edge weights of this graph.
+    #[allow(clippy::type_complexity)]
+    pub fn find_shortest_path_bellman_ford(
+        &self,
+        a: usize,
+        b: usize,
+        algorithm: NegativeWeightAlgorithm,
+    ) -> Result<Option<(Vec<usize>, OrderedFloat<f64>)>, CycleError<usize>> {
+        shortest_path_bellman_ford(a, b, self, self.weight_function(), algorithm)
+    }
+
+    /// Like `find_all_shortest_paths_johnson`, from all nodes, with the edge weights of this graph.
+    pub fn find_all_shortest_paths_johnson(&self) -> Result<HashMap<usize, ShortestPathForest<usize>>, CycleError<usize>> {
+        all_shortest_paths_johnson(&self.all_nodes(), self, self.weight_function())
+    }
+
+    /// Like `find_all_pairs_floyd_warshall`, between all nodes, with the edge weights of this graph.
+    pub fn find_all_pairs_floyd_warshall(&self) -> Result<AllPairsShortestPaths<usize>, CycleError<usize>> {
+        all_pairs_floyd_warshall(&self.all_nodes(), self, self.weight_function())
+    }
+
+    /// Like `find_all_pairs_dijkstra`, with the edge weights of this graph.
+    #[must_use]
+    pub fn find_all_pairs_dijkstra(&self, sources: &[usize], targets: Option<&[usize]>, paths: bool) -> AllPairsShortestPaths<usize> {
+        all_pairs_dijkstra(sources, targets, self, self.weight_function(), paths)
+    }
+
+    /// Like `find_shortest_cycle`, with the edge weights of this graph.
+    #[must_use]
+    pub fn find_shortest_cycle(&self, a: usize) -> Option<(Vec<usize>, OrderedFloat<f64>)> {
+        shortest_cycle(a, self, self.weight_function())
+    }
+
+    /// Like `find_girth`, with the edge weights of this (undirected) graph.
+    #[must_use]
+    pub fn find_girth(&self) -> Option<(Vec<usize>, OrderedFloat<f64>)> {
+        girth(&self.all_nodes(), self, self.weight_function())
+    }
+
+    /// Like `find_minimum_cycle_basis`, with the edge weights of this (undirected) graph.
+    #[must_use]
+    pub fn find_minimum_cycle_basis(&self) -> Vec<(Vec<usize>, OrderedFloat<f64>)> {
+        minimum_cycle_basis(&self.all_nodes(), self, self.weight_function())
+    }
+
+    /// Like `find_k_shortest_paths`, with the edge weights of this graph.
+    #[must_use]
+    pub fn find_k_shortest_paths(&self, a: usize, b: usize, k: usize) -> Vec<(Vec<usize>, OrderedFloat<f64>)> {
+        KShortestPaths::start(a, b, self, self.weight_function()).take(k).collect()
+    }
+
+    /// Like `find_ccs`, on all nodes of this (undirected) graph.
+    #[must_use]
+    pub fn find_ccs(&self) -> Vec<HashSet<usize>> {
+        ccs(&self.all_nodes(), self)
+    }
+
+    /// Like `find_cc`.
+    #[must_use]
+    pub fn find_cc(&self, node: usize) -> HashSet<usize> {
+        reach(node, self)
+    }
+
+    /// Like `find_sccs`, on all nodes of this graph.
+    #[must_use]
+    pub fn find_sccs(&self) -> Vec<HashSet<usize>> {
+        sccs(&self.all_nodes(), self)
+    }
+
+    /// Like `find_condensation`, on all nodes of this graph.
+    #[must_use]
+    pub fn find_condensation(&self) -> Condensation<usize> {
+        condensation(&self.all_nodes(), self)
+    }
+
+    /// Like `find_wccs`, on all nodes of this graph.
+    #[must_use]
+    pub fn find_wccs(&self) -> Vec<HashSet<usize>> {
+        wccs(&self.all_nodes(), self)
+    }
+
+    /// Like `find_minimum_spanning_forest`, with the edge weights of this (undirected) graph.
+    #[must_use]
+    pub fn find_minimum_spanning_forest(&self) -> (Vec<(usize, usize)>, OrderedFloat<f64>) {
+        kruskal(&self.all_nodes(), self, &self.weight_function(), false)
+    }
+
+    /// Like `find_maximum_spanning_forest`, with the edge weights of this (undirected) graph.
+    #[must_use]
+    pub fn find_maximum_spanning_forest(&self) -> (Vec<(usize, usize)>, OrderedFloat<f64>) {
+        kruskal(&self.all_nodes(), self, &self.weight_function(), true)
+    }
+
+    /// Like `find_minimum_spanning_tree`, with the edge weights of this (undirected) graph.
+    #[must_use]
+    pub fn find_minimum_spanning_tree(&self, root: usize) -> (Vec<(usize, usize)>, OrderedFloat<f64>) {
+        minimum_spanning_tree(root, self, self.weight_function())
+    }
+
+    /// Like `find_max_flow`.
+    #[must_use]
+    pub fn find_max_flow(
+        &self,
+        source: usize,
+        sink: usize,
+        capacity_function: impl Fn(usize, usize) -> OrderedFloat<f64>,
+        algorithm: MaxFlowAlgorithm,
+    ) -> MaxFlow<usize> {
+        max_flow(&[source], &[sink], self, &capacity_function, algorithm)
+    }
+
+    /// Like `find_max_flow_multi`.
+    #[must_use]
+    pub fn find_max_flow_multi(
+        &self,
+        sources: &[usize],
+        sinks: &[usize],
+        capacity_function: impl Fn(usize, usize) -> OrderedFloat<f64>,
+        algorithm: MaxFlowAlgorithm,
+    ) -> MaxFlow<usize> {
+        max_flow(sources, sinks, self, &capacity_function, algorithm)
+    }
+
+    /// Like `find_min_cost_flow`.
+    pub fn find_min_cost_flow(
+        &self,
+        supplies: &[(usize, f64)],
+        capacity_function: impl Fn(usize, usize) -> OrderedFloat<f64>,
+        cost_function: impl Fn(usize, usize) -> OrderedFloat<f64>,
+        algorithm: MinCostFlowAlgorithm,
+    ) -> Result<MinCostFlow<usize>, MinCostFlowError> {
+        min_cost_flow(supplies, self, &capacity_function, &cost_function, algorithm)
+    }
+
+    /// Like `find_min_cost_max_flow`.
+    pub fn find_min_cost_max_flow(
+        &self,
+        source: usize,
+        sink: usize,
+        capacity_function: impl Fn(usize, usize) -> OrderedFloat<f64>,
+        cost_function: impl Fn(usize, usize) -> OrderedFloat<f64>,
+        algorithm: MinCostFlowAlgorithm,
+    ) -> Result<MinCostFlow<usize>, MinCostFlowError> {
+        min_cost_max_flow(source, sink, self, capacity_function, cost_function, algorithm)
+    }
+
+    /// Like `two_color`, on all nodes of this (undirected) graph.
+    #[must_use]
+    pub fn two_color(&self) -> Option<(HashSet<usize>, HashSet<usize>)> {
+        bipartition(&self.all_nodes(), self)
+    }
+
+    /// Like `find_greedy_coloring`, on all nodes of this graph.
+    #[must_use]
+    pub fn find_greedy_coloring(&self, order: ColoringOrder) -> HashMap<usize, usize> {
+        ordered_greedy_coloring(&self.all_nodes(), self, order)
+    }
+
+    /// Like `find_dsatur_coloring`, on all nodes of this graph.
+    #[must_use]
+    pub fn find_dsatur_coloring(&self) -> HashMap<usize, usize> {
+        dsatur_coloring(&self.all_nodes(), self)
+    }
+
+    /// Like `find_k_coloring`, on all nodes of this graph.
+    #[must_use]
+    pub fn find_k_coloring(&self, k: usize) -> Option<HashMap<usize, usize>> {
+        k_coloring(&self.all_nodes(), self, k)
+    }
+
+    /// Like `find_bipartite_matching`.
+    #[must_use]
+    pub fn find_bipartite_matching<'a>(&self, left: impl IntoIterator<Item = &'a usize>) -> BipartiteMatching<usize> {
+        bipartite_matching(left, self)
+    }
+
+    /// Like `find_assignment`.
+    #[must_use]
+    pub fn find_assignment<'a>(
+        &self,
+        left: impl IntoIterator<Item = &'a usize>,
+        cost_function: impl Fn(usize, usize) -> OrderedFloat<f64>,
+    ) -> (Vec<(usize, usize)>, OrderedFloat<f64>) {
+        assignment(left, self, cost_function)
+    }
+
+    /// Like `find_maximum_matching`, on all nodes of this (undirected) graph.
+    #[must_use]
+    pub fn find_maximum_matching(&self) -> Vec<(usize, usize)> {
+        weighted_matching(&self.all_nodes(), &NeighborsOnly(self), &|_, _| OrderedFloat(1.), true).0
+    }
+
+    /// Like `find_maximum_weight_matching`, with the edge weights of this (undirected) graph.
+    #[must_use]
+    pub fn find_maximum_weight_matching(&self) -> (Vec<(usize, usize)>, OrderedFloat<f64>) {
+        let (pairs, weight, _) = weighted_matching(&self.all_nodes(), self, &self.weight_function(), false);
+        (pairs, weight)
+    }
+
+    /// Like `find_maximum_weight_perfect_matching`, with the edge weights of this (undirected) graph.
+    #[must_use]
+    #[allow(clippy::type_complexity)]
+    pub fn find_maximum_weight_perfect_matching(&self) -> Option<(Vec<(usize, usize)>, OrderedFloat<f64>)> {
+        let (pairs, weight, perfect) = weighted_matching(&self.all_nodes(), self, &self.weight_function(), true);
+        perfect.then_some((pairs, weight))
+    }
+
+    /// Like `topological_sort`, on all nodes of this (directed) graph.
+    pub fn topological_sort(&self) -> Result<Vec<usize>, CycleError<usize>> {
+        topological_order(&self.all_nodes(), self)
+    }
+
+    /// Like `topological_layers`, on all nodes of this (directed) graph.
+    pub fn topological_layers(&self) -> Result<Vec<Vec<usize>>, CycleError<usize>> {
+        topological_layering(&self.all_nodes(), self)
+    }
+
+    /// Like `find_longest_path_dag`, with the edge weights of this (directed) graph.
+    pub fn find_longest_path_dag(&self) -> Result<(Vec<usize>, OrderedFloat<f64>), CycleError<usize>> {
+        longest_path_dag(&self.all_nodes(), self, self.weight_function())
+    }
+
+    /// Like `check_eulerian`, on all nodes of this graph, and directed if the graph is.
+    #[must_use]
+    pub fn check_eulerian(&self) -> Eulerian<usize> {
+        eulerian(&self.all_nodes(), self, self.directed)
+    }
+
+    /// Like `find_eulerian_path`, on all nodes of this graph, and directed if the graph is.
+    #[must_use]
+    pub fn find_eulerian_path(&self) -> Option<Vec<usize>> {
+        eulerian_path(&self.all_nodes(), self, self.directed)
+    }
+
+    /// Like `find_eulerian_circuit`, on all nodes of this graph, and directed if the graph is.
+    #[must_use]
+    pub fn find_eulerian_circuit(&self) -> Option<Vec<usize>> {
+        eulerian_circuit(&self.all_nodes(), self, self.directed)
+    }
+
+    /// Like `find_chinese_postman_tour`, with the edge weights of this graph, and directed if the graph is.
+    #[must_use]
+    pub fn find_chinese_postman_tour(&self) -> Option<(Vec<usize>, OrderedFloat<f64>)> {
+        chinese_postman_tour(&self.all_nodes(), self, self.weight_function(), self.directed)
+    }
 }
@@ -0,0 +1,809 @@
+use crate::color::Color;
+use crate::geom::Vector3D;
+use crate::mesh::{Mesh, MeshError};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// Errors that can occur when reading or writing mesh files.
+#[derive(Debug)]
+pub enum IoError {
+    /// An error of the underlying reader or writer (including unexpected end of file).
+    Io(std::io::Error),
+    /// The file is malformed. `line` is the (1-based) line number for text data, or 0 for binary data.
+    Malformed { line: usize, reason: String },
+    /// The file uses a format or feature that is not supported.
+    Unsupported(String),
+    /// The data to write is inconsistent, e.g. a face refers to a missing vertex or there are fewer colors than vertices.
+    InvalidData(String),
+}
+
+impl fmt::Display for IoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{error}"),
+            Self::Malformed { line: 0, reason } => write!(f, "malformed file: {reason}"),
+            Self::Malformed { line, reason } => write!(f, "malformed file at line {line}: {reason}"),
+            Self::Unsupported(reason) => write!(f, "unsupported file: {reason}"),
+            Self::InvalidData(reason) => write!(f, "invalid mesh data: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for IoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for IoError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+fn malformed(line: usize, reason: impl Into<String>) -> IoError {
+    IoError::Malformed { line, reason: reason.into() }
+}
+
+/// The contents of a mesh file: vertex positions, faces and polylines (as indices into the positions), and optional per-vertex and per-face colors.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeshData {
+    pub positions: Vec<Vector3D>,
+    pub faces: Vec<Vec<usize>>,
+    pub polylines: Vec<Vec<usize>>,
+    pub vertex_colors: Option<Vec<Color>>,
+    pub face_colors: Option<Vec<Color>>,
+}
+
+impl MeshData {
+    /// Builds a halfedge mesh with the positions as vertex data.
+    pub fn to_mesh(&self) -> Result<Mesh<Vector3D>, MeshError> {
+        Mesh::from_faces(self.positions.clone(), &self.faces)
+    }
+
+    /// Collects the positions and faces of a halfedge mesh.
+    #[must_use]
+    pub fn from_mesh<E, F>(mesh: &Mesh<Vector3D, E, F>) -> Self {
+        Self {
+            positions: mesh.vert_ids().map(|v| *mesh.vert_data(v)).collect(),
+            faces: mesh.face_ids().map(|f| mesh.corners(f).into_iter().map(|v| v.0).collect()).collect(),
+            ..Self::default()
+        }
+    }
+
+    // Checks that all indices refer to existing vertices and that there is a color for every vertex or face, before anything is written.
+    fn validate(&self) -> Result<(), IoError> {
+        let nr_verts = self.positions.len();
+        if let Some(index) = self.faces.iter().chain(&self.polylines).flatten().find(|&&i| i >= nr_verts) {
+            return Err(IoError::InvalidData(format!(
+                "vertex index {index} is out of range for {nr_verts} vertices"
+            )));
+        }
+        for (name, colors, count) in [("vertex", &self.vertex_colors, nr_verts), ("face", &self.face_colors, self.faces.len())] {
+            if let Some(colors) = colors.as_ref().filter(|colors| colors.len() != count) {
+                return Err(IoError::InvalidData(format!("{} {name} colors for {count} {name}s", colors.len())));
+            }
+        }
+        Ok(())
+    }
+
+    // Fan triangulation of all faces, used by formats that only support triangles.
+    fn triangles(&self) -> impl Iterator<Item = [usize; 3]> + '_ {
+        self.faces
+            .iter()
+            .flat_map(|face| (1..face.len().saturating_sub(1)).map(|i| [face[0], face[i], face[i + 1]]))
+    }
+}
+
+fn parse_float(token: &str, line: usize) -> Result<f64, IoError> {
+    token.parse().map_err(|_| malformed(line, format!("invalid number `{token}`")))
+}
+
+// Converts a value read from a file to a count or an index, which must be a non-negative integer.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss, clippy::cast_precision_loss)]
+fn to_index(value: f64) -> Option<usize> {
+    (value >= 0. && value.fract() == 0. && value < usize::MAX as f64).then_some(value as usize)
+}
+
+fn color_to_byte(value: f32) -> u8 {
+    // Truncation is intended: the value is clamped and rounded to 0..=255 first.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let byte = (value.clamp(0., 1.) * 255.).round() as u8;
+    byte
+}
+
+/// Reads a Wavefront OBJ file.
+/// # Arguments
+/// * `reader` - The OBJ data.
+/// # Returns
+/// * `Result<MeshData, IoError>` - The vertices (`v`, optionally followed by an RGB color), faces (`f`), and polylines (`l`). Texture coordinates, normals, groups and materials are ignored.
+///
+/// # Example
+/// ```
+/// use hutspot::io::{read_obj, write_obj};
+/// let file = std::fs::File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/tetrahedron.obj")).unwrap();
+/// let data = read_obj(std::io::BufReader::new(file)).unwrap();
+/// assert_eq!((data.positions.len(), data.faces.len(), data.polylines.len()), (4, 4, 1));
+/// assert!(data.vertex_colors.is_some());
+/// assert!(data.to_mesh().unwrap().boundary_loops().is_empty());
+///
+/// let mut buffer = vec![];
+/// write_obj(&mut buffer, &data).unwrap();
+/// assert_eq!(read_obj(buffer.as_slice()).unwrap(), data);
+///
+/// assert!(read_obj("v 0 0 0\nv 1 0 0\nf 1 2 3\n".as_bytes()).is_err());
+///
+/// let missing_colors = hutspot::io::MeshData { vertex_colors: Some(vec![[1., 0., 0.]]), ..data };
+/// assert!(matches!(write_obj(&mut vec![], &missing_colors), Err(hutspot::io::IoError::InvalidData(_))));
+/// ```
+pub fn read_obj(reader: impl BufRead) -> Result<MeshData, IoError> {
+    let mut data = MeshData::default();
+    let mut colors = vec![];
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let number = index + 1;
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("v") => {
+                let values = tokens.map(|token| parse_float(token, number)).collect::<Result<Vec<_>, _>>()?;
+                match values.len() {
+                    3 | 4 => colors.push(None),
+                    #[allow(clippy::cast_possible_truncation)]
+                    6 => colors.push(Some([values[3] as f32, values[4] as f32, values[5] as f32])),
+                    _ => return Err(malformed(number, "a vertex needs three coordinates")),
+                }
+                data.positions.push(Vector3D::new(values[0], values[1], values[2]));
+            }
+            Some(keyword @ ("f" | "l")) => {
+                let nr_verts = data.positions.len();
+                let indices = tokens
+                    .map(|token| {
+                        // Only the vertex index of `v/vt/vn` is used; negative indices count back from the last vertex.
+                        let index = token.split('/').next().unwrap_or_default();
+                        match index.parse::<i64>() {
+                            Ok(i) if i > 0 && i.unsigned_abs() <= nr_verts as u64 => Ok(usize::try_from(i - 1).unwrap_or_default()),
+                            Ok(i) if i < 0 && i.unsigned_abs() <= nr_verts as u64 => {
+                                Ok(nr_verts - usize::try_from(i.unsigned_abs()).unwrap_or_default())
+                            }
+                            _ => Err(malformed(number, format!("invalid vertex index `{token}`"))),
+                        }
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                if keyword == "f" {
+                    if indices.len() < 3 {
+                        return Err(malformed(number, "a face needs at least three vertices"));
+                    }
+                    data.faces.push(indices);
+                } else {
+                    if indices.len() < 2 {
+                        return Err(malformed(number, "a polyline needs at least two vertices"));
+                    }
+                    data.polylines.push(indices);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !colors.is_empty() && colors.iter().all(Option::is_some) {
+        data.vertex_colors = Some(colors.into_iter().flatten().collect());
+    }
+    Ok(data)
+}
+
+/// Writes a Wavefront OBJ file.
+/// # Arguments
+/// * `writer` - The destination.
+/// * `data` - The mesh to write. Vertex colors are written after the coordinates; face colors are not supported by the format and are ignored.
+/// # Returns
+/// * `Result<(), IoError>` - An `IoError::InvalidData` (before anything is written) if an index is out of range or the number of colors does not match.
+pub fn write_obj(mut writer: impl Write, data: &MeshData) -> Result<(), IoError> {
+    data.validate()?;
+    for (i, p) in data.positions.iter().enumerate() {
+        match &data.vertex_colors {
+            Some(colors) => writeln!(writer, "v {} {} {} {} {} {}", p.x, p.y, p.z, colors[i][0], colors[i][1], colors[i][2])?,
+            None => writeln!(writer, "v {} {} {}", p.x, p.y, p.z)?,
+        }
+    }
+    for (keyword, elements) in [("f", &data.faces), ("l", &data.polylines)] {
+        for element in elements {
+            write!(writer, "{keyword}")?;
+            for index in element {
+                write!(writer, " {}", index + 1)?;
+            }
+            writeln!(writer)?;
+        }
+    }
+    Ok(())
+}
+
+/// The encoding of the body of a PLY file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlyFormat {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum PlyScalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl PlyScalar {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "char" | "int8" => Some(Self::I8),
+            "uchar" | "uint8" => Some(Self::U8),
+            "short" | "int16" => Some(Self::I16),
+            "ushort" | "uint16" => Some(Self::U16),
+            "int" | "int32" => Some(Self::I32),
+            "uint" | "uint32" => Some(Self::U32),
+            "float" | "float32" => Some(Self::F32),
+            "double" | "float64" => Some(Self::F64),
+            _ => None,
+        }
+    }
+
+    const fn is_float(self) -> bool {
+        matches!(self, Self::F32 | Self::F64)
+    }
+
+    fn read_binary(self, reader: &mut impl Read, big_endian: bool) -> Result<f64, IoError> {
+        fn bytes<const N: usize>(reader: &mut impl Read, big_endian: bool) -> Result<[u8; N], IoError> {
+            let mut buffer = [0; N];
+            reader.read_exact(&mut buffer)?;
+            if big_endian {
+                buffer.reverse();
+            }
+            Ok(buffer)
+        }
+        Ok(match self {
+            Self::I8 => f64::from(i8::from_le_bytes(bytes(reader, big_endian)?)),
+            Self::U8 => f64::from(u8::from_le_bytes(bytes(reader, big_endian)?)),
+            Self::I16 => f64::from(i16::from_le_bytes(bytes(reader, big_endian)?)),
+            Self::U16 => f64::from(u16::from_le_bytes(bytes(reader, big_endian)?)),
+            Self::I32 => f64::from(i32::from_le_bytes(bytes(reader, big_endian)?)),
+            Self::U32 => f64::from(u32::from_le_bytes(bytes(reader, big_endian)?)),
+            Self::F32 => f64::from(f32::from_le_bytes(bytes(reader, big_endian)?)),
+            Self::F64 => f64::from_le_bytes(bytes(reader, big_endian)?),
+        })
+    }
+}
+
+#[derive(Debug)]
+struct PlyProperty {
+    name: String,
+    // The count type of a list property, `None` for scalar properties.
+    list: Option<PlyScalar>,
+    scalar: PlyScalar,
+}
+
+#[derive(Debug)]
+struct PlyElement {
+    name: String,
+    count: usize,
+    properties: Vec<PlyProperty>,
+}
+
+impl PlyElement {
+    fn position(&self, names: &[&str]) -> Option<usize> {
+        self.properties.iter().position(|property| names.contains(&property.name.as_str()))
+    }
+
+    // Like `position`, but the property must be a scalar, so that its (single) value can be read as `item[i][0]`.
+    fn scalar_position(&self, names: &[&str], line: usize) -> Result<Option<usize>, IoError> {
+        match self.position(names) {
+            Some(i) if self.properties[i].list.is_some() => Err(malformed(line, format!("`{}` must be a scalar property", self.properties[i].name))),
+            position => Ok(position),
+        }
+    }
+
+    fn color_positions(&self, line: usize) -> Result<Option<[usize; 3]>, IoError> {
+        let positions = [
+            self.scalar_position(&["red", "r"], line)?,
+            self.scalar_position(&["green", "g"], line)?,
+            self.scalar_position(&["blue", "b"], line)?,
+        ];
+        Ok(match positions {
+            [Some(r), Some(g), Some(b)] => Some([r, g, b]),
+            _ => None,
+        })
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn color(&self, item: &[Vec<f64>], positions: [usize; 3]) -> Color {
+        positions.map(|i| {
+            let value = item[i][0];
+            if self.properties[i].scalar.is_float() {
+                value as f32
+            } else {
+                (value / 255.) as f32
+            }
+        })
+    }
+}
+
+fn read_ply_header(reader: &mut impl BufRead) -> Result<(PlyFormat, Vec<PlyElement>, usize), IoError> {
+    let mut format = None;
+    let mut elements: Vec<PlyElement> = vec![];
+    let mut number = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(malformed(number, "missing `end_header`"));
+        }
+        number += 1;
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        match tokens.as_slice() {
+            ["ply"] if number == 1 => {}
+            _ if number == 1 => return Err(malformed(number, "missing `ply` magic number")),
+            ["format", name, _] => {
+                format = Some(match *name {
+                    "ascii" => PlyFormat::Ascii,
+                    "binary_little_endian" => PlyFormat::BinaryLittleEndian,
+                    "binary_big_endian" => PlyFormat::BinaryBigEndian,
+                    _ => return Err(IoError::Unsupported(format!("PLY format `{name}`"))),
+                });
+            }
+            ["comment" | "obj_info", ..] | [] => {}
+            ["element", name, count] => elements.push(PlyElement {
+                name: (*name).to_owned(),
+                count: count.parse().map_err(|_| malformed(number, format!("invalid element count `{count}`")))?,
+                properties: vec![],
+            }),
+            ["property", rest @ ..] => {
+                let element = elements.last_mut().ok_or_else(|| malformed(number, "property outside of an element"))?;
+                let scalar = |name: &str| PlyScalar::parse(name).ok_or_else(|| IoError::Unsupported(format!("PLY property type `{name}`")));
+                let property = match rest {
+                    ["list", count, item, name] => PlyProperty {
+                        name: (*name).to_owned(),
+                        list: Some(scalar(count)?),
+                        scalar: scalar(item)?,
+                    },
+                    [item, name] => PlyProperty {
+                        name: (*name).to_owned(),
+                        list: None,
+                        scalar: scalar(item)?,
+                    },
+                    _ => return Err(malformed(number, "invalid property")),
+                };
+                element.properties.push(property);
+            }
+            ["end_header"] => break,
+            _ => return Err(malformed(number, format!("unexpected header line `{}`", line.trim()))),
+        }
+    }
+    let format = format.ok_or_else(|| malformed(number, "missing `format`"))?;
+    Ok((format, elements, number))
+}
+
+// Reads all items of an element, each as a list of values per property (scalars are lists of length one).
+// Nothing is preallocated from the counts in the file, so that a bogus count ends in an error at the end of the data instead of a huge allocation.
+fn read_ply_items(reader: &mut impl BufRead, format: PlyFormat, element: &PlyElement, line: &mut usize) -> Result<Vec<Vec<Vec<f64>>>, IoError> {
+    // Binary items without properties take up no bytes, so their count would never run into the end of the data.
+    if element.count > 0 && element.properties.is_empty() {
+        return Err(malformed(*line, format!("element `{}` has no properties", element.name)));
+    }
+    let mut items = vec![];
+    for _ in 0..element.count {
+        let mut item = Vec::with_capacity(element.properties.len());
+        if format == PlyFormat::Ascii {
+            let mut text = String::new();
+            if reader.read_line(&mut text)? == 0 {
+                return Err(malformed(*line, format!("expected {} `{}` elements", element.count, element.name)));
+            }
+            *line += 1;
+            let mut tokens = text.split_whitespace().map(|token| parse_float(token, *line));
+            let mut next = || tokens.next().unwrap_or_else(|| Err(malformed(*line, "missing property value")));
+            for property in &element.properties {
+                if property.list.is_some() {
+                    let count = next()?;
+                    let count = to_index(count).ok_or_else(|| malformed(*line, format!("invalid list length `{count}`")))?;
+                    item.push((0..count).map(|_| next()).collect::<Result<_, _>>()?);
+                } else {
+                    item.push(vec![next()?]);
+                }
+            }
+        } else {
+            let big_endian = format == PlyFormat::BinaryBigEndian;
+            for property in &element.properties {
+                if let Some(count) = property.list {
+                    let count = count.read_binary(reader, big_endian)?;
+                    let count = to_index(count).ok_or_else(|| malformed(0, format!("invalid list length `{count}`")))?;
+                    item.push(
+                        (0..count)
+                            .map(|_| property.scalar.read_binary(reader, big_endian))
+                            .collect::<Result<_, _>>()?,
+                    );
+                } else {
+                    item.push(vec![property.scalar.read_binary(reader, big_endian)?]);
+                }
+            }
+        }
+        items.push(item);
+    }
+    Ok(items)
+}
+
+/// Reads a PLY file, in ASCII or binary (little or big endian) format.
+/// # Arguments
+/// * `reader` - The PLY data.
+/// # Returns
+/// * `Result<MeshData, IoError>` - The `vertex` (with optional colors), `face` (with optional colors), and `edge` elements. Edges become polylines of two vertices; other elements and properties are ignored.
+///   Integer colors are scaled from `0..=255` to `0..=1`.
+///
+/// # Example
+/// ```
+/// use hutspot::io::{read_ply, write_ply, PlyFormat};
+/// let file = std::fs::File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/cube.ply")).unwrap();
+/// let data = read_ply(std::io::BufReader::new(file)).unwrap();
+/// assert_eq!((data.positions.len(), data.faces.len()), (8, 6));
+/// assert_eq!(data.face_colors.as_ref().unwrap()[0], [1., 0., 0.]);
+/// assert!(data.to_mesh().unwrap().boundary_loops().is_empty());
+///
+/// let file = std::fs::File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/cube_binary.ply")).unwrap();
+/// assert_eq!(read_ply(std::io::BufReader::new(file)).unwrap(), data);
+///
+/// for format in [PlyFormat::Ascii, PlyFormat::BinaryLittleEndian, PlyFormat::BinaryBigEndian] {
+///     let mut buffer = vec![];
+///     write_ply(&mut buffer, &data, format).unwrap();
+///     assert_eq!(read_ply(buffer.as_slice()).unwrap(), data);
+/// }
+///
+/// let truncated = "ply\nformat ascii 1.0\nelement vertex 2\nproperty float x\nproperty float y\nproperty float z\nend_header\n0 0 0\n";
+/// assert!(read_ply(truncated.as_bytes()).is_err());
+/// let empty_list = "ply\nformat ascii 1.0\nelement vertex 1\nproperty list uchar float x\nproperty float y\nproperty float z\nend_header\n0 0 0\n";
+/// assert!(read_ply(empty_list.as_bytes()).is_err());
+/// let negative_index = "ply\nformat ascii 1.0\nelement vertex 3\nproperty float x\nproperty float y\nproperty float z\nelement face 1\nproperty list uchar int vertex_indices\nend_header\n0 0 0\n1 0 0\n0 1 0\n3 0 1 -1\n";
+/// assert!(read_ply(negative_index.as_bytes()).is_err());
+/// let no_properties = "ply\nformat binary_little_endian 1.0\nelement foo 100000000000\nend_header\n";
+/// assert!(read_ply(no_properties.as_bytes()).is_err());
+/// ```
+pub fn read_ply(mut reader: impl BufRead) -> Result<MeshData, IoError> {
+    let (format, elements, mut line) = read_ply_header(&mut reader)?;
+    let mut data = MeshData::default();
+    let mut face_lines = vec![];
+
+    for element in &elements {
+        let start = line;
+        let items = read_ply_items(&mut reader, format, element, &mut line)?;
+        let item_line = |i: usize| if format == PlyFormat::Ascii { start + i + 1 } else { 0 };
+        let vertex_index = |value: f64, i: usize| to_index(value).ok_or_else(|| malformed(item_line(i), format!("invalid vertex index `{value}`")));
+        match element.name.as_str() {
+            "vertex" => {
+                let (Some(x), Some(y), Some(z)) = (
+                    element.scalar_position(&["x"], start)?,
+                    element.scalar_position(&["y"], start)?,
+                    element.scalar_position(&["z"], start)?,
+                ) else {
+                    return Err(malformed(start, "vertices need `x`, `y`, and `z` properties"));
+                };
+                data.positions = items.iter().map(|item| Vector3D::new(item[x][0], item[y][0], item[z][0])).collect();
+                data.vertex_colors = element
+                    .color_positions(start)?
+                    .map(|colors| items.iter().map(|item| element.color(item, colors)).collect());
+            }
+            "face" => {
+                let indices = element
+                    .position(&["vertex_indices", "vertex_index"])
+                    .ok_or_else(|| malformed(start, "faces need a `vertex_indices` property"))?;
+                face_lines = (0..items.len()).map(item_line).collect();
+                data.faces = items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| item[indices].iter().map(|&value| vertex_index(value, i)).collect())
+                    .collect::<Result<_, _>>()?;
+                data.face_colors = element
+                    .color_positions(start)?
+                    .map(|colors| items.iter().map(|item| element.color(item, colors)).collect());
+            }
+            "edge" => {
+                let (Some(u), Some(v)) = (
+                    element.scalar_position(&["vertex1"], start)?,
+                    element.scalar_position(&["vertex2"], start)?,
+                ) else {
+                    return Err(malformed(start, "edges need `vertex1` and `vertex2` properties"));
+                };
+                data.polylines = items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| Ok(vec![vertex_index(item[u][0], i)?, vertex_index(item[v][0], i)?]))
+                    .collect::<Result<_, IoError>>()?;
+            }
+            _ => {}
+        }
+    }
+
+    for (face, &number) in data.faces.iter().zip(&face_lines) {
+        if face.len() < 3 {
+            return Err(malformed(number, "a face needs at least three vertices"));
+        }
+        if let Some(index) = face.iter().find(|&&i| i >= data.positions.len()) {
+            return Err(malformed(number, format!("invalid vertex index `{index}`")));
+        }
+    }
+    if let Some(index) = data.polylines.iter().flatten().find(|&&i| i >= data.positions.len()) {
+        return Err(malformed(0, format!("invalid vertex index `{index}` in edge")));
+    }
+    Ok(data)
+}
+
+/// Writes a PLY file.
+/// # Arguments
+/// * `writer` - The destination.
+/// * `data` - The mesh to write. Positions are written as doubles and colors as bytes. Polylines are written as `edge` elements (one per segment).
+/// * `format` - The encoding of the body.
+/// # Returns
+/// * `Result<(), IoError>` - An `IoError::InvalidData` (before anything is written) if an index is out of range or the number of colors does not match.
+pub fn write_ply(writer: impl Write, data: &MeshData, format: PlyFormat) -> Result<(), IoError> {
+    data.validate()?;
+    let mut writer = BufWriter::new(writer);
+    let format_name = match format {
+        PlyFormat::Ascii => "ascii",
+        PlyFormat::BinaryLittleEndian => "binary_little_endian",
+        PlyFormat::BinaryBigEndian => "binary_big_endian",
+    };
+    let segments = data
+        .polylines
+        .iter()
+        .flat_map(|polyline| polyline.windows(2).map(|w| [w[0], w[1]]))
+        .collect::<Vec<_>>();
+    let index = |i: usize| u32::try_from(i).map_err(|_| IoError::Unsupported(format!("vertex index {i} does not fit in 32 bits")));
+
+    writeln!(writer, "ply\nformat {format_name} 1.0\ncomment written by hutspot")?;
+    writeln!(writer, "element vertex {}", data.positions.len())?;
+    writeln!(writer, "property double x\nproperty double y\nproperty double z")?;
+    if data.vertex_colors.is_some() {
+        writeln!(writer, "property uchar red\nproperty uchar green\nproperty uchar blue")?;
+    }
+    writeln!(writer, "element face {}\nproperty list uint uint vertex_indices", data.faces.len())?;
+    if data.face_colors.is_some() {
+        writeln!(writer, "property uchar red\nproperty uchar green\nproperty uchar blue")?;
+    }
+    if !segments.is_empty() {
+        writeln!(writer, "element edge {}\nproperty uint vertex1\nproperty uint vertex2", segments.len())?;
+    }
+    writeln!(writer, "end_header")?;
+
+    // Writes one item: doubles, then lists of indices, then a color.
+    let mut write_item = |doubles: &[f64], indices: &[usize], color: Option<&Color>| -> Result<(), IoError> {
+        let color = color.map(|c| c.map(color_to_byte));
+        let mut tokens = vec![];
+        if format == PlyFormat::Ascii {
+            tokens.extend(doubles.iter().map(ToString::to_string));
+            tokens.extend(indices.iter().map(ToString::to_string));
+            tokens.extend(color.iter().flatten().map(ToString::to_string));
+            writeln!(writer, "{}", tokens.join(" "))?;
+        } else {
+            let big_endian = format == PlyFormat::BinaryBigEndian;
+            for &value in doubles {
+                writer.write_all(&if big_endian { value.to_be_bytes() } else { value.to_le_bytes() })?;
+            }
+            for &i in indices {
+                let i = index(i)?;
+                writer.write_all(&if big_endian { i.to_be_bytes() } else { i.to_le_bytes() })?;
+            }
+            writer.write_all(color.as_ref().map_or(&[][..], |c| &c[..]))?;
+        }
+        Ok(())
+    };
+
+    for (i, p) in data.positions.iter().enumerate() {
+        write_item(&[p.x, p.y, p.z], &[], data.vertex_colors.as_ref().map(|colors| &colors[i]))?;
+    }
+    for (i, face) in data.faces.iter().enumerate() {
+        write_item(
+            &[],
+            &[[face.len()].as_slice(), face].concat(),
+            data.face_colors.as_ref().map(|colors| &colors[i]),
+        )?;
+    }
+    for segment in &segments {
+        write_item(&[], segment, None)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads an STL file, in ASCII or binary format (detected automatically).
+/// # Arguments
+/// * `reader` - The STL data.
+/// # Returns
+/// * `Result<MeshData, IoError>` - The triangles, with vertices at identical positions merged. Facet normals are ignored.
+///
+/// # Example
+/// ```
+/// use hutspot::io::{read_stl, write_stl};
+/// let data = read_stl(std::fs::File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/tetrahedron.stl")).unwrap()).unwrap();
+/// assert_eq!((data.positions.len(), data.faces.len()), (4, 4));
+/// assert!(data.to_mesh().unwrap().boundary_loops().is_empty());
+///
+/// let binary = read_stl(std::fs::File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/tetrahedron_binary.stl")).unwrap()).unwrap();
+/// assert_eq!(binary, data);
+///
+/// for binary in [false, true] {
+///     let mut buffer = vec![];
+///     write_stl(&mut buffer, &data, binary).unwrap();
+///     assert_eq!(read_stl(buffer.as_slice()).unwrap(), data);
+/// }
+///
+/// assert!(read_stl("solid broken\nfacet normal 0 0 1\nouter loop\nvertex 0 0 0\nendloop\nendfacet\nendsolid\n".as_bytes()).is_err());
+/// assert!(read_stl("solid open\nfacet normal 0 0 1\nouter loop\nvertex 0 0 0\nvertex 1 0 0\nvertex 0 1 0\nendloop\nendsolid\n".as_bytes()).is_err());
+/// ```
+pub fn read_stl(mut reader: impl Read) -> Result<MeshData, IoError> {
+    let mut bytes = vec![];
+    reader.read_to_end(&mut bytes)?;
+
+    let mut data = MeshData::default();
+    let mut welded = HashMap::new();
+    let mut add_vertex = |data: &mut MeshData, p: Vector3D| {
+        // Adding zero maps -0 to +0, so that both are merged.
+        let key = [p.x + 0., p.y + 0., p.z + 0.].map(f64::to_bits);
+        *welded.entry(key).or_insert_with(|| {
+            data.positions.push(p);
+            data.positions.len() - 1
+        })
+    };
+
+    let nr_triangles = bytes
+        .get(80..84)
+        .map(|count| u32::from_le_bytes([count[0], count[1], count[2], count[3]]) as usize);
+    if let Some(nr_triangles) = nr_triangles.filter(|&n| bytes.len() == 84 + 50 * n) {
+        for triangle in bytes[84..].chunks_exact(50).take(nr_triangles) {
+            let value = |i: usize| {
+                f64::from(f32::from_le_bytes([
+                    triangle[4 * i],
+                    triangle[4 * i + 1],
+                    triangle[4 * i + 2],
+                    triangle[4 * i + 3],
+                ]))
+            };
+            let face = (1..4)
+                .map(|v| add_vertex(&mut data, Vector3D::new(value(3 * v), value(3 * v + 1), value(3 * v + 2))))
+                .collect();
+            data.faces.push(face);
+        }
+        return Ok(data);
+    }
+
+    let text = std::str::from_utf8(&bytes).map_err(|_| malformed(0, "neither valid binary nor ASCII STL"))?;
+    if !text.trim_start().starts_with("solid") {
+        return Err(malformed(0, "neither valid binary nor ASCII STL"));
+    }
+    // The open facet, with the line where it starts.
+    let mut facet: Option<(usize, Vec<usize>)> = None;
+    for (index, line) in text.lines().enumerate() {
+        let number = index + 1;
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        match tokens.as_slice() {
+            ["facet", ..] => {
+                if let Some((start, _)) = facet.replace((number, vec![])) {
+                    return Err(malformed(number, format!("facet starts before the facet of line {start} ends")));
+                }
+            }
+            ["vertex", x, y, z] => {
+                let p = Vector3D::new(parse_float(x, number)?, parse_float(y, number)?, parse_float(z, number)?);
+                let vertex = add_vertex(&mut data, p);
+                facet
+                    .as_mut()
+                    .ok_or_else(|| malformed(number, "vertex outside of a facet"))?
+                    .1
+                    .push(vertex);
+            }
+            ["vertex", ..] => return Err(malformed(number, "a vertex needs three coordinates")),
+            ["endfacet"] => match facet.take() {
+                Some((_, face)) if face.len() == 3 => data.faces.push(face),
+                _ => return Err(malformed(number, "a facet needs exactly three vertices")),
+            },
+            ["endsolid", ..] => {
+                if let Some((start, _)) = facet {
+                    return Err(malformed(start, "facet without `endfacet`"));
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some((start, _)) = facet {
+        return Err(malformed(start, "facet without `endfacet`"));
+    }
+    Ok(data)
+}
+
+/// Writes an STL file. Faces with more than three vertices are triangulated as fans; polylines and colors are not supported by the format and are ignored.
+/// # Arguments
+/// * `writer` - The destination.
+/// * `data` - The mesh to write.
+/// * `binary` - Whether to write binary STL (with single-precision coordinates) instead of ASCII STL.
+/// # Returns
+/// * `Result<(), IoError>` - An `IoError::InvalidData` (before anything is written) if an index is out of range or the number of colors does not match.
+pub fn write_stl(writer: impl Write, data: &MeshData, binary: bool) -> Result<(), IoError> {
+    data.validate()?;
+    let mut writer = BufWriter::new(writer);
+    let triangles = data.triangles().collect::<Vec<_>>();
+    let normal = |[a, b, c]: [usize; 3]| {
+        let n = (data.positions[b] - data.positions[a]).cross(&(data.positions[c] - data.positions[a]));
+        n.try_normalize(0.).unwrap_or(n)
+    };
+
+    if binary {
+        let mut header = [0; 80];
+        header[..18].copy_from_slice(b"written by hutspot");
+        writer.write_all(&header)?;
+        let count = u32::try_from(triangles.len()).map_err(|_| IoError::Unsupported("more than 2^32 triangles".to_owned()))?;
+        writer.write_all(&count.to_le_bytes())?;
+        for triangle in triangles {
+            let n = normal(triangle);
+            for v in [n, data.positions[triangle[0]], data.positions[triangle[1]], data.positions[triangle[2]]] {
+                for value in v.iter() {
+                    #[allow(clippy::cast_possible_truncation)]
+                    writer.write_all(&(*value as f32).to_le_bytes())?;
+                }
+            }
+            writer.write_all(&[0, 0])?;
+        }
+    } else {
+        writeln!(writer, "solid hutspot")?;
+        for triangle in triangles {
+            let n = normal(triangle);
+            writeln!(writer, "facet normal {} {} {}\nouter loop", n.x, n.y, n.z)?;
+            for v in triangle {
+                let p = data.positions[v];
+                writeln!(writer, "vertex {} {} {}", p.x, p.y, p.z)?;
+            }
+            writeln!(writer, "endloop\nendfacet")?;
+        }
+        writeln!(writer, "endsolid hutspot")?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn extension(path: &Path) -> Result<String, IoError> {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase)
+        .ok_or_else(|| IoError::Unsupported(format!("missing file extension in `{}`", path.display())))
+}
+
+/// Reads an OBJ, PLY, or STL file, depending on its extension.
+pub fn read_file(path: impl AsRef<Path>) -> Result<MeshData, IoError> {
+    let path = path.as_ref();
+    let extension = extension(path)?;
+    let reader = BufReader::new(File::open(path)?);
+    match extension.as_str() {
+        "obj" => read_obj(reader),
+        "ply" => read_ply(reader),
+        "stl" => read_stl(reader),
+        _ => Err(IoError::Unsupported(format!("file extension `{extension}`"))),
+    }
+}
+
+/// Writes an OBJ, PLY (binary), or STL (binary) file, depending on its extension.
+pub fn write_file(path: impl AsRef<Path>, data: &MeshData) -> Result<(), IoError> {
+    let path = path.as_ref();
+    let extension = extension(path)?;
+    if !["obj", "ply", "stl"].contains(&extension.as_str()) {
+        return Err(IoError::Unsupported(format!("file extension `{extension}`")));
+    }
+    let writer = BufWriter::new(File::create(path)?);
+    match extension.as_str() {
+        "obj" => write_obj(writer, data),
+        "ply" => write_ply(writer, data, PlyFormat::BinaryLittleEndian),
+        _ => write_stl(writer, data, true),
+    }
+}
@@ -1,9 +1,14 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
 #![allow(clippy::missing_panics_doc, clippy::missing_errors_doc)]
+pub mod bvh;
 pub mod color;
 pub mod consts;
 pub mod draw;
+pub mod geodesic;
 pub mod geom;
 pub mod graph;
+pub mod io;
 pub mod math;
+pub mod mesh;
+pub mod predicates;
 pub mod timer;
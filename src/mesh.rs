@@ -0,0 +1,415 @@
+use crate::math::wrap_pairs;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Identifier of a vertex in a `Mesh`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct VertID(pub usize);
+
+/// Identifier of a (directed) halfedge in a `Mesh`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EdgeID(pub usize);
+
+/// Identifier of a face in a `Mesh`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FaceID(pub usize);
+
+/// Errors that can occur when building a `Mesh` from a list of faces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MeshError {
+    /// The face (by index) has fewer than three vertices, or repeats a vertex.
+    DegenerateFace(usize),
+    /// The face (by index) refers to a vertex that does not exist.
+    VertexOutOfBounds(usize, usize),
+    /// The directed edge is used by more than one face (non-manifold edge or inconsistent face orientation).
+    NonManifoldEdge(usize, usize),
+    /// The vertex is shared by multiple fans of faces that are not connected through its edges.
+    NonManifoldVertex(usize),
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DegenerateFace(face) => write!(f, "face {face} has fewer than three distinct vertices"),
+            Self::VertexOutOfBounds(face, vert) => write!(f, "face {face} refers to non-existing vertex {vert}"),
+            Self::NonManifoldEdge(u, v) => write!(
+                f,
+                "edge ({u}, {v}) is used by more than one face (non-manifold or inconsistently oriented)"
+            ),
+            Self::NonManifoldVertex(vert) => write!(f, "vertex {vert} is non-manifold"),
+        }
+    }
+}
+
+impl std::error::Error for MeshError {}
+
+#[derive(Debug, Clone)]
+struct Vertex<V> {
+    edge: Option<EdgeID>,
+    data: V,
+}
+
+#[derive(Debug, Clone)]
+struct HalfEdge<E> {
+    root: VertID,
+    twin: EdgeID,
+    next: EdgeID,
+    prev: EdgeID,
+    face: Option<FaceID>,
+    data: E,
+}
+
+#[derive(Debug, Clone)]
+struct Face<F> {
+    edge: EdgeID,
+    data: F,
+}
+
+/// A polygon mesh in halfedge (doubly connected edge list) representation, storing data of type `V`, `E`, and `F` per vertex, halfedge, and face.
+///
+/// Every face is a loop of halfedges (counterclockwise if the input faces are), and every halfedge has a twin in the opposite direction.
+/// Halfedges on the boundary of the mesh have no face; they are linked into boundary loops, so that all traversals also work on meshes with boundary.
+///
+/// # Example
+/// ```
+/// use hutspot::geom::Vector3D;
+/// use hutspot::graph::find_shortest_path;
+/// use hutspot::mesh::{Mesh, VertID};
+/// use ordered_float::OrderedFloat;
+///
+/// // A square split into two triangles.
+/// let positions = vec![Vector3D::new(0., 0., 0.), Vector3D::new(1., 0., 0.), Vector3D::new(1., 1., 0.), Vector3D::new(0., 1., 0.)];
+/// let mesh: Mesh<Vector3D> = Mesh::from_faces(positions, &[vec![0, 1, 2], vec![0, 2, 3]]).unwrap();
+/// assert_eq!((mesh.nr_verts(), mesh.nr_edges(), mesh.nr_faces()), (4, 10, 2));
+/// assert_eq!(mesh.vneighbors(VertID(0)), vec![VertID(1), VertID(2), VertID(3)]);
+/// assert_eq!(mesh.boundary_loops().len(), 1);
+///
+/// // The adjacency plugs directly into the `graph` module.
+/// let (path, cost) = find_shortest_path(
+///     VertID(1),
+///     VertID(3),
+///     |v| mesh.vneighbors(v),
+///     |a, b| OrderedFloat((mesh.vert_data(a) - mesh.vert_data(b)).norm()),
+/// )
+/// .unwrap();
+/// assert_eq!(path, vec![VertID(1), VertID(2), VertID(3)]);
+/// assert_eq!(cost, OrderedFloat(2.));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Mesh<V = (), E = (), F = ()> {
+    verts: Vec<Vertex<V>>,
+    edges: Vec<HalfEdge<E>>,
+    faces: Vec<Face<F>>,
+}
+
+impl<V, E: Default, F: Default> Mesh<V, E, F> {
+    /// Builds a mesh from per-vertex data and faces given as loops of vertex indices.
+    /// # Arguments
+    /// * `verts` - The data of each vertex (e.g. its position).
+    /// * `faces` - The faces, each a list of (at least three) indices into `verts`.
+    /// # Returns
+    /// * `Result<Self, MeshError>` - The mesh, or an error if the faces do not describe an oriented manifold (with boundary).
+    ///
+    /// # Example
+    /// ```
+    /// use hutspot::mesh::{Mesh, MeshError};
+    /// let mesh: Result<Mesh, _> = Mesh::from_faces(vec![(); 4], &[vec![0, 1, 2], vec![0, 1, 3]]);
+    /// assert_eq!(mesh.unwrap_err(), MeshError::NonManifoldEdge(0, 1));
+    ///
+    /// // Two triangles that only share vertex 0.
+    /// let mesh: Result<Mesh, _> = Mesh::from_faces(vec![(); 5], &[vec![0, 1, 2], vec![0, 3, 4]]);
+    /// assert_eq!(mesh.unwrap_err(), MeshError::NonManifoldVertex(0));
+    ///
+    /// let mesh: Result<Mesh, _> = Mesh::from_faces(vec![(); 3], &[vec![0, 1, 3]]);
+    /// assert_eq!(mesh.unwrap_err(), MeshError::VertexOutOfBounds(0, 3));
+    /// ```
+    pub fn from_faces(verts: Vec<V>, faces: &[Vec<usize>]) -> Result<Self, MeshError> {
+        let nr_verts = verts.len();
+        let mut mesh = Self {
+            verts: verts.into_iter().map(|data| Vertex { edge: None, data }).collect(),
+            edges: vec![],
+            faces: Vec::with_capacity(faces.len()),
+        };
+
+        let mut directed = HashMap::new();
+        for (face_index, face) in faces.iter().enumerate() {
+            if let Some(&vert) = face.iter().find(|&&vert| vert >= nr_verts) {
+                return Err(MeshError::VertexOutOfBounds(face_index, vert));
+            }
+            if face.len() < 3 || (1..face.len()).any(|i| face[..i].contains(&face[i])) {
+                return Err(MeshError::DegenerateFace(face_index));
+            }
+
+            let first = mesh.edges.len();
+            let n = face.len();
+            for (k, (u, v)) in wrap_pairs(face).into_iter().enumerate() {
+                if directed.insert((u, v), EdgeID(first + k)).is_some() {
+                    return Err(MeshError::NonManifoldEdge(u, v));
+                }
+                mesh.edges.push(HalfEdge {
+                    root: VertID(u),
+                    twin: EdgeID(usize::MAX),
+                    next: EdgeID(first + (k + 1) % n),
+                    prev: EdgeID(first + (k + n - 1) % n),
+                    face: Some(FaceID(face_index)),
+                    data: E::default(),
+                });
+            }
+            mesh.faces.push(Face {
+                edge: EdgeID(first),
+                data: F::default(),
+            });
+        }
+
+        // Pair up halfedges, creating boundary halfedges where a twin is missing.
+        let mut boundary_out = HashMap::new();
+        for e in 0..mesh.edges.len() {
+            let u = mesh.edges[e].root.0;
+            let v = mesh.edges[mesh.edges[e].next.0].root.0;
+            if let Some(&twin) = directed.get(&(v, u)) {
+                mesh.edges[e].twin = twin;
+                continue;
+            }
+            let twin = EdgeID(mesh.edges.len());
+            mesh.edges[e].twin = twin;
+            mesh.edges.push(HalfEdge {
+                root: VertID(v),
+                twin: EdgeID(e),
+                next: EdgeID(usize::MAX),
+                prev: EdgeID(usize::MAX),
+                face: None,
+                data: E::default(),
+            });
+            if boundary_out.insert(v, twin).is_some() {
+                return Err(MeshError::NonManifoldVertex(v));
+            }
+        }
+        for &boundary in boundary_out.values() {
+            let next = boundary_out[&mesh.toor(boundary).0];
+            mesh.edges[boundary.0].next = next;
+            mesh.edges[next.0].prev = boundary;
+        }
+
+        // A boundary vertex starts its one-ring at the outgoing halfedge whose twin lies on the boundary, so the one-ring is one counterclockwise sweep.
+        let mut nr_outgoing = vec![0; nr_verts];
+        for e in mesh.edge_ids() {
+            let root = mesh.root(e);
+            nr_outgoing[root.0] += 1;
+            if mesh.verts[root.0].edge.is_none() || mesh.face_of(mesh.twin(e)).is_none() {
+                mesh.verts[root.0].edge = Some(e);
+            }
+        }
+        if let Some(v) = mesh.vert_ids().find(|&v| mesh.outgoing(v).len() != nr_outgoing[v.0]) {
+            return Err(MeshError::NonManifoldVertex(v.0));
+        }
+
+        Ok(mesh)
+    }
+}
+
+impl<V, E, F> Mesh<V, E, F> {
+    #[must_use]
+    pub const fn nr_verts(&self) -> usize {
+        self.verts.len()
+    }
+
+    /// The number of halfedges (twice the number of edges).
+    #[must_use]
+    pub const fn nr_edges(&self) -> usize {
+        self.edges.len()
+    }
+
+    #[must_use]
+    pub const fn nr_faces(&self) -> usize {
+        self.faces.len()
+    }
+
+    pub fn vert_ids(&self) -> impl Iterator<Item = VertID> + use<V, E, F> {
+        (0..self.verts.len()).map(VertID)
+    }
+
+    pub fn edge_ids(&self) -> impl Iterator<Item = EdgeID> + use<V, E, F> {
+        (0..self.edges.len()).map(EdgeID)
+    }
+
+    pub fn face_ids(&self) -> impl Iterator<Item = FaceID> + use<V, E, F> {
+        (0..self.faces.len()).map(FaceID)
+    }
+
+    #[must_use]
+    pub fn vert_data(&self, v: VertID) -> &V {
+        &self.verts[v.0].data
+    }
+
+    pub fn vert_data_mut(&mut self, v: VertID) -> &mut V {
+        &mut self.verts[v.0].data
+    }
+
+    #[must_use]
+    pub fn edge_data(&self, e: EdgeID) -> &E {
+        &self.edges[e.0].data
+    }
+
+    pub fn edge_data_mut(&mut self, e: EdgeID) -> &mut E {
+        &mut self.edges[e.0].data
+    }
+
+    #[must_use]
+    pub fn face_data(&self, f: FaceID) -> &F {
+        &self.faces[f.0].data
+    }
+
+    pub fn face_data_mut(&mut self, f: FaceID) -> &mut F {
+        &mut self.faces[f.0].data
+    }
+
+    /// The vertex from which halfedge `e` starts.
+    #[must_use]
+    pub fn root(&self, e: EdgeID) -> VertID {
+        self.edges[e.0].root
+    }
+
+    /// The vertex at which halfedge `e` ends.
+    #[must_use]
+    pub fn toor(&self, e: EdgeID) -> VertID {
+        self.root(self.twin(e))
+    }
+
+    /// Both endpoints of halfedge `e`.
+    #[must_use]
+    pub fn endpoints(&self, e: EdgeID) -> (VertID, VertID) {
+        (self.root(e), self.toor(e))
+    }
+
+    #[must_use]
+    pub fn twin(&self, e: EdgeID) -> EdgeID {
+        self.edges[e.0].twin
+    }
+
+    #[must_use]
+    pub fn next(&self, e: EdgeID) -> EdgeID {
+        self.edges[e.0].next
+    }
+
+    #[must_use]
+    pub fn prev(&self, e: EdgeID) -> EdgeID {
+        self.edges[e.0].prev
+    }
+
+    /// The face to the left of halfedge `e`, or `None` if `e` lies on the boundary.
+    #[must_use]
+    pub fn face_of(&self, e: EdgeID) -> Option<FaceID> {
+        self.edges[e.0].face
+    }
+
+    /// The halfedge from `u` to `v`, if it exists.
+    #[must_use]
+    pub fn edge_between(&self, u: VertID, v: VertID) -> Option<EdgeID> {
+        self.outgoing(u).into_iter().find(|&e| self.toor(e) == v)
+    }
+
+    #[must_use]
+    pub fn is_boundary_edge(&self, e: EdgeID) -> bool {
+        self.face_of(e).is_none() || self.face_of(self.twin(e)).is_none()
+    }
+
+    #[must_use]
+    pub fn is_boundary_vert(&self, v: VertID) -> bool {
+        self.verts[v.0].edge.is_some_and(|e| self.face_of(self.twin(e)).is_none())
+    }
+
+    /// The halfedges leaving vertex `v`, in counterclockwise order (from boundary to boundary, if `v` lies on it).
+    #[must_use]
+    pub fn outgoing(&self, v: VertID) -> Vec<EdgeID> {
+        let Some(start) = self.verts[v.0].edge else {
+            return vec![];
+        };
+        let mut edges = vec![start];
+        let mut e = self.twin(self.prev(start));
+        while e != start {
+            edges.push(e);
+            e = self.twin(self.prev(e));
+        }
+        edges
+    }
+
+    /// The vertices adjacent to vertex `v` (its one-ring), in counterclockwise order.
+    #[must_use]
+    pub fn vneighbors(&self, v: VertID) -> Vec<VertID> {
+        self.outgoing(v).into_iter().map(|e| self.toor(e)).collect()
+    }
+
+    /// The faces incident to vertex `v`, in counterclockwise order.
+    #[must_use]
+    pub fn vfaces(&self, v: VertID) -> Vec<FaceID> {
+        self.outgoing(v).into_iter().filter_map(|e| self.face_of(e)).collect()
+    }
+
+    /// The halfedges of the loop through halfedge `e` (a face loop or a boundary loop).
+    #[must_use]
+    pub fn edge_loop(&self, e: EdgeID) -> Vec<EdgeID> {
+        let mut edges = vec![e];
+        let mut current = self.next(e);
+        while current != e {
+            edges.push(current);
+            current = self.next(current);
+        }
+        edges
+    }
+
+    /// The halfedges around face `f`.
+    #[must_use]
+    pub fn fedges(&self, f: FaceID) -> Vec<EdgeID> {
+        self.edge_loop(self.faces[f.0].edge)
+    }
+
+    /// The vertices around face `f`, in the order of the input face.
+    #[must_use]
+    pub fn corners(&self, f: FaceID) -> Vec<VertID> {
+        self.fedges(f).into_iter().map(|e| self.root(e)).collect()
+    }
+
+    /// The faces sharing an edge with face `f` (its neighbors in the dual graph).
+    #[must_use]
+    pub fn fneighbors(&self, f: FaceID) -> Vec<FaceID> {
+        self.fedges(f).into_iter().filter_map(|e| self.face_of(self.twin(e))).collect()
+    }
+
+    /// The boundary loops of the mesh, each as a loop of boundary halfedges (which have no face).
+    #[must_use]
+    pub fn boundary_loops(&self) -> Vec<Vec<EdgeID>> {
+        let mut visited = vec![false; self.edges.len()];
+        let mut loops = vec![];
+        for e in self.edge_ids() {
+            if visited[e.0] || self.face_of(e).is_some() {
+                continue;
+            }
+            let boundary = self.edge_loop(e);
+            for &b in &boundary {
+                visited[b.0] = true;
+            }
+            loops.push(boundary);
+        }
+        loops
+    }
+
+    /// Returns a neighbor function over the vertices, for use with the `graph` module.
+    pub fn vert_neighbor_function(&self) -> impl Fn(VertID) -> Vec<VertID> + '_ {
+        |v| self.vneighbors(v)
+    }
+
+    /// Returns a neighbor function over the faces (the dual graph), for use with the `graph` module.
+    ///
+    /// # Example
+    /// ```
+    /// use hutspot::graph::find_ccs;
+    /// use hutspot::mesh::{FaceID, Mesh};
+    /// // Two separate triangles.
+    /// let mesh: Mesh = Mesh::from_faces(vec![(); 6], &[vec![0, 1, 2], vec![3, 4, 5]]).unwrap();
+    /// let faces = mesh.face_ids().collect::<Vec<_>>();
+    /// assert_eq!(find_ccs(&faces, mesh.face_neighbor_function()).len(), 2);
+    /// ```
+    pub fn face_neighbor_function(&self) -> impl Fn(FaceID) -> Vec<FaceID> + '_ {
+        |f| self.fneighbors(f)
+    }
+}
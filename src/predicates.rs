@@ -0,0 +1,417 @@
+// The error bounds below assume every product and difference is rounded separately, so fused multiply-adds must not be used in the fast paths.
+// Variable names follow Shewchuk's `predicates.c` (e.g. `bdxcdy` is `bdx * cdy`).
+#![allow(clippy::suboptimal_flops, clippy::similar_names, clippy::many_single_char_names)]
+
+use crate::geom::{Orientation, Vector2D, Vector3D};
+
+// Half an ulp of 1.0, the machine epsilon used in Shewchuk's error analysis (note that `f64::EPSILON` is twice as large).
+const EPSILON: f64 = f64::EPSILON * 0.5;
+const CCW_ERRBOUND_A: f64 = (3.0 + 16.0 * EPSILON) * EPSILON;
+const O3D_ERRBOUND_A: f64 = (7.0 + 56.0 * EPSILON) * EPSILON;
+const ICC_ERRBOUND_A: f64 = (10.0 + 96.0 * EPSILON) * EPSILON;
+const ISP_ERRBOUND_A: f64 = (16.0 + 224.0 * EPSILON) * EPSILON;
+
+/// A floating-point expansion: a sum of non-overlapping components ordered by increasing magnitude, without zeros.
+/// The sign of the represented (exact) value is the sign of its last component.
+type Expansion = Vec<f64>;
+
+#[inline]
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    let b_virtual = x - a;
+    let a_virtual = x - b_virtual;
+    (x, (a - a_virtual) + (b - b_virtual))
+}
+
+#[inline]
+fn fast_two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    (x, b - (x - a))
+}
+
+#[inline]
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let x = a * b;
+    (x, a.mul_add(b, -x))
+}
+
+fn push_nonzero(h: &mut Expansion, value: f64) {
+    if value != 0.0 {
+        h.push(value);
+    }
+}
+
+fn difference(a: f64, b: f64) -> Expansion {
+    let (x, y) = two_sum(a, -b);
+    let mut h = Expansion::with_capacity(2);
+    push_nonzero(&mut h, y);
+    push_nonzero(&mut h, x);
+    h
+}
+
+fn grow(e: &[f64], b: f64) -> Expansion {
+    let mut h = Expansion::with_capacity(e.len() + 1);
+    let mut q = b;
+    for &component in e {
+        let (sum, error) = two_sum(q, component);
+        push_nonzero(&mut h, error);
+        q = sum;
+    }
+    push_nonzero(&mut h, q);
+    h
+}
+
+fn sum(e: &[f64], f: &[f64]) -> Expansion {
+    f.iter().fold(e.to_vec(), |acc, &component| grow(&acc, component))
+}
+
+fn negate(e: &[f64]) -> Expansion {
+    e.iter().map(|component| -component).collect()
+}
+
+fn diff(e: &[f64], f: &[f64]) -> Expansion {
+    sum(e, &negate(f))
+}
+
+fn scale(e: &[f64], b: f64) -> Expansion {
+    let mut h = Expansion::with_capacity(2 * e.len());
+    let Some((&first, rest)) = e.split_first() else {
+        return h;
+    };
+    let (mut q, error) = two_product(first, b);
+    push_nonzero(&mut h, error);
+    for &component in rest {
+        let (product_hi, product_lo) = two_product(component, b);
+        let (partial, error) = two_sum(q, product_lo);
+        push_nonzero(&mut h, error);
+        let (next, error) = fast_two_sum(product_hi, partial);
+        push_nonzero(&mut h, error);
+        q = next;
+    }
+    push_nonzero(&mut h, q);
+    h
+}
+
+fn product(e: &[f64], f: &[f64]) -> Expansion {
+    f.iter().fold(Expansion::new(), |acc, &component| sum(&acc, &scale(e, component)))
+}
+
+fn sign(e: &[f64]) -> f64 {
+    e.last().copied().unwrap_or(0.0)
+}
+
+const fn orientation_from_sign(value: f64) -> Orientation {
+    if value > 0. {
+        Orientation::CCW
+    } else if value < 0. {
+        Orientation::CW
+    } else {
+        Orientation::C
+    }
+}
+
+/// Determinant of `[a - c; b - c]`, with a correct sign.
+fn orient2d_det(a: Vector2D, b: Vector2D, c: Vector2D) -> f64 {
+    let det_left = (a.x - c.x) * (b.y - c.y);
+    let det_right = (a.y - c.y) * (b.x - c.x);
+    let det = det_left - det_right;
+    if det.abs() > CCW_ERRBOUND_A * (det_left.abs() + det_right.abs()) {
+        return det;
+    }
+
+    let (acx, acy) = (difference(a.x, c.x), difference(a.y, c.y));
+    let (bcx, bcy) = (difference(b.x, c.x), difference(b.y, c.y));
+    sign(&diff(&product(&acx, &bcy), &product(&acy, &bcx)))
+}
+
+/// Determinant of `[u.0 - u.1; v.0 - v.1; w.0 - w.1]`, with a correct sign.
+fn triple_product_det(u: (Vector3D, Vector3D), v: (Vector3D, Vector3D), w: (Vector3D, Vector3D)) -> f64 {
+    let (ux, uy, uz) = (u.0.x - u.1.x, u.0.y - u.1.y, u.0.z - u.1.z);
+    let (vx, vy, vz) = (v.0.x - v.1.x, v.0.y - v.1.y, v.0.z - v.1.z);
+    let (wx, wy, wz) = (w.0.x - w.1.x, w.0.y - w.1.y, w.0.z - w.1.z);
+
+    let (vywz, vzwy) = (vy * wz, vz * wy);
+    let (vzwx, vxwz) = (vz * wx, vx * wz);
+    let (vxwy, vywx) = (vx * wy, vy * wx);
+    let det = ux * (vywz - vzwy) + uy * (vzwx - vxwz) + uz * (vxwy - vywx);
+    let permanent = ux.abs() * (vywz.abs() + vzwy.abs()) + uy.abs() * (vzwx.abs() + vxwz.abs()) + uz.abs() * (vxwy.abs() + vywx.abs());
+    if det.abs() > O3D_ERRBOUND_A * permanent {
+        return det;
+    }
+
+    let [ux, uy, uz] = [0, 1, 2].map(|i| difference(u.0[i], u.1[i]));
+    let [vx, vy, vz] = [0, 1, 2].map(|i| difference(v.0[i], v.1[i]));
+    let [wx, wy, wz] = [0, 1, 2].map(|i| difference(w.0[i], w.1[i]));
+    let x = product(&ux, &diff(&product(&vy, &wz), &product(&vz, &wy)));
+    let y = product(&uy, &diff(&product(&vz, &wx), &product(&vx, &wz)));
+    let z = product(&uz, &diff(&product(&vx, &wy), &product(&vy, &wx)));
+    sign(&sum(&sum(&x, &y), &z))
+}
+
+/// Lifted determinant for the in-circle test, positive if `d` lies inside the circle through counterclockwise `a`, `b`, `c`, with a correct sign.
+fn incircle_det(a: Vector2D, b: Vector2D, c: Vector2D, d: Vector2D) -> f64 {
+    let (adx, ady) = (a.x - d.x, a.y - d.y);
+    let (bdx, bdy) = (b.x - d.x, b.y - d.y);
+    let (cdx, cdy) = (c.x - d.x, c.y - d.y);
+
+    let (bdxcdy, cdxbdy) = (bdx * cdy, cdx * bdy);
+    let (cdxady, adxcdy) = (cdx * ady, adx * cdy);
+    let (adxbdy, bdxady) = (adx * bdy, bdx * ady);
+    let alift = adx * adx + ady * ady;
+    let blift = bdx * bdx + bdy * bdy;
+    let clift = cdx * cdx + cdy * cdy;
+    let det = alift * (bdxcdy - cdxbdy) + blift * (cdxady - adxcdy) + clift * (adxbdy - bdxady);
+    let permanent = (bdxcdy.abs() + cdxbdy.abs()) * alift + (cdxady.abs() + adxcdy.abs()) * blift + (adxbdy.abs() + bdxady.abs()) * clift;
+    if det.abs() > ICC_ERRBOUND_A * permanent {
+        return det;
+    }
+
+    let (adx, ady) = (difference(a.x, d.x), difference(a.y, d.y));
+    let (bdx, bdy) = (difference(b.x, d.x), difference(b.y, d.y));
+    let (cdx, cdy) = (difference(c.x, d.x), difference(c.y, d.y));
+    let lift = |x: &[f64], y: &[f64]| sum(&product(x, x), &product(y, y));
+    let a_term = product(&lift(&adx, &ady), &diff(&product(&bdx, &cdy), &product(&cdx, &bdy)));
+    let b_term = product(&lift(&bdx, &bdy), &diff(&product(&cdx, &ady), &product(&adx, &cdy)));
+    let c_term = product(&lift(&cdx, &cdy), &diff(&product(&adx, &bdy), &product(&bdx, &ady)));
+    sign(&sum(&sum(&a_term, &b_term), &c_term))
+}
+
+/// Lifted determinant for the in-sphere test, positive if `e` lies inside the sphere through `a`, `b`, `c`, `d` when `orient3d(a, b, c, d)` is `CCW`, with a correct sign.
+fn insphere_det(a: Vector3D, b: Vector3D, c: Vector3D, d: Vector3D, e: Vector3D) -> f64 {
+    let [ae, be, ce, de] = [a - e, b - e, c - e, d - e];
+
+    let minor = |p: &Vector3D, q: &Vector3D| (p.x * q.y, q.x * p.y);
+    let (aebe, beae) = minor(&ae, &be);
+    let (bece, cebe) = minor(&be, &ce);
+    let (cede, dece) = minor(&ce, &de);
+    let (deae, aede) = minor(&de, &ae);
+    let (aece, ceae) = minor(&ae, &ce);
+    let (bede, debe) = minor(&be, &de);
+    let (ab, bc, cd, da, ac, bd) = (aebe - beae, bece - cebe, cede - dece, deae - aede, aece - ceae, bede - debe);
+    let (ab_p, bc_p, cd_p, da_p, ac_p, bd_p) = (
+        aebe.abs() + beae.abs(),
+        bece.abs() + cebe.abs(),
+        cede.abs() + dece.abs(),
+        deae.abs() + aede.abs(),
+        aece.abs() + ceae.abs(),
+        bede.abs() + debe.abs(),
+    );
+
+    let abc = ae.z * bc - be.z * ac + ce.z * ab;
+    let bcd = be.z * cd - ce.z * bd + de.z * bc;
+    let cda = ce.z * da + de.z * ac + ae.z * cd;
+    let dab = de.z * ab + ae.z * bd + be.z * da;
+    let abc_p = ae.z.abs() * bc_p + be.z.abs() * ac_p + ce.z.abs() * ab_p;
+    let bcd_p = be.z.abs() * cd_p + ce.z.abs() * bd_p + de.z.abs() * bc_p;
+    let cda_p = ce.z.abs() * da_p + de.z.abs() * ac_p + ae.z.abs() * cd_p;
+    let dab_p = de.z.abs() * ab_p + ae.z.abs() * bd_p + be.z.abs() * da_p;
+
+    let [alift, blift, clift, dlift] = [ae, be, ce, de].map(|p| p.x * p.x + p.y * p.y + p.z * p.z);
+    let det = (dlift * abc - clift * dab) + (blift * cda - alift * bcd);
+    let permanent = dlift * abc_p + clift * dab_p + blift * cda_p + alift * bcd_p;
+    if det.abs() > ISP_ERRBOUND_A * permanent {
+        return -det;
+    }
+
+    let [ae, be, ce, de] = [a, b, c, d].map(|p| [0, 1, 2].map(|i| difference(p[i], e[i])));
+    let minor = |p: &[Expansion; 3], q: &[Expansion; 3]| diff(&product(&p[0], &q[1]), &product(&q[0], &p[1]));
+    let (ab, bc, cd, da, ac, bd) = (
+        minor(&ae, &be),
+        minor(&be, &ce),
+        minor(&ce, &de),
+        minor(&de, &ae),
+        minor(&ae, &ce),
+        minor(&be, &de),
+    );
+    let abc = sum(&diff(&product(&ae[2], &bc), &product(&be[2], &ac)), &product(&ce[2], &ab));
+    let bcd = sum(&diff(&product(&be[2], &cd), &product(&ce[2], &bd)), &product(&de[2], &bc));
+    let cda = sum(&sum(&product(&ce[2], &da), &product(&de[2], &ac)), &product(&ae[2], &cd));
+    let dab = sum(&sum(&product(&de[2], &ab), &product(&ae[2], &bd)), &product(&be[2], &da));
+    let lift = |p: &[Expansion; 3]| sum(&sum(&product(&p[0], &p[0]), &product(&p[1], &p[1])), &product(&p[2], &p[2]));
+    let det = sum(
+        &diff(&product(&lift(&de), &abc), &product(&lift(&ce), &dab)),
+        &diff(&product(&lift(&be), &cda), &product(&lift(&ae), &bcd)),
+    );
+    -sign(&det)
+}
+
+/// Calculates the orientation of three points `a`, `b`, `c` in the plane, exactly.
+/// # Arguments
+/// * `a` - First point.
+/// * `b` - Second point.
+/// * `c` - Third point.
+/// # Returns
+/// * `Orientation` - `CCW` if `a`, `b`, `c` make a left turn, `CW` if they make a right turn, `C` if they are collinear.
+///
+/// # Source
+/// The determinant is first evaluated in floating point and accepted if it exceeds Shewchuk's forward error bound; otherwise it is recomputed exactly with floating-point expansions.
+/// The result is therefore consistent under any permutation of the arguments. For more details, see [Shewchuk (1997)](https://www.cs.cmu.edu/~quake/robust.html).
+///
+/// # Example
+/// ```
+/// use hutspot::geom::{Orientation, Vector2D};
+/// use hutspot::predicates::orient2d;
+/// use itertools::Itertools;
+///
+/// assert_eq!(orient2d(Vector2D::new(0., 0.), Vector2D::new(1., 0.), Vector2D::new(0., 1.)), Orientation::CCW);
+/// assert_eq!(orient2d(Vector2D::new(0., 0.), Vector2D::new(0., 1.), Vector2D::new(1., 0.)), Orientation::CW);
+/// assert_eq!(orient2d(Vector2D::new(0., 0.), Vector2D::new(1., 1.), Vector2D::new(3., 3.)), Orientation::C);
+///
+/// // Points that are (nearly) collinear with the line y = x, perturbed by a few ulps.
+/// let (b, c) = (Vector2D::new(12., 12.), Vector2D::new(24., 24.));
+/// for i in 0..32 {
+///     for j in 0..32 {
+///         let a = Vector2D::new(0.5 + f64::from(i) * f64::EPSILON, 0.5 + f64::from(j) * f64::EPSILON);
+///         let reference = orient2d(a, b, c);
+///         assert_eq!(reference == Orientation::C, i == j);
+///         for (p, (x, y, z)) in [a, b, c].into_iter().permutations(3).map(|p| (p[0], p[1], p[2])).enumerate() {
+///             let expected = if [0, 3, 4].contains(&p) { reference } else { reference.reversed() };
+///             assert_eq!(orient2d(x, y, z), expected);
+///         }
+///     }
+/// }
+/// ```
+#[must_use]
+pub fn orient2d(a: Vector2D, b: Vector2D, c: Vector2D) -> Orientation {
+    orientation_from_sign(orient2d_det(a, b, c))
+}
+
+/// Calculates the orientation of point `d` with respect to the plane through `a`, `b`, `c`, exactly.
+/// # Arguments
+/// * `a` - First point of the plane.
+/// * `b` - Second point of the plane.
+/// * `c` - Third point of the plane.
+/// * `d` - The point to test.
+/// # Returns
+/// * `Orientation` - `CCW` if `a`, `b`, `c` appear counterclockwise when seen from `d` (i.e. `d` lies on the side of `(b - a) x (c - a)`), `CW` if clockwise, `C` if the four points are coplanar.
+///
+/// # Source
+/// Adaptive evaluation of the scalar triple product `((b - a) x (c - a)) . (d - a)` with an exact fallback. For more details, see [Shewchuk (1997)](https://www.cs.cmu.edu/~quake/robust.html).
+///
+/// # Example
+/// ```
+/// use hutspot::geom::{Orientation, Vector3D};
+/// use hutspot::predicates::orient3d;
+///
+/// let (a, b, c) = (Vector3D::new(0., 0., 0.), Vector3D::new(1., 0., 0.), Vector3D::new(0., 1., 0.));
+/// assert_eq!(orient3d(a, b, c, Vector3D::new(0.3, 0.3, 1.)), Orientation::CCW);
+/// assert_eq!(orient3d(a, b, c, Vector3D::new(0.3, 0.3, -1.)), Orientation::CW);
+/// assert_eq!(orient3d(a, b, c, Vector3D::new(5., -7., 0.)), Orientation::C);
+///
+/// // A nearly degenerate configuration: `d` is on the plane x + y + z = 1 up to a few ulps.
+/// let (a, b, c) = (Vector3D::new(1., 0., 0.), Vector3D::new(0., 1., 0.), Vector3D::new(0., 0., 1.));
+/// for i in -4..=4 {
+///     let d = Vector3D::new(0.1, 0.7, 0.2 + f64::from(i) * f64::EPSILON);
+///     let reference = orient3d(a, b, c, d);
+///     // Swapping two points flips the orientation, cyclic shifts of an even permutation keep it.
+///     assert_eq!(orient3d(b, a, c, d), reference.reversed());
+///     assert_eq!(orient3d(a, b, d, c), reference.reversed());
+///     assert_eq!(orient3d(d, a, b, c), reference.reversed());
+///     assert_eq!(orient3d(b, c, a, d), reference);
+///     assert_eq!(orient3d(c, d, a, b), reference);
+/// }
+/// ```
+#[must_use]
+pub fn orient3d(a: Vector3D, b: Vector3D, c: Vector3D, d: Vector3D) -> Orientation {
+    orientation_from_sign(triple_product_det((b, a), (c, a), (d, a)))
+}
+
+/// Calculates the orientation of three points `a`, `b`, `c` seen along the direction `n`, exactly.
+/// # Arguments
+/// * `a` - First point.
+/// * `b` - Second point.
+/// * `c` - Third point.
+/// * `n` - The viewing direction (pointing towards the viewer).
+/// # Returns
+/// * `Orientation` - The sign of `((b - a) x (c - a)) . n` as an orientation.
+#[must_use]
+pub fn orient3d_along(a: Vector3D, b: Vector3D, c: Vector3D, n: Vector3D) -> Orientation {
+    orientation_from_sign(triple_product_det((b, a), (c, a), (n, Vector3D::zeros())))
+}
+
+/// Tests whether point `d` lies inside the circle through `a`, `b`, `c`, exactly.
+/// # Arguments
+/// * `a` - First point on the circle.
+/// * `b` - Second point on the circle.
+/// * `c` - Third point on the circle.
+/// * `d` - The point to test.
+/// # Returns
+/// * `Orientation` - The sign of the lifted determinant: `CCW` if `d` lies inside the circle and `a`, `b`, `c` are counterclockwise (or outside and clockwise),
+///   `CW` in the opposite cases, and `C` if the four points are cocircular (or `a`, `b`, `c` are collinear and `d` is on their line).
+///
+/// # Source
+/// Adaptive evaluation of the in-circle determinant with an exact fallback. For more details, see [Shewchuk (1997)](https://www.cs.cmu.edu/~quake/robust.html).
+///
+/// # Example
+/// ```
+/// use hutspot::geom::{Orientation, Vector2D};
+/// use hutspot::predicates::incircle;
+/// use itertools::Itertools;
+///
+/// let (a, b, c) = (Vector2D::new(1., 0.), Vector2D::new(0., 1.), Vector2D::new(-1., 0.));
+/// assert_eq!(incircle(a, b, c, Vector2D::new(0., 0.)), Orientation::CCW);
+/// assert_eq!(incircle(a, b, c, Vector2D::new(2., 2.)), Orientation::CW);
+/// assert_eq!(incircle(a, b, c, Vector2D::new(0., -1.)), Orientation::C);
+/// assert_eq!(incircle(c, b, a, Vector2D::new(0., 0.)), Orientation::CW);
+///
+/// // The determinant is alternating in all four points.
+/// let parity = |p: &[usize]| (0..p.len()).tuple_combinations().filter(|&(i, j)| p[i] > p[j]).count() % 2;
+/// for i in -4..=4 {
+///     let d = Vector2D::new(0., -1. + f64::from(i) * f64::EPSILON);
+///     let points = [a, b, c, d];
+///     let reference = incircle(a, b, c, d);
+///     assert_eq!(reference == Orientation::C, i == 0);
+///     for p in (0..4).permutations(4) {
+///         let expected = if parity(&p) == 0 { reference } else { reference.reversed() };
+///         assert_eq!(incircle(points[p[0]], points[p[1]], points[p[2]], points[p[3]]), expected);
+///     }
+/// }
+/// ```
+#[must_use]
+pub fn incircle(a: Vector2D, b: Vector2D, c: Vector2D, d: Vector2D) -> Orientation {
+    orientation_from_sign(incircle_det(a, b, c, d))
+}
+
+/// Tests whether point `e` lies inside the sphere through `a`, `b`, `c`, `d`, exactly.
+/// # Arguments
+/// * `a` - First point on the sphere.
+/// * `b` - Second point on the sphere.
+/// * `c` - Third point on the sphere.
+/// * `d` - Fourth point on the sphere.
+/// * `e` - The point to test.
+/// # Returns
+/// * `Orientation` - The sign of the lifted determinant: `CCW` if `e` lies inside the sphere and `orient3d(a, b, c, d)` is `CCW` (or outside and `CW`),
+///   `CW` in the opposite cases, and `C` if the five points are cospherical (or degenerate).
+///
+/// # Source
+/// Adaptive evaluation of the in-sphere determinant with an exact fallback. For more details, see [Shewchuk (1997)](https://www.cs.cmu.edu/~quake/robust.html).
+///
+/// # Example
+/// ```
+/// use hutspot::geom::{Orientation, Vector3D};
+/// use hutspot::predicates::{insphere, orient3d};
+/// use itertools::Itertools;
+///
+/// let (a, b, c, d) = (Vector3D::new(1., 0., 0.), Vector3D::new(0., 1., 0.), Vector3D::new(-1., 0., 0.), Vector3D::new(0., 0., 1.));
+/// assert_eq!(orient3d(a, b, c, d), Orientation::CCW);
+/// assert_eq!(insphere(a, b, c, d, Vector3D::new(0., 0., 0.)), Orientation::CCW);
+/// assert_eq!(insphere(a, b, c, d, Vector3D::new(2., 0., 0.)), Orientation::CW);
+/// assert_eq!(insphere(a, b, c, d, Vector3D::new(0., 0., -1.)), Orientation::C);
+///
+/// // The determinant is alternating in all five points.
+/// let parity = |p: &[usize]| (0..p.len()).tuple_combinations().filter(|&(i, j)| p[i] > p[j]).count() % 2;
+/// for i in -2..=2 {
+///     let e = Vector3D::new(0., -1. + f64::from(i) * f64::EPSILON, 0.);
+///     let points = [a, b, c, d, e];
+///     let reference = insphere(a, b, c, d, e);
+///     assert_eq!(reference == Orientation::C, i == 0);
+///     for p in (0..5).permutations(5) {
+///         let expected = if parity(&p) == 0 { reference } else { reference.reversed() };
+///         assert_eq!(insphere(points[p[0]], points[p[1]], points[p[2]], points[p[3]], points[p[4]]), expected);
+///     }
+/// }
+/// ```
+#[must_use]
+pub fn insphere(a: Vector3D, b: Vector3D, c: Vector3D, d: Vector3D, e: Vector3D) -> Orientation {
+    orientation_from_sign(insphere_det(a, b, c, d, e))
+}